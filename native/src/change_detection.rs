@@ -0,0 +1,259 @@
+use crate::file_locator::FileLocator;
+use crate::parser::TypeScriptParser;
+use std::collections::HashMap;
+use swc_ecma_ast::Module;
+
+/// A reason a component cannot simply switch to `OnPush`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnPushBlocker {
+    /// e.g. `ngOnInit mutates this.items`
+    pub reason: String,
+    /// 1-based line of the offending input or write
+    pub line: u32,
+}
+
+/// Change detection cost of a component measured by the runtime probe
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RuntimeCost {
+    pub change_detection_count: u32,
+    pub duration_ms: f64,
+}
+
+/// A component using default change detection, assessed for `OnPush`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnPushCandidate {
+    pub file_path: String,
+    pub class_name: String,
+    /// 1-based line of the class
+    pub line: u32,
+    /// Empty when the component can switch to `OnPush` as is
+    pub blockers: Vec<OnPushBlocker>,
+    pub runtime: Option<RuntimeCost>,
+}
+
+/// Assess every default change detection component of the workspace for
+/// `OnPush`
+///
+/// # Arguments
+/// * `locator` - The locator of the workspace to scan
+/// * `costs` - Runtime change detection cost by component class name
+///
+/// # Returns
+/// Components without blockers first, each group by decreasing runtime cost
+/// and then by path. Files that fail to parse are skipped.
+pub fn find_on_push_candidates(
+    locator: &FileLocator,
+    costs: &HashMap<String, RuntimeCost>,
+) -> Result<Vec<OnPushCandidate>, std::io::Error> {
+    let mut candidates = Vec::new();
+
+    for path in locator.typescript_files() {
+        let path = path?;
+        let Some(content) = locator.read_source(&path) else {
+            continue;
+        };
+
+        // Cheap textual pre-filter before parsing
+        if !content.contains("@Component") {
+            continue;
+        }
+
+        // Fresh parser per file, so its source map only ever holds this one
+        let parser = TypeScriptParser::new();
+        let Ok(module) = parser.parse_module(&content) else {
+            continue;
+        };
+        let components = assess_components(&parser, &module);
+        let file_path = path.to_string_lossy().to_string();
+        candidates.extend(components.into_iter().map(|(class_name, line, blockers)| {
+            OnPushCandidate {
+                file_path: file_path.clone(),
+                runtime: costs.get(&class_name).copied(),
+                class_name,
+                line,
+                blockers,
+            }
+        }));
+    }
+
+    candidates.sort_by(|a, b| {
+        let duration = |candidate: &OnPushCandidate| {
+            candidate
+                .runtime
+                .map_or(f64::NEG_INFINITY, |runtime| runtime.duration_ms)
+        };
+        a.blockers
+            .is_empty()
+            .cmp(&b.blockers.is_empty())
+            .reverse()
+            .then(duration(b).total_cmp(&duration(a)))
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+    Ok(candidates)
+}
+
+/// Find the blockers of the default change detection components of a file
+///
+/// An input blocks when its declared type is mutable in place (arrays,
+/// maps, sets, object literal types), since `OnPush` only reacts to new
+/// references. A lifecycle hook other than `ngOnDestroy` blocks when it
+/// writes component state, directly or from a callback.
+///
+/// # Returns
+/// Class name, class line and blockers of each component
+fn assess_components(
+    parser: &TypeScriptParser,
+    module: &Module,
+) -> Vec<(String, u32, Vec<OnPushBlocker>)> {
+    let mut components = Vec::new();
+
+    for component in parser.find_change_detection(module) {
+        if component
+            .strategy
+            .as_deref()
+            .is_some_and(|strategy| strategy != "Default")
+        {
+            continue;
+        }
+        let class_name = component.class_name;
+        let mut blockers = Vec::new();
+
+        for binding in parser
+            .find_component_bindings_in_module(module, &class_name)
+            .unwrap_or_default()
+        {
+            if let Some(type_text) = binding.type_text.filter(|text| is_mutable_type(text)) {
+                blockers.push(OnPushBlocker {
+                    reason: format!("input {} has mutable type {}", binding.name, type_text),
+                    line: binding.line,
+                });
+            }
+        }
+
+        for hook in parser
+            .find_lifecycle_hooks_in_module(module, &class_name)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|hook| hook.name != "ngOnDestroy")
+        {
+            for mutation in parser
+                .find_state_mutations(module, &class_name, hook.name)
+                .unwrap_or_default()
+            {
+                blockers.push(OnPushBlocker {
+                    reason: format!("{} mutates this.{}", hook.name, mutation.member),
+                    line: mutation.line,
+                });
+            }
+        }
+
+        blockers.sort_by_key(|blocker| blocker.line);
+        components.push((class_name, component.span.line, blockers));
+    }
+
+    components
+}
+
+/// Check whether a declared type can be changed in place, in any member of
+/// a union
+fn is_mutable_type(type_text: &str) -> bool {
+    type_text.split('|').map(str::trim).any(|member| {
+        let readonly = [
+            "readonly ",
+            "Readonly<",
+            "ReadonlyArray<",
+            "ReadonlyMap<",
+            "ReadonlySet<",
+        ]
+        .iter()
+        .any(|prefix| member.starts_with(prefix));
+        let mutable = member.ends_with("[]")
+            || ["Array<", "Map<", "Set<", "{"]
+                .iter()
+                .any(|prefix| member.starts_with(prefix))
+            || member == "object";
+        mutable && !readonly
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_workspace;
+
+    #[test]
+    fn test_is_mutable_type() {
+        assert!(is_mutable_type("Item[]"));
+        assert!(is_mutable_type("Array<Item> | null"));
+        assert!(is_mutable_type("{ id: string }"));
+        assert!(!is_mutable_type("readonly Item[]"));
+        assert!(!is_mutable_type("ReadonlyArray<Item>"));
+        assert!(!is_mutable_type("string | undefined"));
+        assert!(!is_mutable_type("User"));
+    }
+
+    #[test]
+    fn test_find_on_push_candidates() {
+        let workspace = temp_workspace("on-push");
+        std::fs::write(
+            workspace.join("list.component.ts"),
+            r#"
+@Component({ selector: 'app-list' })
+export class ListComponent implements OnInit {
+    @Input() items: Item[] = [];
+
+    ngOnInit() {
+        this.store.select(selectUser).subscribe(user => this.user = user);
+    }
+}
+
+@Component({ selector: 'app-badge' })
+export class BadgeComponent {
+    @Input() label: string;
+    count = input(0);
+
+    ngOnDestroy() {
+        this.label = '';
+    }
+}
+
+@Component({ selector: 'app-card' })
+export class CardComponent {}
+
+@Component({ selector: 'app-fast', changeDetection: ChangeDetectionStrategy.OnPush })
+export class FastComponent {}
+"#,
+        )
+        .unwrap();
+
+        let costs = HashMap::from([(
+            "CardComponent".to_string(),
+            RuntimeCost {
+                change_detection_count: 120,
+                duration_ms: 35.5,
+            },
+        )]);
+        let candidates = find_on_push_candidates(&FileLocator::new(&workspace), &costs).unwrap();
+
+        let names: Vec<_> = candidates.iter().map(|c| c.class_name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["CardComponent", "BadgeComponent", "ListComponent"]
+        );
+        assert_eq!(candidates[0].runtime.unwrap().change_detection_count, 120);
+        assert!(candidates[1].blockers.is_empty());
+
+        let blockers: Vec<_> = candidates[2]
+            .blockers
+            .iter()
+            .map(|blocker| (blocker.reason.as_str(), blocker.line))
+            .collect();
+        assert_eq!(
+            blockers,
+            vec![
+                ("input items has mutable type Item[]", 4),
+                ("ngOnInit mutates this.user", 7),
+            ]
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Directory (relative to the workspace root) where the index is persisted
+pub const INDEX_DIR: &str = ".xray/index";
+const INDEX_FILE: &str = "classes.json";
+const INDEX_VERSION: u32 = 1;
+
+/// Indexed information about a single TypeScript file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexedFile {
+    /// Modification time in nanoseconds since the Unix epoch
+    modified: u64,
+    classes: Vec<String>,
+}
+
+/// Persistent class-name → file-path index for a workspace
+///
+/// The index is stored as JSON under `.xray/index` and refreshed
+/// incrementally: only files whose mtime changed since the last refresh are
+/// re-read, and files that disappeared are dropped.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassIndex {
+    version: u32,
+    files: BTreeMap<String, IndexedFile>,
+    #[serde(skip)]
+    index_path: PathBuf,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl ClassIndex {
+    /// Load the index for a workspace, or start an empty one if none exists
+    /// (or the stored one cannot be read)
+    pub fn load<P: AsRef<Path>>(workspace_path: P) -> Self {
+        let index_path = workspace_path.as_ref().join(INDEX_DIR).join(INDEX_FILE);
+
+        let loaded = fs::read_to_string(&index_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<ClassIndex>(&content).ok())
+            .filter(|index| index.version == INDEX_VERSION);
+
+        match loaded {
+            Some(mut index) => {
+                index.index_path = index_path;
+                index
+            }
+            None => Self {
+                version: INDEX_VERSION,
+                files: BTreeMap::new(),
+                index_path,
+                // Force a write so the next call can reuse the index
+                dirty: true,
+            },
+        }
+    }
+
+    /// Bring the index up to date with the given set of files
+    ///
+    /// Files with an unchanged mtime keep their cached classes; new or modified
+    /// files are re-read, and entries for files not in `paths` are removed.
+    ///
+    /// Changed files are read with `read`; files it declines (e.g. too large
+    /// or binary) are indexed without classes.
+    ///
+    /// If `paths` yields an error the refresh stops there: files visited so
+    /// far are up to date and the rest keep their previous entries.
+    pub fn refresh<I, R, S>(&mut self, paths: I, read: R) -> io::Result<()>
+    where
+        I: IntoIterator<Item = io::Result<PathBuf>>,
+        R: Fn(&Path) -> Option<S>,
+        S: std::ops::Deref<Target = str>,
+    {
+        let mut seen: BTreeMap<String, IndexedFile> = BTreeMap::new();
+
+        for path in paths {
+            let path = match path {
+                Ok(path) => path,
+                Err(e) => {
+                    self.files.append(&mut seen);
+                    return Err(e);
+                }
+            };
+            let key = path.to_string_lossy().to_string();
+            let modified = match modified_nanos(&path) {
+                Some(modified) => modified,
+                None => continue,
+            };
+
+            match self.files.remove(&key) {
+                Some(entry) if entry.modified == modified => {
+                    seen.insert(key, entry);
+                }
+                _ => {
+                    let classes = read(&path)
+                        .map(|content| extract_class_names(&content))
+                        .unwrap_or_default();
+                    seen.insert(key, IndexedFile { modified, classes });
+                    self.dirty = true;
+                }
+            }
+        }
+
+        // Anything left over was deleted (or is now excluded)
+        if !self.files.is_empty() {
+            self.dirty = true;
+        }
+        self.files = seen;
+
+        Ok(())
+    }
+
+    /// Look up every file (in path order) that declares the class
+    pub fn lookup_all(&self, class_name: &str) -> Vec<&str> {
+        self.files
+            .iter()
+            .filter(|(_, entry)| entry.classes.iter().any(|c| c == class_name))
+            .map(|(path, _)| path.as_str())
+            .collect()
+    }
+
+    /// Iterate over indexed files and the classes they declare
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.files
+            .iter()
+            .map(|(path, entry)| (path.as_str(), entry.classes.as_slice()))
+    }
+
+    /// Persist the index to disk if it changed since it was loaded
+    pub fn save(&mut self) -> io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if let Some(parent) = self.index_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let json = serde_json::to_string(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(&self.index_path, json)?;
+        self.dirty = false;
+
+        Ok(())
+    }
+}
+
+/// Get a file's modification time in nanoseconds since the Unix epoch
+fn modified_nanos(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    let duration = modified.duration_since(UNIX_EPOCH).ok()?;
+    Some(duration.as_nanos() as u64)
+}
+
+/// Extract the names of all classes declared in the file content
+///
+/// Mirrors the lightweight pattern matching used by the locator: any `class`
+/// keyword followed by an identifier counts as a declaration.
+pub fn extract_class_names(content: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let bytes = content.as_bytes();
+    let mut search_from = 0;
+
+    while let Some(offset) = content[search_from..].find("class") {
+        let start = search_from + offset;
+        let end = start + "class".len();
+        search_from = end;
+
+        // `class` must be a standalone keyword
+        let preceded_ok = start == 0 || !is_ident_byte(bytes[start - 1]);
+        let followed_ok = bytes.get(end).is_some_and(|b| b.is_ascii_whitespace());
+        if !preceded_ok || !followed_ok {
+            continue;
+        }
+
+        let rest = content[end..].trim_start();
+        let name: String = rest
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+            .collect();
+
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    names
+}
+
+pub(crate) fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_workspace;
+
+    fn read(path: &Path) -> Option<String> {
+        fs::read_to_string(path).ok()
+    }
+
+    #[test]
+    fn test_extract_class_names() {
+        let content = r#"
+@Component({ selector: 'app-root' })
+export class AppComponent {}
+
+class Helper extends Base {}
+const subclass = 1;
+"#;
+        assert_eq!(extract_class_names(content), vec!["AppComponent", "Helper"]);
+    }
+
+    #[test]
+    fn test_refresh_and_persist() {
+        let workspace = temp_workspace("index-persist");
+        let file = workspace.join("app.ts");
+        fs::write(&file, "export class AppComponent {}").unwrap();
+
+        let mut index = ClassIndex::load(&workspace);
+        index.refresh(vec![Ok(file.clone())], read).unwrap();
+        index.save().unwrap();
+        assert_eq!(
+            index.lookup_all("AppComponent"),
+            vec![file.to_str().unwrap()]
+        );
+
+        let mut reloaded = ClassIndex::load(&workspace);
+        assert!(!reloaded.dirty);
+        assert_eq!(
+            reloaded.lookup_all("AppComponent"),
+            vec![file.to_str().unwrap()]
+        );
+
+        // Deleted files drop out of the index
+        reloaded.refresh(Vec::new(), read).unwrap();
+        assert!(reloaded.dirty);
+        assert!(reloaded.lookup_all("AppComponent").is_empty());
+    }
+
+    #[test]
+    fn test_interrupted_refresh_keeps_entries() {
+        let workspace = temp_workspace("index-interrupted");
+        let first = workspace.join("a.ts");
+        let second = workspace.join("b.ts");
+        fs::write(&first, "export class A {}").unwrap();
+        fs::write(&second, "export class B {}").unwrap();
+
+        let mut index = ClassIndex::load(&workspace);
+        index
+            .refresh(vec![Ok(first.clone()), Ok(second.clone())], read)
+            .unwrap();
+
+        let interrupted = vec![
+            Ok(first.clone()),
+            Err(io::Error::new(io::ErrorKind::TimedOut, "timeout")),
+        ];
+        assert!(index.refresh(interrupted, read).is_err());
+        assert_eq!(index.lookup_all("B"), vec![second.to_str().unwrap()]);
+    }
+}
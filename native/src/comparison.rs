@@ -1,5 +1,4 @@
 use napi::bindgen_prelude::*;
-use napi_derive::napi;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -8,7 +7,6 @@ use std::collections::HashMap;
 #[serde(rename_all = "camelCase")]
 struct MethodData {
     average_duration: f64,
-    executions: Vec<f64>,
 }
 
 /// Comparison result for a single method
@@ -1,75 +1,1537 @@
+use crate::class_index::{extract_class_names, ClassIndex};
+use crate::fuzzy;
+use crate::match_cache::{self, content_hash};
+use crate::matcher::{on_ident_boundaries, ClassMatcher, DeclarationMatcher};
+use crate::node_modules::installed_packages;
+use crate::parser::{
+    ClassSummary, DeclarationSpan, ReExport, TypeScriptParser, UnmanagedSubscription,
+};
+use crate::projects::{ProjectScope, WorkspaceProjects};
+use crate::tsconfig::TsConfig;
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::WalkBuilder;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+/// A file declaring the searched class, with a confidence score in `0.0..=1.0`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassCandidate {
+    pub path: String,
+    pub score: f64,
+    /// Path heuristics that raised or lowered the score
+    pub reasons: Vec<String>,
+}
+
+/// A class whose name resembles the searched one
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyCandidate {
+    pub path: String,
+    pub class_name: String,
+    /// Name similarity in `0.0..=1.0`; `1.0` is an exact match
+    pub score: f64,
+}
+
+/// Similarity below which fuzzy candidates are dropped
+pub const DEFAULT_FUZZY_THRESHOLD: f64 = 0.6;
+
+/// Extensions of TypeScript sources, including ES module / CommonJS variants
+const TYPESCRIPT_EXTENSIONS: &[&str] = &["ts", "tsx", "mts", "cts"];
+
+/// Directories skipped by every search unless the skip list is replaced
+pub const DEFAULT_SKIP_DIRS: &[&str] = &[
+    "node_modules",
+    "dist",
+    "out",
+    "build",
+    ".git",
+    ".vscode",
+    "target",
+    "coverage",
+];
+
+/// Directory depth cap applied when following symlinks without an explicit
+/// `max_depth`
+pub const DEFAULT_SYMLINK_DEPTH: usize = 32;
+
+/// Maximum number of barrel hops followed when resolving re-exports
+const MAX_REEXPORT_DEPTH: usize = 16;
+
+/// Directory names that hold tests rather than application code
+const TEST_DIRS: &[&str] = &["test", "tests", "__tests__", "testing", "e2e"];
+
+/// Kinds of top-level symbols the locator can search for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Class,
+    Interface,
+    Enum,
+    TypeAlias,
+    Function,
+    Const,
+}
+
+impl SymbolKind {
+    /// Parse the kind name used across the FFI boundary
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "class" => Some(Self::Class),
+            "interface" => Some(Self::Interface),
+            "enum" => Some(Self::Enum),
+            "type" | "typeAlias" => Some(Self::TypeAlias),
+            "function" => Some(Self::Function),
+            "const" => Some(Self::Const),
+            _ => None,
+        }
+    }
+
+    /// Keywords that introduce a declaration of this kind
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            Self::Class => &["class"],
+            Self::Interface => &["interface"],
+            Self::Enum => &["enum"],
+            Self::TypeAlias => &["type"],
+            Self::Function => &["function", "function*"],
+            // Only exported consts are addressable from other modules
+            Self::Const => &["export const"],
+        }
+    }
+}
+
+/// Snapshot of a running workspace scan
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanProgress {
+    pub files_scanned: usize,
+    pub current_directory: String,
+    pub matches: usize,
+    /// Files skipped for exceeding the size limit
+    pub skipped_large: usize,
+    /// Files skipped because their content is not text
+    pub skipped_binary: usize,
+}
+
+/// Statistics of a finished lookup, for tuning large workspaces
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScanStats {
+    /// Files visited by the workspace walk
+    pub files_visited: usize,
+    /// Files whose content was read
+    pub files_read: usize,
+    pub bytes_read: u64,
+    /// Files re-read to bring the class index up to date
+    pub files_reindexed: usize,
+    /// Match answers served from the match cache
+    pub cache_hits: usize,
+    /// Match answers that required reading the file
+    pub cache_misses: usize,
+    pub elapsed: Duration,
+}
+
+impl ScanStats {
+    /// Share of match answers served from cache, `0.0` when nothing was asked
+    pub fn cache_hit_rate(&self) -> f64 {
+        let lookups = self.cache_hits + self.cache_misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.cache_hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Callback receiving periodic scan progress
+#[derive(Clone)]
+pub struct ProgressReporter(pub Arc<dyn Fn(&ScanProgress) + Send + Sync>);
+
+impl std::fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressReporter")
+    }
+}
+
+/// Number of files between two progress reports
+const PROGRESS_INTERVAL: usize = 200;
+
+/// Files larger than this are skipped unless configured otherwise (2 MiB)
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Number of leading bytes checked for NUL when detecting binary content
+const BINARY_SNIFF_LEN: usize = 8000;
+
+/// Tunable behavior of the locator
+#[derive(Debug, Clone, Default)]
+pub struct LocatorConfig {
+    /// Parse candidate files to confirm a real class declaration exists,
+    /// rejecting matches inside comments or strings
+    pub verify_ast: bool,
+    /// Include/exclude globs relative to the workspace root (e.g. `src/**`,
+    /// `!**/*.spec.ts`). When any include glob is present, only matching
+    /// files are searched.
+    pub globs: Vec<String>,
+    /// Flag checked during the walk; once set, the search stops with an
+    /// `Interrupted` error
+    pub cancellation: Option<Arc<AtomicBool>>,
+    /// Receives progress every few hundred files and once when done
+    pub progress: Option<ProgressReporter>,
+    /// Project name, or a path inside the project, from `angular.json` /
+    /// Nx configuration used to scope the search
+    pub project: Option<String>,
+    /// Whether the project restricts or only prioritizes the search
+    pub project_scope: ProjectScope,
+    /// Skip files larger than this many bytes; `None` uses
+    /// [`DEFAULT_MAX_FILE_SIZE`]
+    pub max_file_size: Option<u64>,
+    /// Also search ambient declaration files (`.d.ts`, `.d.mts`, `.d.cts`)
+    pub scan_declaration_files: bool,
+    /// Persist class match answers under `.xray/index` so they survive
+    /// restarts; they are always cached in memory
+    pub persist_match_cache: bool,
+    /// Maximum number of candidate files examined (or returned) per class
+    pub max_results: Option<usize>,
+    /// Give up on class lookups after this long, returning what was found
+    pub timeout: Option<Duration>,
+    /// Fall back to searching installed packages' typings and `fesm` bundles
+    /// when the workspace sources do not declare the class
+    pub search_node_modules: bool,
+    /// Follow symlinked files and directories (e.g. pnpm workspace packages);
+    /// each real directory is visited once, which also breaks cycles
+    pub follow_symlinks: bool,
+    /// Maximum directory depth below the search root; defaults to
+    /// [`DEFAULT_SYMLINK_DEPTH`] when following symlinks, unlimited otherwise
+    pub max_depth: Option<usize>,
+    /// Directory names to skip, replacing [`DEFAULT_SKIP_DIRS`]
+    pub skip_dirs: Option<Vec<String>>,
+    /// Directory names skipped on top of the skip list (e.g. `storybook-static`)
+    pub extra_skip_dirs: Vec<String>,
+}
+
+/// Filters applied to index lookups
+struct SearchFilter {
+    overrides: Override,
+    /// Resolved project search root and how it applies
+    project: Option<(PathBuf, ProjectScope)>,
+}
+
+/// Counters accumulated while a locator walks the workspace
+#[derive(Debug, Default)]
+struct ScanCounters {
+    files_scanned: Cell<usize>,
+    matches: Cell<usize>,
+    skipped_large: Cell<usize>,
+    skipped_binary: Cell<usize>,
+    files_read: Cell<usize>,
+    bytes_read: Cell<u64>,
+    files_reindexed: Cell<usize>,
+    cache_hits: Cell<usize>,
+    cache_misses: Cell<usize>,
+    /// When the running lookup started
+    started: Cell<Option<Instant>>,
+    /// When the running lookup has to stop
+    deadline: Cell<Option<Instant>>,
+    /// Set when a limit cut a lookup short
+    truncated: Cell<bool>,
+}
 
 pub struct FileLocator {
     workspace_path: PathBuf,
+    config: LocatorConfig,
+    counters: ScanCounters,
 }
 
-impl FileLocator {
-    pub fn new<P: AsRef<Path>>(workspace_path: P) -> Self {
-        Self {
-            workspace_path: workspace_path.as_ref().to_path_buf(),
+impl FileLocator {
+    pub fn new<P: AsRef<Path>>(workspace_path: P) -> Self {
+        Self {
+            workspace_path: workspace_path.as_ref().to_path_buf(),
+            config: LocatorConfig::default(),
+            counters: ScanCounters {
+                started: Cell::new(Some(Instant::now())),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Use the given configuration instead of the defaults
+    pub fn with_config(mut self, config: LocatorConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Find a TypeScript file containing the specified class
+    ///
+    /// Uses the persistent class index under `.xray/index`, which is updated
+    /// incrementally so only files modified since the last lookup are re-read.
+    pub fn find_class(&self, class_name: &str) -> Result<Option<String>, std::io::Error> {
+        self.start_deadline();
+        let index = self.load_index()?;
+        let filter = self.search_filter()?;
+
+        self.load_match_cache();
+        let mut path = self.lookup_class(&index, &filter, class_name);
+        self.save_match_cache();
+
+        if path.is_none() && self.config.search_node_modules {
+            path = self.find_library_class(class_name);
+        }
+        self.report_progress(&self.workspace_path);
+
+        Ok(path)
+    }
+
+    /// Look for a class in the given directories and their sibling
+    /// directories, without walking or indexing the workspace
+    ///
+    /// Only the files directly inside each directory are checked. Globs,
+    /// project scope and the skip list apply as for a full search. Hits must
+    /// declare the class under its exact name, as index lookups do, so a
+    /// `UserService` nearby does not answer for `User`.
+    pub fn find_class_near(
+        &self,
+        class_name: &str,
+        directories: &[PathBuf],
+    ) -> Result<Option<String>, std::io::Error> {
+        self.start_deadline();
+        let filter = self.search_filter()?;
+        let mut searched = HashSet::new();
+
+        for directory in directories {
+            let siblings = directory
+                .parent()
+                .filter(|parent| parent.starts_with(&self.workspace_path))
+                .map(sorted_entries)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|path| path.is_dir());
+
+            for neighbour in std::iter::once(directory.clone()).chain(siblings) {
+                if !searched.insert(neighbour.clone()) {
+                    continue;
+                }
+
+                for path in sorted_entries(&neighbour) {
+                    if self.is_timed_out() {
+                        return Ok(None);
+                    }
+
+                    let in_scope = match &filter.project {
+                        Some((root, ProjectScope::Restrict)) => path.starts_with(root),
+                        _ => true,
+                    };
+                    if path.is_file()
+                        && in_scope
+                        && self.is_searchable(&path)
+                        && Self::is_selected(&filter.overrides, &path)
+                        && self.accepts_declaration_file(&path)
+                        && self.file_matches_class(&path, class_name)
+                        && self.declares_class(&path, class_name)
+                    {
+                        return Ok(Some(path.to_string_lossy().to_string()));
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Search the packages under `node_modules` for a class declaration
+    ///
+    /// Each package's `.d.ts` files are tried before its `fesm` bundles, so
+    /// the public declaration wins over the compiled implementation.
+    fn find_library_class(&self, class_name: &str) -> Option<String> {
+        for package in installed_packages(&self.workspace_path) {
+            let files = package
+                .declaration_files()
+                .into_iter()
+                .chain(package.bundle_files());
+
+            for file in files {
+                if self.is_timed_out() {
+                    return None;
+                }
+
+                if let Some(content) = self.read_source(&file) {
+                    if self.matches_class(&content, class_name) {
+                        return Some(file.to_string_lossy().to_string());
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Find the files for many classes with a single workspace traversal
+    ///
+    /// Results are returned in the same order as `class_names`.
+    pub fn find_classes(
+        &self,
+        class_names: &[String],
+    ) -> Result<Vec<Option<String>>, std::io::Error> {
+        self.start_deadline();
+        let index = self.load_index()?;
+        let filter = self.search_filter()?;
+
+        self.load_match_cache();
+        self.prime_match_cache(&index, &filter, class_names);
+        let paths = class_names
+            .iter()
+            .map(|class_name| self.lookup_class(&index, &filter, class_name))
+            .collect();
+        self.save_match_cache();
+
+        Ok(paths)
+    }
+
+    /// Resolve a class against an up-to-date index
+    fn lookup_class(
+        &self,
+        index: &ClassIndex,
+        filter: &SearchFilter,
+        class_name: &str,
+    ) -> Option<String> {
+        for path in self.limited(self.candidate_paths(index, filter, class_name)) {
+            if self.is_timed_out() {
+                break;
+            }
+
+            // Confirm against the current content in case the file changed
+            // within the mtime resolution of the filesystem
+            if self.file_matches_class(Path::new(path), class_name) {
+                return Some(path.to_string());
+            }
+        }
+
+        None
+    }
+
+    /// Answer every uncached (file, class) pair of a batch with one read and
+    /// one multi-pattern scan per file
+    ///
+    /// The per-class lookups that follow are then served from the match cache.
+    fn prime_match_cache(&self, index: &ClassIndex, filter: &SearchFilter, class_names: &[String]) {
+        let mut targets_by_path: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (target, class_name) in class_names.iter().enumerate() {
+            for path in self.candidate_paths(index, filter, class_name) {
+                targets_by_path.entry(path).or_default().push(target);
+            }
+        }
+
+        let matcher = ClassMatcher::new(class_names);
+        for (path, targets) in targets_by_path {
+            if self.is_timed_out() {
+                break;
+            }
+
+            let path = Path::new(path);
+            let Some((modified, len)) = file_stamp(path) else {
+                continue;
+            };
+            let path_key = path.to_string_lossy();
+
+            let pending: Vec<usize> = {
+                let cache = match_cache::shared();
+                targets
+                    .into_iter()
+                    .filter(|&target| {
+                        let key = self.cache_key(&class_names[target]);
+                        let cached = cache.lookup(&path_key, modified, len, &key).is_some();
+                        self.count_cache_lookup(cached);
+                        !cached
+                    })
+                    .collect()
+            };
+            if pending.is_empty() {
+                continue;
+            }
+
+            let Some(content) = self.read_source(path) else {
+                continue;
+            };
+            let matched = matcher.matched(&content);
+            let hash = content_hash(content.as_bytes());
+
+            for target in pending {
+                let class_name = &class_names[target];
+                let answer = matched[target] && self.verify_class(&content, class_name);
+                let key = self.cache_key(class_name);
+                match_cache::shared().record(&path_key, modified, len, hash, &key, answer);
+            }
+        }
+    }
+
+    /// Match cache key for a class; verified and textual answers can differ
+    /// for the same file
+    fn cache_key(&self, class_name: &str) -> String {
+        if self.config.verify_ast {
+            format!("ast:{}", class_name)
+        } else {
+            class_name.to_string()
+        }
+    }
+
+    /// Check a file for the class, answering from the match cache while the
+    /// file's mtime and size are unchanged
+    fn file_matches_class(&self, path: &Path, class_name: &str) -> bool {
+        let key = self.cache_key(class_name);
+        let path_key = path.to_string_lossy();
+        let stamp = file_stamp(path);
+
+        if let Some((modified, len)) = stamp {
+            let cached = match_cache::shared().lookup(&path_key, modified, len, &key);
+            self.count_cache_lookup(cached.is_some());
+            if let Some(answer) = cached {
+                if answer {
+                    self.counters.matches.set(self.counters.matches.get() + 1);
+                }
+                return answer;
+            }
+        }
+
+        let Some(content) = self.read_source(path) else {
+            return false;
+        };
+        let answer = self.matches_class(&content, class_name);
+
+        if let Some((modified, len)) = stamp {
+            let hash = content_hash(content.as_bytes());
+            match_cache::shared().record(&path_key, modified, len, hash, &key, answer);
+        }
+
+        answer
+    }
+
+    /// Check that a file declares the class under exactly this name
+    fn declares_class(&self, path: &Path, class_name: &str) -> bool {
+        self.read_source(path).is_some_and(|content| {
+            extract_class_names(&content)
+                .iter()
+                .any(|name| name == class_name)
+        })
+    }
+
+    /// Merge the persisted match cache, when enabled
+    fn load_match_cache(&self) {
+        if self.config.persist_match_cache {
+            match_cache::shared().load_persisted(&self.workspace_path);
+        }
+    }
+
+    /// Write the match cache back, when enabled
+    fn save_match_cache(&self) {
+        if self.config.persist_match_cache {
+            // Persisting is an optimization, like the class index
+            let _ = match_cache::shared().save_persisted(&self.workspace_path);
+        }
+    }
+
+    /// Find every TypeScript file declaring the specified class
+    ///
+    /// Candidates are ranked by confidence, highest first. Ties keep path order.
+    /// The score averages the declaration confidence with the path heuristics
+    /// of [`FileLocator::path_rank`].
+    pub fn find_class_all(&self, class_name: &str) -> Result<Vec<ClassCandidate>, std::io::Error> {
+        self.start_deadline();
+        let index = self.load_index()?;
+        let filter = self.search_filter()?;
+
+        let mut candidates: Vec<ClassCandidate> = self
+            .limited(self.candidate_paths(&index, &filter, class_name))
+            .into_iter()
+            .take_while(|_| !self.is_timed_out())
+            .filter_map(|path| {
+                let content = self.read_source(Path::new(path))?;
+                if !self.matches_class(&content, class_name) {
+                    return None;
+                }
+                let (path_score, reasons) = Self::path_rank(Path::new(path), class_name);
+                Some(ClassCandidate {
+                    path: path.to_string(),
+                    score: (self.declaration_confidence(&content, class_name) + path_score) / 2.0,
+                    reasons,
+                })
+            })
+            .collect();
+
+        // Project-prioritized files stay ahead of the rest
+        let in_project = |candidate: &ClassCandidate| match &filter.project {
+            Some((root, _)) => Path::new(&candidate.path).starts_with(root),
+            None => false,
+        };
+        candidates.sort_by(|a, b| {
+            in_project(b).cmp(&in_project(a)).then(
+                b.score
+                    .partial_cmp(&a.score)
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            )
+        });
+
+        Ok(candidates)
+    }
+
+    /// Find indexed classes whose names resemble a possibly mangled one
+    ///
+    /// Recovers names from minified production builds (`_AppComponent_1`,
+    /// `AppComponentFactory`) or partial input (`AppCmp`). Candidates scoring
+    /// below `threshold` are dropped; the rest are ranked by similarity,
+    /// highest first, and capped by `max_results`.
+    pub fn find_class_fuzzy(
+        &self,
+        class_name: &str,
+        threshold: f64,
+    ) -> Result<Vec<FuzzyCandidate>, std::io::Error> {
+        self.start_deadline();
+        let index = self.load_index()?;
+        let filter = self.search_filter()?;
+
+        let mut candidates: Vec<FuzzyCandidate> = index
+            .entries()
+            .filter(|(path, _)| Self::is_selected(&filter.overrides, Path::new(path)))
+            .filter(|(path, _)| self.accepts_declaration_file(Path::new(path)))
+            .filter(|(path, _)| match &filter.project {
+                Some((root, ProjectScope::Restrict)) => Path::new(path).starts_with(root),
+                _ => true,
+            })
+            .flat_map(|(path, classes)| {
+                classes.iter().map(move |candidate| FuzzyCandidate {
+                    path: path.to_string(),
+                    class_name: candidate.clone(),
+                    score: fuzzy::similarity(class_name, candidate),
+                })
+            })
+            .filter(|candidate| candidate.score >= threshold)
+            .collect();
+
+        // Stable sort keeps path order between equal scores
+        candidates.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        if let Some(max_results) = self.config.max_results {
+            if candidates.len() > max_results {
+                candidates.truncate(max_results);
+                self.counters.truncated.set(true);
+            }
+        }
+
+        Ok(candidates)
+    }
+
+    /// Find the file declaring a class and the position of one of its methods
+    ///
+    /// Returns `None` if the class is not found or does not declare the method.
+    pub fn find_method(
+        &self,
+        class_name: &str,
+        method_name: &str,
+    ) -> Result<Option<(String, DeclarationSpan)>, std::io::Error> {
+        let Some(path) = self.find_class(class_name)? else {
+            return Ok(None);
+        };
+        let Some(content) = self.read_source(Path::new(&path)) else {
+            return Ok(None);
+        };
+
+        let span = TypeScriptParser::new()
+            .find_method_declaration(&content, class_name, method_name)
+            .unwrap_or(None);
+
+        Ok(span.map(|span| (path, span)))
+    }
+
+    /// Find a TypeScript file declaring a symbol of the given kind
+    pub fn find_symbol(
+        &self,
+        symbol_name: &str,
+        kind: SymbolKind,
+    ) -> Result<Option<String>, std::io::Error> {
+        if kind == SymbolKind::Class {
+            return self.find_class(symbol_name);
+        }
+
+        let matcher = DeclarationMatcher::new(kind.keywords(), symbol_name);
+
+        for path in self.typescript_files() {
+            let path = path?;
+            if let Some(content) = self.read_source(&path) {
+                if matcher.is_match(&content) {
+                    return Ok(Some(path.to_string_lossy().to_string()));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Find the component class declaring the given `@Component` selector
+    ///
+    /// Returns the file path and the class name.
+    pub fn find_component_by_selector(
+        &self,
+        selector: &str,
+    ) -> Result<Option<(String, String)>, std::io::Error> {
+        let parser = TypeScriptParser::new();
+
+        for path in self.typescript_files() {
+            let path = path?;
+            let Some(content) = self.read_source(&path) else {
+                continue;
+            };
+
+            // Cheap textual pre-filter before parsing
+            if !content.contains("@Component") || !content.contains(selector) {
+                continue;
+            }
+
+            if let Ok(Some(class_name)) = parser.find_component_by_selector(&content, selector) {
+                return Ok(Some((path.to_string_lossy().to_string(), class_name)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Find the component whose `templateUrl` points at the given HTML file
+    ///
+    /// The sibling `.ts` file of the template (`foo.component.html` →
+    /// `foo.component.ts`) is checked first, then every file mentioning the
+    /// template's file name. Returns the file path and the class name.
+    pub fn find_component_for_template(
+        &self,
+        template_path: &Path,
+    ) -> Result<Option<(String, String)>, std::io::Error> {
+        let template = fs::canonicalize(template_path)?;
+        let Some(file_name) = template
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+        else {
+            return Ok(None);
+        };
+        let parser = TypeScriptParser::new();
+
+        let references_template = |path: &Path| -> Option<String> {
+            let content = self.read_source(path)?;
+            if !content.contains(&file_name) {
+                return None;
+            }
+
+            let directory = path.parent()?;
+            parser
+                .find_component_templates(&content)
+                .ok()?
+                .into_iter()
+                .find(|(_, template_url)| {
+                    fs::canonicalize(directory.join(template_url))
+                        .is_ok_and(|resolved| resolved == template)
+                })
+                .map(|(class_name, _)| class_name)
+        };
+
+        let sibling = template.with_extension("ts");
+        if sibling.is_file() {
+            if let Some(class_name) = references_template(&sibling) {
+                return Ok(Some((sibling.to_string_lossy().to_string(), class_name)));
+            }
+        }
+
+        for path in self.typescript_files() {
+            let path = path?;
+            if let Some(class_name) = references_template(&path) {
+                return Ok(Some((path.to_string_lossy().to_string(), class_name)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Find every class carrying the given decorator (e.g. `Injectable`,
+    /// `Component`, `Directive`, `Pipe`)
+    ///
+    /// Returns file path and class name pairs in path order.
+    pub fn find_by_decorator(
+        &self,
+        decorator_name: &str,
+    ) -> Result<Vec<(String, String)>, std::io::Error> {
+        let parser = TypeScriptParser::new();
+        let marker = format!("@{}", decorator_name);
+        let mut found = Vec::new();
+
+        for path in self.typescript_files() {
+            let path = path?;
+            let Some(content) = self.read_source(&path) else {
+                continue;
+            };
+
+            // Cheap textual pre-filter before parsing
+            if !content.contains(&marker) {
+                continue;
+            }
+
+            if let Ok(class_names) = parser.find_decorated_classes(&content, decorator_name) {
+                let file_path = path.to_string_lossy().to_string();
+                found.extend(class_names.into_iter().map(|c| (file_path.clone(), c)));
+            }
+        }
+
+        found.sort();
+        Ok(found)
+    }
+
+    /// Summarize every exported class of the workspace in one pass
+    ///
+    /// Returns file path and class summary pairs in path order. Files that
+    /// fail to parse are skipped.
+    pub fn enumerate_classes(&self) -> Result<Vec<(String, ClassSummary)>, std::io::Error> {
+        let mut found = Vec::new();
+
+        for path in self.typescript_files() {
+            let path = path?;
+            let Some(content) = self.read_source(&path) else {
+                continue;
+            };
+
+            // Cheap textual pre-filter before parsing
+            if !content.contains("class ") {
+                continue;
+            }
+
+            // A parser per file: its source map keeps every file it parsed
+            if let Ok(classes) = TypeScriptParser::new().find_exported_classes(&content) {
+                let file_path = path.to_string_lossy().to_string();
+                found.extend(classes.into_iter().map(|c| (file_path.clone(), c)));
+            }
+        }
+
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(found)
+    }
+
+    /// Find the `subscribe()` calls of the workspace whose subscription can
+    /// leak
+    ///
+    /// Returns file path and subscription pairs in path order. Files that
+    /// fail to parse are skipped.
+    pub fn find_unmanaged_subscriptions(
+        &self,
+    ) -> Result<Vec<(String, UnmanagedSubscription)>, std::io::Error> {
+        let mut found = Vec::new();
+
+        for path in self.typescript_files() {
+            let path = path?;
+            let Some(content) = self.read_source(&path) else {
+                continue;
+            };
+
+            // Cheap textual pre-filter before parsing
+            if !content.contains(".subscribe(") {
+                continue;
+            }
+
+            if let Ok(subscriptions) =
+                TypeScriptParser::new().find_unmanaged_subscriptions(&content)
+            {
+                let file_path = path.to_string_lossy().to_string();
+                found.extend(subscriptions.into_iter().map(|s| (file_path.clone(), s)));
+            }
+        }
+
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(found)
+    }
+
+    /// Find the spec file covering a class
+    ///
+    /// The `*.spec.ts` file next to the class file is preferred (Angular CLI
+    /// convention); otherwise the first spec file importing the class from
+    /// its defining file, directly or through a barrel, is returned.
+    pub fn find_spec_for_class(&self, class_name: &str) -> Result<Option<String>, std::io::Error> {
+        let Some(class_file) = self.find_class(class_name)? else {
+            return Ok(None);
+        };
+        let class_file = PathBuf::from(class_file);
+
+        if let (Some(stem), Some(ext)) = (class_file.file_stem(), class_file.extension()) {
+            let spec = class_file.with_file_name(format!(
+                "{}.spec.{}",
+                stem.to_string_lossy(),
+                ext.to_string_lossy()
+            ));
+            if spec.is_file() {
+                return Ok(Some(spec.to_string_lossy().to_string()));
+            }
+        }
+
+        let defining_file = fs::canonicalize(&class_file)?;
+        let parser = TypeScriptParser::new();
+
+        for path in self.typescript_files() {
+            let path = path?;
+            if !is_spec_file(&path) {
+                continue;
+            }
+
+            let Some(content) = self.read_source(&path) else {
+                continue;
+            };
+            if !content.contains(class_name) {
+                continue;
+            }
+
+            let Ok(imports) = parser.find_imports(&content) else {
+                continue;
+            };
+            let directory = path.parent().unwrap_or(&self.workspace_path);
+
+            let imports_class = imports
+                .iter()
+                .filter(|import| import.source.starts_with('.'))
+                .filter(|import| import.names.iter().any(|name| name == class_name))
+                .filter_map(|import| resolve_module_file(directory, &import.source))
+                .any(|target| {
+                    let mut visited = HashSet::new();
+                    self.follow_reexports(&target, class_name, &parser, &mut visited, 0)
+                        .and_then(|found| fs::canonicalize(found).ok())
+                        .is_some_and(|found| found == defining_file)
+                });
+
+            if imports_class {
+                return Ok(Some(path.to_string_lossy().to_string()));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Find every class directly extending the given base class
+    ///
+    /// Returns file path and class name pairs in path order.
+    pub fn find_subclasses(
+        &self,
+        base_class_name: &str,
+    ) -> Result<Vec<(String, String)>, std::io::Error> {
+        let parser = TypeScriptParser::new();
+        // Cheap textual pre-filter; namespaced bases are confirmed by the parser
+        let matcher = DeclarationMatcher::new(&["extends"], base_class_name);
+        let namespaced = format!(".{}", base_class_name);
+        let mut found = Vec::new();
+
+        for path in self.typescript_files() {
+            let path = path?;
+            let Some(content) = self.read_source(&path) else {
+                continue;
+            };
+
+            if !matcher.is_match(&content) && !content.contains(&namespaced) {
+                continue;
+            }
+
+            if let Ok(class_names) = parser.find_subclasses(&content, base_class_name) {
+                let file_path = path.to_string_lossy().to_string();
+                found.extend(class_names.into_iter().map(|c| (file_path.clone(), c)));
+            }
+        }
+
+        found.sort();
+        Ok(found)
+    }
+
+    /// Find a class imported through a tsconfig path alias
+    ///
+    /// The alias is resolved to its target (file, barrel or directory) and
+    /// only that part of the tree is searched. Falls back to a full workspace
+    /// search if the alias does not resolve to the class.
+    pub fn find_class_with_tsconfig(
+        &self,
+        class_name: &str,
+        import_path: &str,
+        tsconfig: &TsConfig,
+    ) -> Result<Option<String>, std::io::Error> {
+        for target in tsconfig.resolve(import_path) {
+            // Direct file or barrel hits
+            let mut files = vec![target.join("index.ts")];
+            for ext in TYPESCRIPT_EXTENSIONS {
+                let mut file = target.clone().into_os_string();
+                file.push(".");
+                file.push(ext);
+                files.push(PathBuf::from(file));
+            }
+
+            let parser = TypeScriptParser::new();
+            for file in files.iter().filter(|f| f.is_file()) {
+                let mut visited = HashSet::new();
+                if let Some(path) =
+                    self.follow_reexports(file, class_name, &parser, &mut visited, 0)
+                {
+                    return Ok(Some(path));
+                }
+            }
+
+            // The alias may name a directory, or end with the class name
+            // itself (`@shared/services/FooService`)
+            let directory = if target.is_dir() {
+                Some(target.as_path())
+            } else {
+                target.parent().filter(|p| p.is_dir())
+            };
+
+            if let Some(directory) = directory {
+                if let Some(path) = self.scan_class_in(directory, class_name)? {
+                    return Ok(Some(path));
+                }
+            }
+        }
+
+        self.find_class(class_name)
+    }
+
+    /// Find the file defining a class exported through a barrel
+    ///
+    /// `barrel` is an `index.ts`-style file, or a directory containing one.
+    /// `export * from` and `export { X as Y } from` chains are followed
+    /// until a file declaring the class is reached.
+    pub fn find_class_via_barrel(&self, class_name: &str, barrel: &Path) -> Option<String> {
+        let entry = if barrel.is_dir() {
+            resolve_module_file(barrel, "./index")?
+        } else {
+            barrel.to_path_buf()
+        };
+
+        let parser = TypeScriptParser::new();
+        let mut visited = HashSet::new();
+        self.follow_reexports(&entry, class_name, &parser, &mut visited, 0)
+    }
+
+    /// Check a file for the class, then follow its relative re-exports
+    fn follow_reexports(
+        &self,
+        file: &Path,
+        class_name: &str,
+        parser: &TypeScriptParser,
+        visited: &mut HashSet<(PathBuf, String)>,
+        depth: usize,
+    ) -> Option<String> {
+        if depth > MAX_REEXPORT_DEPTH
+            || !visited.insert((file.to_path_buf(), class_name.to_string()))
+        {
+            return None;
+        }
+
+        let content = self.read_source(file)?;
+        if self.matches_class(&content, class_name) {
+            return Some(file.to_string_lossy().to_string());
+        }
+
+        let directory = file.parent()?;
+        for reexport in parser.find_reexports(&content).ok()? {
+            let (source, name) = match &reexport {
+                ReExport::All { source } => (source, class_name),
+                ReExport::Named { source, names } => {
+                    match names.iter().find(|(exported, _)| exported == class_name) {
+                        Some((_, original)) => (source, original.as_str()),
+                        None => continue,
+                    }
+                }
+            };
+
+            // Package re-exports cannot be followed into the workspace
+            if !source.starts_with('.') {
+                continue;
+            }
+
+            if let Some(target) = resolve_module_file(directory, source) {
+                if let Some(path) = self.follow_reexports(&target, name, parser, visited, depth + 1)
+                {
+                    return Some(path);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Search the files under a directory directly, without the index
+    fn scan_class_in(
+        &self,
+        directory: &Path,
+        class_name: &str,
+    ) -> Result<Option<String>, std::io::Error> {
+        for path in self.walk(directory, true) {
+            let path = path?;
+            if let Some(content) = self.read_source(&path) {
+                if self.matches_class(&content, class_name) {
+                    return Ok(Some(path.to_string_lossy().to_string()));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Load the persistent class index and bring it up to date
+    ///
+    /// The index always covers the full workspace; caller-supplied globs are
+    /// applied to lookups so differently filtered searches share one index.
+    ///
+    /// A refresh cut short by the timeout still returns the index, with the
+    /// unvisited files answered from their previous entries.
+    pub fn load_index(&self) -> Result<ClassIndex, std::io::Error> {
+        let mut index = ClassIndex::load(&self.workspace_path);
+        let refreshed = index.refresh(self.walk(&self.workspace_path, false), |path| {
+            self.increment(&self.counters.files_reindexed);
+            self.read_source(path)
+        });
+
+        match refreshed {
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+            refreshed => refreshed?,
+        }
+
+        // Persisting is an optimization; a read-only workspace still gets an answer
+        let _ = index.save();
+
+        Ok(index)
+    }
+
+    /// Iterate over all TypeScript files in the workspace selected by the
+    /// configured globs
+    ///
+    /// A restricting project scope limits the walk to the project's sources.
+    pub fn typescript_files(&self) -> impl Iterator<Item = Result<PathBuf, std::io::Error>> + '_ {
+        let (root, error) = match self.resolve_project() {
+            Ok(Some((root, ProjectScope::Restrict))) => (root, None),
+            Ok(_) => (self.workspace_path.clone(), None),
+            Err(e) => (self.workspace_path.clone(), Some(e)),
+        };
+
+        let failed = error.is_some();
+        error
+            .map(Err)
+            .into_iter()
+            .chain(self.walk(&root, true).take_while(move |_| !failed))
+    }
+
+    /// Iterate over the TypeScript files below a directory of the workspace,
+    /// filtered like a search but regardless of the project scope
+    pub fn typescript_files_in<'a>(
+        &'a self,
+        dir: &Path,
+    ) -> impl Iterator<Item = Result<PathBuf, std::io::Error>> + 'a {
+        self.walk(dir, true)
+    }
+
+    /// Iterate over `root` and the directories below it that a search would
+    /// enter, honoring the skip list and ignore files like file walks do
+    pub fn directories(&self, root: &Path) -> impl Iterator<Item = PathBuf> {
+        self.walk_builder(root)
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_dir()))
+            .map(|entry| entry.into_path())
+    }
+
+    /// Build the filters applied to index lookups
+    fn search_filter(&self) -> Result<SearchFilter, std::io::Error> {
+        Ok(SearchFilter {
+            overrides: self.overrides()?,
+            project: self.resolve_project()?,
+        })
+    }
+
+    /// Index candidates for a class after applying globs and project scope
+    fn candidate_paths<'a>(
+        &self,
+        index: &'a ClassIndex,
+        filter: &SearchFilter,
+        class_name: &str,
+    ) -> Vec<&'a str> {
+        let mut paths: Vec<&str> = index
+            .lookup_all(class_name)
+            .into_iter()
+            .filter(|path| Self::is_selected(&filter.overrides, Path::new(path)))
+            .filter(|path| self.accepts_declaration_file(Path::new(path)))
+            .collect();
+
+        // Likely application files first, so single lookups pick them
+        paths.sort_by(|a, b| {
+            let a = Self::path_rank(Path::new(a), class_name).0;
+            let b = Self::path_rank(Path::new(b), class_name).0;
+            b.partial_cmp(&a).unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        match &filter.project {
+            Some((root, ProjectScope::Restrict)) => {
+                paths.retain(|path| Path::new(path).starts_with(root));
+            }
+            Some((root, ProjectScope::Prioritize)) => {
+                // Stable sort keeps path order within each group
+                paths.sort_by_key(|path| !Path::new(path).starts_with(root));
+            }
+            None => {}
+        }
+
+        paths
+    }
+
+    /// Resolve the configured project to its source root
+    fn resolve_project(&self) -> Result<Option<(PathBuf, ProjectScope)>, std::io::Error> {
+        let Some(project) = &self.config.project else {
+            return Ok(None);
+        };
+
+        let projects = WorkspaceProjects::load(&self.workspace_path);
+        match projects.resolve(project) {
+            Some(info) => Ok(Some((
+                info.search_root().to_path_buf(),
+                self.config.project_scope,
+            ))),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Unknown project: {}", project),
+            )),
+        }
+    }
+
+    /// Walker of the entries under `root`
+    ///
+    /// Honors `.gitignore`, `.ignore` and nested ignore files (even outside
+    /// a git repository) on top of the built-in skip list.
+    fn walk_builder(&self, root: &Path) -> WalkBuilder {
+        let mut builder = WalkBuilder::new(root);
+        builder
+            .follow_links(self.config.follow_symlinks)
+            .max_depth(self.max_depth())
+            .hidden(true)
+            .git_ignore(true)
+            .git_exclude(true)
+            .ignore(true)
+            .parents(true)
+            .require_git(false);
+
+        let skip_dirs = self.skip_dirs();
+        if self.config.follow_symlinks {
+            let visited = VisitedDirs::default();
+            // The root itself never passes through the entry filter
+            visited.first_visit(root);
+            builder.filter_entry(move |e| {
+                Self::should_include_entry(&skip_dirs, e.path()) && visited.first_visit(e.path())
+            });
+        } else {
+            builder.filter_entry(move |e| Self::should_include_entry(&skip_dirs, e.path()));
+        }
+        builder
+    }
+
+    /// Walk TypeScript files under `root`, see [`Self::walk_builder`]
+    ///
+    /// With `filtered`, the configured globs and declaration-file setting
+    /// are applied too; an invalid glob is reported as the first item.
+    fn walk<'a>(
+        &'a self,
+        root: &Path,
+        filtered: bool,
+    ) -> impl Iterator<Item = Result<PathBuf, std::io::Error>> + 'a {
+        let mut builder = self.walk_builder(root);
+
+        let mut error = None;
+        if filtered {
+            match self.overrides() {
+                Ok(overrides) => {
+                    builder.overrides(overrides);
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+
+        let walker = if error.is_none() {
+            Some(builder.build())
+        } else {
+            None
+        };
+
+        error
+            .map(Err)
+            .into_iter()
+            .chain(
+                walker
+                    .into_iter()
+                    .flatten()
+                    .filter_map(move |entry| match entry {
+                        _ if self.is_cancelled() => Some(Err(std::io::Error::new(
+                            std::io::ErrorKind::Interrupted,
+                            "Search cancelled",
+                        ))),
+                        _ if self.is_timed_out() => Some(Err(std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            "Search timed out",
+                        ))),
+                        Ok(entry)
+                            if self.is_typescript_file(entry.path())
+                                && (!filtered || self.accepts_declaration_file(entry.path())) =>
+                        {
+                            self.record_file(entry.path());
+
+                            // Generated bundles can be huge; skip them before reading
+                            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                            if size > self.max_file_size() {
+                                self.increment(&self.counters.skipped_large);
+                                return None;
+                            }
+
+                            Some(Ok(entry.into_path()))
+                        }
+                        Ok(_) => None,
+                        // Malformed ignore files should not abort the search
+                        Err(e) => e.into_io_error().map(Err),
+                    }),
+            )
+    }
+
+    /// Read a source file, skipping files over the size limit and binary
+    /// content
+    ///
+    /// The file is copied into memory rather than mapped, since the sources
+    /// may be saved or truncated by an editor while they are searched.
+    /// Content with a NUL byte in its first few kilobytes, or that is not
+    /// valid UTF-8, is treated as binary. Skipped files are counted.
+    pub fn read_source(&self, path: &Path) -> Option<String> {
+        let size = fs::metadata(path).ok()?.len();
+        if size > self.max_file_size() {
+            self.increment(&self.counters.skipped_large);
+            return None;
+        }
+
+        let bytes = fs::read(path).ok()?;
+        self.increment(&self.counters.files_read);
+        self.counters
+            .bytes_read
+            .set(self.counters.bytes_read.get() + bytes.len() as u64);
+        let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_LEN)];
+        if sniff.contains(&0) {
+            self.increment(&self.counters.skipped_binary);
+            return None;
+        }
+
+        match String::from_utf8(bytes) {
+            Ok(content) => Some(content),
+            Err(_) => {
+                self.increment(&self.counters.skipped_binary);
+                None
+            }
+        }
+    }
+
+    /// Configured directory depth cap
+    fn max_depth(&self) -> Option<usize> {
+        match self.config.max_depth {
+            Some(depth) => Some(depth),
+            None if self.config.follow_symlinks => Some(DEFAULT_SYMLINK_DEPTH),
+            None => None,
         }
     }
 
-    /// Find a TypeScript file containing the specified class
-    /// Uses a two-phase approach:
-    /// 1. If file path is provided in the search, use it directly
-    /// 2. Otherwise, search the workspace for a class matching the class name
-    pub fn find_class(&self, class_name: &str) -> Result<Option<String>, std::io::Error> {
-        // Search the workspace for files containing the class
-        for entry in WalkDir::new(&self.workspace_path)
-            .follow_links(false)
-            .into_iter()
-            .filter_entry(|e| self.should_include_entry(e))
-        {
-            let entry = entry?;
-            let path = entry.path();
+    /// Configured file size limit in bytes
+    fn max_file_size(&self) -> u64 {
+        self.config.max_file_size.unwrap_or(DEFAULT_MAX_FILE_SIZE)
+    }
 
-            // Only process TypeScript files
-            if !self.is_typescript_file(path) {
-                continue;
-            }
+    fn increment(&self, counter: &Cell<usize>) {
+        counter.set(counter.get() + 1);
+    }
 
-            // Read file content and search for class definition
-            if let Ok(content) = fs::read_to_string(path) {
-                if self.contains_class(&content, class_name) {
-                    return Ok(Some(path.to_string_lossy().to_string()));
-                }
+    fn count_cache_lookup(&self, hit: bool) {
+        if hit {
+            self.increment(&self.counters.cache_hits);
+        } else {
+            self.increment(&self.counters.cache_misses);
+        }
+    }
+
+    /// Count a visited file, reporting progress at regular intervals
+    fn record_file(&self, path: &Path) {
+        let files_scanned = self.counters.files_scanned.get() + 1;
+        self.counters.files_scanned.set(files_scanned);
+
+        if files_scanned.is_multiple_of(PROGRESS_INTERVAL) {
+            self.report_progress(path.parent().unwrap_or(path));
+        }
+    }
+
+    /// Send the current counters to the progress callback, if any
+    fn report_progress(&self, current_directory: &Path) {
+        if let Some(ProgressReporter(callback)) = &self.config.progress {
+            callback(&ScanProgress {
+                files_scanned: self.counters.files_scanned.get(),
+                current_directory: current_directory.to_string_lossy().to_string(),
+                matches: self.counters.matches.get(),
+                skipped_large: self.counters.skipped_large.get(),
+                skipped_binary: self.counters.skipped_binary.get(),
+            });
+        }
+    }
+
+    /// Statistics accumulated since the last lookup started
+    pub fn stats(&self) -> ScanStats {
+        ScanStats {
+            files_visited: self.counters.files_scanned.get(),
+            files_read: self.counters.files_read.get(),
+            bytes_read: self.counters.bytes_read.get(),
+            files_reindexed: self.counters.files_reindexed.get(),
+            cache_hits: self.counters.cache_hits.get(),
+            cache_misses: self.counters.cache_misses.get(),
+            elapsed: self
+                .counters
+                .started
+                .get()
+                .map(|started| started.elapsed())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Whether the last search was cut short by `max_results` or `timeout`
+    pub fn truncated(&self) -> bool {
+        self.counters.truncated.get()
+    }
+
+    /// Arm the configured timeout and reset the statistics for a new lookup
+    fn start_deadline(&self) {
+        let counters = &self.counters;
+        for counter in [
+            &counters.files_scanned,
+            &counters.files_read,
+            &counters.files_reindexed,
+            &counters.cache_hits,
+            &counters.cache_misses,
+        ] {
+            counter.set(0);
+        }
+        counters.bytes_read.set(0);
+        counters.started.set(Some(Instant::now()));
+        self.counters.truncated.set(false);
+        self.counters
+            .deadline
+            .set(self.config.timeout.map(|timeout| Instant::now() + timeout));
+    }
+
+    /// Check the lookup deadline, flagging the result as truncated once passed
+    fn is_timed_out(&self) -> bool {
+        let timed_out = self
+            .counters
+            .deadline
+            .get()
+            .is_some_and(|deadline| Instant::now() >= deadline);
+        if timed_out {
+            self.counters.truncated.set(true);
+        }
+        timed_out
+    }
+
+    /// Apply `max_results` to the candidates of one class
+    fn limited<'a>(&self, mut paths: Vec<&'a str>) -> Vec<&'a str> {
+        if let Some(max_results) = self.config.max_results {
+            if paths.len() > max_results {
+                paths.truncate(max_results);
+                self.counters.truncated.set(true);
             }
         }
+        paths
+    }
 
-        Ok(None)
+    /// Check whether the caller asked to stop the search
+    fn is_cancelled(&self) -> bool {
+        self.config
+            .cancellation
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+    }
+
+    /// Build the include/exclude matcher from the configured globs
+    fn overrides(&self) -> Result<Override, std::io::Error> {
+        let invalid = |e: ignore::Error| std::io::Error::new(std::io::ErrorKind::InvalidInput, e);
+
+        let mut builder = OverrideBuilder::new(&self.workspace_path);
+        for glob in &self.config.globs {
+            builder.add(glob).map_err(invalid)?;
+        }
+        builder.build().map_err(invalid)
+    }
+
+    /// Check whether a file passes the include/exclude globs
+    fn is_selected(overrides: &Override, path: &Path) -> bool {
+        !overrides.matched(path, false).is_ignore()
+    }
+
+    /// Check if a path under the workspace would be visited by a search
+    ///
+    /// Applies the extension filter and the skip list to every component
+    /// below the workspace root. Ignore files are not consulted.
+    pub fn is_searchable(&self, path: &Path) -> bool {
+        self.is_typescript_file(path) && self.is_searchable_dir(path)
+    }
+
+    /// Check if a directory under the workspace would be entered by a search
+    ///
+    /// Applies the skip list to every component below the workspace root.
+    /// Ignore files are not consulted.
+    pub fn is_searchable_dir(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(&self.workspace_path).unwrap_or(path);
+        let skip_dirs = self.skip_dirs();
+
+        relative
+            .ancestors()
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .all(|ancestor| Self::should_include_entry(&skip_dirs, ancestor))
+    }
+
+    /// Effective skip list: the configured or default list plus extras
+    fn skip_dirs(&self) -> Vec<String> {
+        let base = match &self.config.skip_dirs {
+            Some(skip_dirs) => skip_dirs.clone(),
+            None => DEFAULT_SKIP_DIRS
+                .iter()
+                .map(|dir| dir.to_string())
+                .collect(),
+        };
+
+        base.into_iter()
+            .chain(self.config.extra_skip_dirs.iter().cloned())
+            .collect()
     }
 
     /// Check if the file is a TypeScript file
     fn is_typescript_file(&self, path: &Path) -> bool {
         path.extension()
             .and_then(|ext| ext.to_str())
-            .map(|ext| ext == "ts" || ext == "tsx")
+            .map(|ext| TYPESCRIPT_EXTENSIONS.contains(&ext))
             .unwrap_or(false)
     }
 
+    /// Check if a path is an ambient declaration file (`foo.d.ts`)
+    fn is_declaration_file(path: &Path) -> bool {
+        path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.ends_with(".d"))
+    }
+
+    /// Declaration files are only searched when opted in
+    fn accepts_declaration_file(&self, path: &Path) -> bool {
+        self.config.scan_declaration_files || !Self::is_declaration_file(path)
+    }
+
     /// Check if a file entry should be included in the search
-    fn should_include_entry(&self, entry: &walkdir::DirEntry) -> bool {
-        let path = entry.path();
+    fn should_include_entry(skip_dirs: &[String], path: &Path) -> bool {
         let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
 
         // Skip common directories that should be ignored
-        let skip_dirs = [
-            "node_modules",
-            "dist",
-            "out",
-            "build",
-            ".git",
-            ".vscode",
-            "target",
-            "coverage",
-        ];
-
-        for skip_dir in &skip_dirs {
-            if path.components().any(|c| c.as_os_str() == *skip_dir) {
+        for skip_dir in skip_dirs {
+            if path
+                .components()
+                .any(|c| c.as_os_str() == skip_dir.as_str())
+            {
                 return false;
             }
         }
@@ -82,26 +1544,260 @@ impl FileLocator {
         true
     }
 
-    /// Check if file content contains a class definition
-    /// Uses simple regex-like pattern matching for performance
-    fn contains_class(&self, content: &str, class_name: &str) -> bool {
-        // Look for class declarations, exports, and decorators
-        let patterns = [
-            format!("class {}", class_name),
-            format!("class {} ", class_name),
-            format!("class {}\n", class_name),
-            format!("class {} {{", class_name),
+    /// Estimate how likely the content holds the real declaration of the class
+    ///
+    /// Exported declarations are what imports resolve to, so they score
+    /// higher than file-local classes; an exact name boundary is required for
+    /// anything above the minimum score.
+    fn declaration_confidence(&self, content: &str, class_name: &str) -> f64 {
+        let exact = extract_class_names(content)
+            .iter()
+            .any(|name| name == class_name);
+        if !exact {
+            // Only a prefix match (e.g. `class FooBar` for `Foo`)
+            return 0.1;
+        }
+
+        let exported = [
             format!("export class {}", class_name),
             format!("export default class {}", class_name),
+            format!("export abstract class {}", class_name),
         ];
+        if exported.iter().any(|pattern| content.contains(pattern)) {
+            1.0
+        } else {
+            0.6
+        }
+    }
 
-        patterns.iter().any(|pattern| content.contains(pattern))
+    /// Score a candidate path in `0.0..=1.0`, with the reasons for the score
+    ///
+    /// Starts neutral at 0.5; files named after the class (`user-list.component.ts`
+    /// for `UserListComponent`) and files under `src/app` rank higher, spec
+    /// files and files in test directories rank lower.
+    fn path_rank(path: &Path, class_name: &str) -> (f64, Vec<String>) {
+        let mut score: f64 = 0.5;
+        let mut reasons = Vec::new();
+
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut parts: Vec<&str> = file_name.split('.').collect();
+        parts.pop();
+
+        let is_spec = parts
+            .last()
+            .is_some_and(|part| *part == "spec" || *part == "test");
+        if is_spec {
+            parts.pop();
+            score -= 0.3;
+            reasons.push("spec file".to_string());
+        }
+
+        if parts.join("-") == to_kebab_case(class_name) {
+            score += 0.3;
+            reasons.push("file name matches class name".to_string());
+        }
+
+        let directories: Vec<String> = path
+            .parent()
+            .map(|parent| {
+                parent
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if directories
+            .windows(2)
+            .any(|pair| pair[0] == "src" && pair[1] == "app")
+        {
+            score += 0.2;
+            reasons.push("under src/app".to_string());
+        }
+
+        if directories
+            .iter()
+            .any(|dir| TEST_DIRS.contains(&dir.as_str()))
+        {
+            score -= 0.2;
+            reasons.push("in test directory".to_string());
+        }
+
+        (score.clamp(0.0, 1.0), reasons)
+    }
+
+    /// Check if file content declares the class, applying AST verification
+    /// when enabled
+    ///
+    /// Files that fail to parse are rejected in verification mode, since a
+    /// declaration cannot be confirmed.
+    fn matches_class(&self, content: &str, class_name: &str) -> bool {
+        let matched =
+            self.contains_class(content, class_name) && self.verify_class(content, class_name);
+
+        if matched {
+            self.counters.matches.set(self.counters.matches.get() + 1);
+        }
+
+        matched
+    }
+
+    /// Confirm a textual match by parsing, when AST verification is enabled
+    fn verify_class(&self, content: &str, class_name: &str) -> bool {
+        !self.config.verify_ast
+            || TypeScriptParser::new()
+                .has_class_declaration(content, class_name)
+                .unwrap_or(false)
+    }
+
+    /// Check if file content contains a class definition
+    /// Uses a SIMD substring search for performance
+    ///
+    /// Both ends of the match must sit on identifier boundaries, so
+    /// `class UserService` does not declare `User`.
+    fn contains_class(&self, content: &str, class_name: &str) -> bool {
+        // `class Foo` covers exported, default and abstract declarations
+        let pattern = format!("class {}", class_name);
+        let bytes = content.as_bytes();
+        memchr::memmem::find_iter(bytes, pattern.as_bytes())
+            .any(|start| on_ident_boundaries(bytes, start, start + pattern.len()))
+    }
+}
+
+/// Real directories already entered by a symlink-following walk
+///
+/// Keyed by device and inode, so a directory reached through several links
+/// (or a link pointing back at an ancestor) is only walked once.
+#[derive(Clone, Default)]
+struct VisitedDirs(Arc<std::sync::Mutex<HashSet<(u64, u64)>>>);
+
+impl VisitedDirs {
+    /// Record a directory, returning `false` if it was already visited; files
+    /// always pass
+    fn first_visit(&self, path: &Path) -> bool {
+        let Ok(metadata) = fs::metadata(path) else {
+            return true;
+        };
+        if !metadata.is_dir() {
+            return true;
+        }
+
+        match file_id(&metadata) {
+            Some(id) => self
+                .0
+                .lock()
+                .map(|mut visited| visited.insert(id))
+                .unwrap_or(true),
+            None => true,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// Without inode numbers, cycles are still caught by the walker's own loop
+/// detection
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Entries of a directory in path order; unreadable directories are empty
+fn sorted_entries(directory: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+    entries
+}
+
+/// Modification time (nanoseconds since the Unix epoch) and size of a file
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((modified.as_nanos() as u64, metadata.len()))
+}
+
+/// Check if a path is a test file (`foo.spec.ts`, `foo.test.ts`)
+fn is_spec_file(path: &Path) -> bool {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.ends_with(".spec") || stem.ends_with(".test"))
+}
+
+/// Resolve a relative module specifier to a TypeScript file
+///
+/// Tries the specifier with each TypeScript extension, then as a directory
+/// with an `index` file. ESM-style `.js` / `.mjs` / `.cjs` specifiers map to
+/// their TypeScript sources.
+fn resolve_module_file(directory: &Path, specifier: &str) -> Option<PathBuf> {
+    let base = directory.join(specifier);
+
+    let mapped = [(".js", "ts"), (".mjs", "mts"), (".cjs", "cts")]
+        .iter()
+        .find_map(|(js, ts)| {
+            specifier
+                .strip_suffix(js)
+                .map(|stem| directory.join(format!("{}.{}", stem, ts)))
+        });
+
+    let with_extensions = TYPESCRIPT_EXTENSIONS.iter().map(|ext| {
+        let mut file = base.clone().into_os_string();
+        file.push(".");
+        file.push(ext);
+        PathBuf::from(file)
+    });
+    let indexes = TYPESCRIPT_EXTENSIONS
+        .iter()
+        .map(|ext| base.join(format!("index.{}", ext)));
+
+    mapped
+        .into_iter()
+        .chain(std::iter::once(base.clone()))
+        .chain(with_extensions)
+        .chain(indexes)
+        .find(|candidate| candidate.is_file())
+}
+
+/// Convert a PascalCase or camelCase name to kebab-case (`UserListComponent`
+/// → `user-list-component`)
+fn to_kebab_case(name: &str) -> String {
+    let mut kebab = String::with_capacity(name.len() + 4);
+    let chars: Vec<char> = name.chars().collect();
+
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            // Break before a new word, keeping acronyms together (`HTTPClient`)
+            let prev_lower = i > 0 && !chars[i - 1].is_uppercase();
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if i > 0 && (prev_lower || next_lower) && !kebab.ends_with('-') {
+                kebab.push('-');
+            }
+            kebab.extend(c.to_lowercase());
+        } else if *c == '_' {
+            kebab.push('-');
+        } else {
+            kebab.push(*c);
+        }
     }
+
+    kebab
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::temp_workspace;
 
     #[test]
     fn test_contains_class() {
@@ -118,5 +1814,669 @@ mod tests {
 
         let content = "const MyClass = () => { }";
         assert!(!locator.contains_class(content, "MyClass"));
+
+        let content = "export class MatButtonToggle {}\nexport class MatButton {}";
+        assert!(locator.contains_class(content, "MatButton"));
+        assert!(!locator.contains_class("export class MatButtonToggle {}", "MatButton"));
+        assert!(!locator.contains_class("subclass User {}", "User"));
+    }
+
+    #[test]
+    fn test_respects_gitignore() {
+        let workspace = temp_workspace("ignore");
+        fs::create_dir_all(workspace.join("generated")).unwrap();
+        fs::create_dir_all(workspace.join("src")).unwrap();
+        fs::write(workspace.join(".gitignore"), "generated/\n").unwrap();
+        fs::write(
+            workspace.join("generated/api.ts"),
+            "export class ApiClient {}",
+        )
+        .unwrap();
+        fs::write(workspace.join("src/app.ts"), "export class AppComponent {}").unwrap();
+
+        let locator = FileLocator::new(&workspace);
+        assert!(locator.find_class("ApiClient").unwrap().is_none());
+        assert!(locator.find_class("AppComponent").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_declaration_confidence() {
+        let locator = FileLocator::new(".");
+
+        let exported = locator.declaration_confidence("export class UserService {}", "UserService");
+        let local = locator.declaration_confidence("class UserService {}", "UserService");
+        let prefix = locator.declaration_confidence("class UserServiceMock {}", "UserService");
+
+        assert!(exported > local);
+        assert!(local > prefix);
+    }
+
+    #[test]
+    fn test_contains_declaration() {
+        let content = r#"
+export interface UserDto { id: string }
+export const enum Role { Admin }
+type Handler<T> = (value: T) => void;
+export function formatUser(user: UserDto) {}
+export const API_URL = '/api';
+"#;
+        let declares = |kind: SymbolKind, name: &str| {
+            DeclarationMatcher::new(kind.keywords(), name).is_match(content)
+        };
+
+        assert!(declares(SymbolKind::Interface, "UserDto"));
+        assert!(declares(SymbolKind::Enum, "Role"));
+        assert!(declares(SymbolKind::TypeAlias, "Handler"));
+        assert!(declares(SymbolKind::Function, "formatUser"));
+        assert!(declares(SymbolKind::Const, "API_URL"));
+
+        assert!(!declares(SymbolKind::Interface, "User"));
+        assert!(!declares(SymbolKind::Function, "UserDto"));
+    }
+
+    #[test]
+    fn test_verify_ast_rejects_comments() {
+        let content = "// TODO: class UserService should move here\nexport const x = 1;";
+
+        let textual = FileLocator::new(".");
+        assert!(textual.matches_class(content, "UserService"));
+
+        let verified = FileLocator::new(".").with_config(LocatorConfig {
+            verify_ast: true,
+            ..Default::default()
+        });
+        assert!(!verified.matches_class(content, "UserService"));
+        assert!(verified.matches_class("export class UserService {}", "UserService"));
+    }
+
+    #[test]
+    fn test_glob_filters() {
+        let workspace = temp_workspace("globs");
+        fs::create_dir_all(workspace.join("src")).unwrap();
+        fs::write(
+            workspace.join("src/user.service.spec.ts"),
+            "class UserService {}",
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("src/user.service.ts"),
+            "export class UserService {}",
+        )
+        .unwrap();
+        fs::write(workspace.join("tool.ts"), "export class Tool {}").unwrap();
+
+        let locator = FileLocator::new(&workspace).with_config(LocatorConfig {
+            globs: vec!["src/**".to_string(), "!**/*.spec.ts".to_string()],
+            ..Default::default()
+        });
+
+        let candidates = locator.find_class_all("UserService").unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert!(candidates[0].path.ends_with("user.service.ts"));
+        assert!(locator.find_class("Tool").unwrap().is_none());
+
+        let invalid = FileLocator::new(&workspace).with_config(LocatorConfig {
+            globs: vec!["src/[".to_string()],
+            ..Default::default()
+        });
+        assert!(invalid.find_class("Tool").is_err());
+    }
+
+    #[test]
+    fn test_find_classes_batch() {
+        let workspace = temp_workspace("batch");
+        fs::write(workspace.join("a.ts"), "export class A {}").unwrap();
+        fs::write(workspace.join("b.ts"), "export class B {}").unwrap();
+
+        let locator = FileLocator::new(&workspace);
+        let names = vec!["B".to_string(), "Missing".to_string(), "A".to_string()];
+        let results = locator.find_classes(&names).unwrap();
+
+        assert!(results[0].as_deref().unwrap().ends_with("b.ts"));
+        assert!(results[1].is_none());
+        assert!(results[2].as_deref().unwrap().ends_with("a.ts"));
+    }
+
+    #[test]
+    fn test_cancelled_search() {
+        let flag = Arc::new(AtomicBool::new(true));
+        let locator = FileLocator::new(".").with_config(LocatorConfig {
+            cancellation: Some(flag),
+            ..Default::default()
+        });
+
+        let error = locator
+            .scan_class_in(Path::new("."), "Anything")
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Interrupted);
+    }
+
+    #[test]
+    fn test_progress_reports() {
+        let workspace = temp_workspace("progress");
+        for i in 0..PROGRESS_INTERVAL {
+            fs::write(
+                workspace.join(format!("file{}.ts", i)),
+                "export class Target {}",
+            )
+            .unwrap();
+        }
+
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reports);
+        let locator = FileLocator::new(&workspace).with_config(LocatorConfig {
+            progress: Some(ProgressReporter(Arc::new(move |progress| {
+                sink.lock().unwrap().push(progress.clone());
+            }))),
+            ..Default::default()
+        });
+
+        assert!(locator.find_class("Target").unwrap().is_some());
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].files_scanned, PROGRESS_INTERVAL);
+        assert_eq!(reports[1].matches, 1);
+    }
+
+    #[test]
+    fn test_project_scoping() {
+        let workspace = temp_workspace("scope");
+        fs::create_dir_all(workspace.join("apps/admin/src")).unwrap();
+        fs::create_dir_all(workspace.join("apps/shop/src")).unwrap();
+        fs::write(
+            workspace.join("angular.json"),
+            r#"{ "projects": {
+                "admin": { "root": "apps/admin", "sourceRoot": "apps/admin/src" },
+                "shop": { "root": "apps/shop", "sourceRoot": "apps/shop/src" }
+            } }"#,
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("apps/admin/src/user.ts"),
+            "export class User {}",
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("apps/shop/src/user.ts"),
+            "export class User {}",
+        )
+        .unwrap();
+
+        let scoped = |scope| {
+            FileLocator::new(&workspace).with_config(LocatorConfig {
+                project: Some("shop".to_string()),
+                project_scope: scope,
+                ..Default::default()
+            })
+        };
+
+        let restricted = scoped(ProjectScope::Restrict)
+            .find_class_all("User")
+            .unwrap();
+        assert_eq!(restricted.len(), 1);
+        assert!(restricted[0].path.contains("shop"));
+
+        let prioritized = scoped(ProjectScope::Prioritize)
+            .find_class_all("User")
+            .unwrap();
+        assert_eq!(prioritized.len(), 2);
+        assert!(prioritized[0].path.contains("shop"));
+
+        let unknown = FileLocator::new(&workspace).with_config(LocatorConfig {
+            project: Some("missing".to_string()),
+            ..Default::default()
+        });
+        assert!(unknown.find_class("User").is_err());
+    }
+
+    #[test]
+    fn test_path_rank() {
+        assert_eq!(to_kebab_case("UserListComponent"), "user-list-component");
+        assert_eq!(to_kebab_case("HTTPClientService"), "http-client-service");
+
+        let (app, reasons) =
+            FileLocator::path_rank(Path::new("/ws/src/app/user.service.ts"), "UserService");
+        assert_eq!(
+            reasons,
+            vec!["file name matches class name", "under src/app"]
+        );
+
+        let (spec, _) =
+            FileLocator::path_rank(Path::new("/ws/src/app/user.service.spec.ts"), "UserService");
+        let (test_dir, _) =
+            FileLocator::path_rank(Path::new("/ws/src/test/mocks.ts"), "UserService");
+
+        assert!(app > spec);
+        assert!(spec > test_dir);
+    }
+
+    #[test]
+    fn test_skips_large_and_binary_files() {
+        let workspace = temp_workspace("guards");
+        fs::write(
+            workspace.join("bundle.ts"),
+            format!("export class Bundle {{}}\n{}", "x".repeat(4096)),
+        )
+        .unwrap();
+        fs::write(workspace.join("blob.ts"), b"export class Blob {}\0\x01").unwrap();
+        fs::write(workspace.join("small.ts"), "export class Small {}").unwrap();
+
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reports);
+        let locator = FileLocator::new(&workspace).with_config(LocatorConfig {
+            max_file_size: Some(1024),
+            progress: Some(ProgressReporter(Arc::new(move |p: &ScanProgress| {
+                sink.lock().unwrap().push(p.clone());
+            }))),
+            ..Default::default()
+        });
+
+        assert!(locator.find_class("Small").unwrap().is_some());
+        assert!(locator.find_class("Bundle").unwrap().is_none());
+        assert!(locator.find_class("Blob").unwrap().is_none());
+
+        let first = reports.lock().unwrap()[0].clone();
+        assert_eq!(first.skipped_large, 1);
+        assert_eq!(first.skipped_binary, 1);
+    }
+
+    #[test]
+    fn test_module_suffixes_and_declaration_files() {
+        let workspace = temp_workspace("suffixes");
+        fs::write(workspace.join("worker.mts"), "export class Worker {}").unwrap();
+        fs::write(workspace.join("legacy.cts"), "export class Legacy {}").unwrap();
+        fs::write(
+            workspace.join("globals.d.ts"),
+            "export declare class Ambient {}",
+        )
+        .unwrap();
+
+        let locator = FileLocator::new(&workspace);
+        assert!(locator.find_class("Worker").unwrap().is_some());
+        assert!(locator.find_class("Legacy").unwrap().is_some());
+        assert!(locator.find_class("Ambient").unwrap().is_none());
+
+        let with_declarations = FileLocator::new(&workspace).with_config(LocatorConfig {
+            scan_declaration_files: true,
+            ..Default::default()
+        });
+        assert!(with_declarations.find_class("Ambient").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_find_by_decorator() {
+        let workspace = temp_workspace("decorators");
+        fs::write(
+            workspace.join("a.service.ts"),
+            "@Injectable()\nexport class AService {}",
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("b.service.ts"),
+            "@Injectable()\nexport class BService {}\n@Pipe({ name: 'b' })\nexport class BPipe {}",
+        )
+        .unwrap();
+
+        let found = FileLocator::new(&workspace)
+            .find_by_decorator("Injectable")
+            .unwrap();
+        let class_names: Vec<&str> = found.iter().map(|(_, c)| c.as_str()).collect();
+        assert_eq!(class_names, vec!["AService", "BService"]);
+    }
+
+    #[test]
+    fn test_find_unmanaged_subscriptions() {
+        let workspace = temp_workspace("subscriptions");
+        fs::write(
+            workspace.join("a.component.ts"),
+            "export class AComponent {\n    ngOnInit() {\n        this.a$.subscribe();\n    }\n}",
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("b.component.ts"),
+            "export class BComponent {\n    ngOnInit() {\n        this.sub = this.b$.subscribe();\n    }\n}",
+        )
+        .unwrap();
+
+        let found = FileLocator::new(&workspace)
+            .find_unmanaged_subscriptions()
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].0.ends_with("a.component.ts"));
+        assert_eq!(
+            (found[0].1.class_name.as_str(), found[0].1.line),
+            ("AComponent", 3)
+        );
+    }
+
+    #[test]
+    fn test_find_class_via_barrel() {
+        let workspace = temp_workspace("barrel");
+        fs::create_dir_all(workspace.join("shared/services")).unwrap();
+        fs::write(
+            workspace.join("shared/index.ts"),
+            "export * from './services';",
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("shared/services/index.ts"),
+            "export { InternalAuth as AuthService } from './auth.service.js';\nexport * from './index';",
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("shared/services/auth.service.ts"),
+            "export class InternalAuth {}",
+        )
+        .unwrap();
+
+        let locator = FileLocator::new(&workspace);
+        let found = locator
+            .find_class_via_barrel("AuthService", &workspace.join("shared"))
+            .unwrap();
+        assert!(found.ends_with("auth.service.ts"));
+
+        // The self re-export cycle terminates
+        assert!(locator
+            .find_class_via_barrel("Missing", &workspace.join("shared"))
+            .is_none());
+
+        // A longer class name re-exported first is not taken for the class
+        fs::write(
+            workspace.join("shared/models.ts"),
+            "export * from './user.service';\nexport * from './user';",
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("shared/user.service.ts"),
+            "export class UserService {}",
+        )
+        .unwrap();
+        fs::write(workspace.join("shared/user.ts"), "export class User {}").unwrap();
+        let found = locator
+            .find_class_via_barrel("User", &workspace.join("shared/models.ts"))
+            .unwrap();
+        assert!(found.ends_with("user.ts"));
+    }
+
+    #[test]
+    fn test_find_spec_for_class() {
+        let workspace = temp_workspace("spec");
+        fs::create_dir_all(workspace.join("src/app")).unwrap();
+        fs::create_dir_all(workspace.join("test")).unwrap();
+        fs::write(
+            workspace.join("src/app/user.service.ts"),
+            "export class UserService {}",
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("src/app/cart.service.ts"),
+            "export class CartService {}",
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("src/app/user.service.spec.ts"),
+            "import { UserService } from './user.service';",
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("test/cart.spec.ts"),
+            "import { CartService } from '../src/app/cart.service';",
+        )
+        .unwrap();
+
+        let locator = FileLocator::new(&workspace);
+
+        let by_name = locator.find_spec_for_class("UserService").unwrap().unwrap();
+        assert!(by_name.ends_with("user.service.spec.ts"));
+
+        let by_import = locator.find_spec_for_class("CartService").unwrap().unwrap();
+        assert!(by_import.ends_with("cart.spec.ts"));
+    }
+
+    #[test]
+    fn test_match_cache_skips_unchanged_files() {
+        let workspace = temp_workspace("match-cache");
+        let file = workspace.join("user.service.ts");
+        fs::write(&file, "export class UserService {}").unwrap();
+
+        let locator = FileLocator::new(&workspace);
+        assert!(locator.find_class("UserService").unwrap().is_some());
+
+        // Same size and mtime: the cached answer is used without reading
+        let modified = fs::metadata(&file).unwrap().modified().unwrap();
+        fs::write(&file, "// not a declaration here!!").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert!(locator.find_class("UserService").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_limits_truncate_lookups() {
+        let workspace = temp_workspace("limits");
+        for name in ["a", "b", "c"] {
+            fs::write(
+                workspace.join(format!("{}.ts", name)),
+                "export class Shared {}",
+            )
+            .unwrap();
+        }
+
+        let limited = FileLocator::new(&workspace).with_config(LocatorConfig {
+            max_results: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(limited.find_class_all("Shared").unwrap().len(), 2);
+        assert!(limited.truncated());
+
+        let timed_out = FileLocator::new(&workspace).with_config(LocatorConfig {
+            timeout: Some(Duration::ZERO),
+            ..Default::default()
+        });
+        assert!(timed_out.find_class("Shared").is_ok());
+        assert!(timed_out.truncated());
+
+        let unlimited = FileLocator::new(&workspace);
+        assert!(unlimited.find_class("Shared").unwrap().is_some());
+        assert!(!unlimited.truncated());
+    }
+
+    #[test]
+    fn test_node_modules_fallback() {
+        let workspace = temp_workspace("library");
+        let package = workspace.join("node_modules/ui-kit");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(package.join("package.json"), r#"{ "types": "index.d.ts" }"#).unwrap();
+        fs::write(
+            package.join("index.d.ts"),
+            "export declare class KitButton {}",
+        )
+        .unwrap();
+
+        assert!(FileLocator::new(&workspace)
+            .find_class("KitButton")
+            .unwrap()
+            .is_none());
+
+        let with_libraries = FileLocator::new(&workspace).with_config(LocatorConfig {
+            search_node_modules: true,
+            ..Default::default()
+        });
+        let found = with_libraries.find_class("KitButton").unwrap().unwrap();
+        assert!(found.ends_with("index.d.ts"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_symlinks_with_cycles() {
+        use std::os::unix::fs::symlink;
+
+        let base = temp_workspace("symlinks");
+        let workspace = base.join("app");
+        let linked = base.join("shared-lib");
+        fs::create_dir_all(&workspace).unwrap();
+        fs::create_dir_all(&linked).unwrap();
+        fs::write(linked.join("shared.ts"), "export class SharedThing {}").unwrap();
+        fs::write(workspace.join("main.ts"), "export class Main {}").unwrap();
+
+        symlink(&linked, workspace.join("shared")).unwrap();
+        symlink(&linked, workspace.join("shared-again")).unwrap();
+        // Cycle back to the workspace root
+        symlink(&workspace, linked.join("loop")).unwrap();
+
+        assert!(FileLocator::new(&workspace)
+            .find_class("SharedThing")
+            .unwrap()
+            .is_none());
+
+        let following = FileLocator::new(&workspace).with_config(LocatorConfig {
+            follow_symlinks: true,
+            ..Default::default()
+        });
+        assert_eq!(following.find_class_all("SharedThing").unwrap().len(), 1);
+        assert_eq!(following.find_class_all("Main").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_find_component_for_template() {
+        let workspace = temp_workspace("template");
+        fs::create_dir_all(workspace.join("src/app/views")).unwrap();
+        fs::write(workspace.join("src/app/views/list.html"), "<ul></ul>").unwrap();
+        fs::write(
+            workspace.join("src/app/list.component.ts"),
+            "@Component({ templateUrl: './views/list.html' })\nexport class ListComponent {}",
+        )
+        .unwrap();
+
+        let (path, class_name) = FileLocator::new(&workspace)
+            .find_component_for_template(&workspace.join("src/app/views/list.html"))
+            .unwrap()
+            .unwrap();
+        assert!(path.ends_with("list.component.ts"));
+        assert_eq!(class_name, "ListComponent");
+    }
+
+    #[test]
+    fn test_configurable_skip_dirs() {
+        let workspace = temp_workspace("skip");
+        fs::create_dir_all(workspace.join("storybook-static")).unwrap();
+        fs::create_dir_all(workspace.join("dist")).unwrap();
+        fs::write(
+            workspace.join("storybook-static/story.ts"),
+            "export class Story {}",
+        )
+        .unwrap();
+        fs::write(workspace.join("dist/built.ts"), "export class Built {}").unwrap();
+
+        let extra = FileLocator::new(&workspace).with_config(LocatorConfig {
+            extra_skip_dirs: vec!["storybook-static".to_string()],
+            ..Default::default()
+        });
+        assert!(extra.find_class("Story").unwrap().is_none());
+        assert!(extra.find_class("Built").unwrap().is_none());
+
+        let replaced = FileLocator::new(&workspace).with_config(LocatorConfig {
+            skip_dirs: Some(Vec::new()),
+            ..Default::default()
+        });
+        assert!(replaced.find_class("Built").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_scan_stats() {
+        let workspace = temp_workspace("stats");
+        fs::write(workspace.join("a.ts"), "export class Alpha {}").unwrap();
+        fs::write(workspace.join("b.ts"), "export class Beta {}").unwrap();
+
+        let cold = FileLocator::new(&workspace);
+        assert!(cold.find_class("Alpha").unwrap().is_some());
+        let stats = cold.stats();
+        assert_eq!(stats.files_visited, 2);
+        assert_eq!(stats.files_reindexed, 2);
+        assert_eq!(stats.cache_misses, 1);
+        assert!(stats.bytes_read > 0);
+
+        // A second lookup is served from the index and the match cache
+        let warm = FileLocator::new(&workspace);
+        assert!(warm.find_class("Alpha").unwrap().is_some());
+        let stats = warm.stats();
+        assert_eq!(stats.files_reindexed, 0);
+        assert_eq!(stats.cache_hits, 1);
+        assert_eq!(stats.cache_hit_rate(), 1.0);
+    }
+
+    #[test]
+    fn test_find_class_fuzzy() {
+        let workspace = temp_workspace("fuzzy");
+        fs::write(workspace.join("app.ts"), "export class AppComponent {}").unwrap();
+        fs::write(workspace.join("user.ts"), "export class UserService {}").unwrap();
+
+        let locator = FileLocator::new(&workspace);
+        let candidates = locator
+            .find_class_fuzzy("_AppComponent_1", DEFAULT_FUZZY_THRESHOLD)
+            .unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].class_name, "AppComponent");
+        assert!(candidates[0].path.ends_with("app.ts"));
+
+        assert!(locator
+            .find_class_fuzzy("Unrelated", DEFAULT_FUZZY_THRESHOLD)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_enumerate_classes() {
+        let workspace = temp_workspace("enumerate");
+        fs::write(
+            workspace.join("b.service.ts"),
+            "@Injectable()\nexport class BService { load() {} }",
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("a.ts"),
+            "export class A {}\nclass Internal {}",
+        )
+        .unwrap();
+
+        let classes = FileLocator::new(&workspace).enumerate_classes().unwrap();
+        let names: Vec<&str> = classes.iter().map(|(_, c)| c.name.as_str()).collect();
+        assert_eq!(names, vec!["A", "BService"]);
+        assert_eq!(classes[1].1.decorators, vec!["Injectable".to_string()]);
+        assert_eq!(classes[1].1.method_count, 1);
+    }
+
+    #[test]
+    fn test_find_class_near() {
+        let workspace = temp_workspace("near");
+        fs::create_dir_all(workspace.join("features/cart")).unwrap();
+        fs::create_dir_all(workspace.join("features/checkout")).unwrap();
+        fs::create_dir_all(workspace.join("node_modules/lib")).unwrap();
+        fs::write(
+            workspace.join("features/checkout/checkout.service.ts"),
+            "export class CheckoutService {}",
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("node_modules/lib/cart.ts"),
+            "export class CartStore {}",
+        )
+        .unwrap();
+
+        let locator = FileLocator::new(&workspace);
+        let near = [workspace.join("features/cart")];
+        let path = locator.find_class_near("CheckoutService", &near).unwrap();
+        assert!(path.unwrap().ends_with("checkout.service.ts"));
+
+        // Skipped directories stay skipped among the siblings
+        let near = [workspace.join("node_modules/other")];
+        assert!(locator
+            .find_class_near("CartStore", &near)
+            .unwrap()
+            .is_none());
     }
 }
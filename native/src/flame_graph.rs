@@ -1,139 +1,2315 @@
+use crate::match_cache::{fnv1a, FNV_OFFSET};
 use napi::bindgen_prelude::*;
-use napi_derive::napi;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 /// Call stack input from TypeScript
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct CallStackInput {
     call_id: String,
-    class_name: String,
-    method_name: String,
+    // Shared between the calls of binary records, see
+    // [`decode_call_records`]
+    class_name: Arc<str>,
+    method_name: Arc<str>,
     duration: f64,
     start_time: f64,
     end_time: f64,
     parent_call_id: Option<String>,
-    file_path: Option<String>,
+    file_path: Option<Arc<str>>,
     line: Option<u32>,
+    /// Custom dimensions of the call, e.g. `route`, `zone`, `componentId`
+    /// or `httpUrl`, carried through to its node
+    #[serde(default)]
+    tags: Option<Map<String, Value>>,
+    /// Bytes allocated during the call, children included
+    #[serde(default)]
+    allocated_bytes: Option<f64>,
+    /// Asynchronous context the call runs in, e.g. an `async_hooks` id
+    #[serde(default)]
+    async_id: Option<u64>,
+    /// Context that scheduled the context of the call
+    #[serde(default)]
+    trigger_async_id: Option<u64>,
+    /// The parent was linked from `trigger_async_id` rather than given
+    #[serde(skip)]
+    async_continuation: bool,
+    /// Index of the recording of the call, when merging recordings
+    #[serde(skip)]
+    run: Option<u32>,
 }
 
+/// Order of the children of a flame graph node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChildOrder {
+    /// By start time, as the calls happened
+    #[default]
+    Chronological,
+    /// By decreasing value, widest frames first, then by start time
+    LeftHeavy,
+}
+
+impl ChildOrder {
+    /// Parse the order name used across the FFI boundary
+    pub fn parse(order: &str) -> Option<Self> {
+        match order {
+            "chronological" => Some(Self::Chronological),
+            "left-heavy" => Some(Self::LeftHeavy),
+            _ => None,
+        }
+    }
+}
+
+/// What the value of a flame graph node measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Weight {
+    /// Time spent in the calls, in milliseconds
+    #[default]
+    Duration,
+    /// Number of calls, to surface chattiness
+    Count,
+    /// Bytes allocated by the calls
+    Allocation,
+}
+
+impl Weight {
+    /// Parse the weight name used across the FFI boundary
+    pub fn parse(weight: &str) -> Option<Self> {
+        match weight {
+            "duration" => Some(Self::Duration),
+            "count" => Some(Self::Count),
+            "allocation" => Some(Self::Allocation),
+            _ => None,
+        }
+    }
+
+    /// Weight of a call itself; for counts, the children are added once the
+    /// tree is built
+    fn of(self, call: &CallStackInput) -> f64 {
+        match self {
+            Self::Duration => call.duration,
+            Self::Count => 1.0,
+            Self::Allocation => call.allocated_bytes.unwrap_or(0.0),
+        }
+    }
+}
+
+/// Settings of flame graph construction
+#[derive(Debug, Clone, Default)]
+pub struct FlameGraphConfig {
+    pub child_order: ChildOrder,
+    /// Merge the calls of the same `Class.method` under the same parent into
+    /// one node
+    pub aggregate: bool,
+    /// Only keep the calls, or the parts of calls, after this time
+    pub start_time: Option<f64>,
+    /// Only keep the calls, or the parts of calls, before this time
+    pub end_time: Option<f64>,
+    /// Collapse children below this share of the total duration
+    pub min_percentage: Option<f64>,
+    /// Collapse children shorter than this many milliseconds
+    pub min_duration_ms: Option<f64>,
+    /// Mark the nodes whose name contains this text
+    pub search: Option<String>,
+    /// Match `search` as a regular expression instead of a substring
+    pub search_regex: bool,
+    /// Deepest depth written, roots being at depth 0
+    pub max_depth: Option<u32>,
+    /// Wrap the roots in one `(session)` root lasting the total duration
+    pub virtual_root: bool,
+    /// What node values measure; `min_duration_ms` then applies to values
+    /// in that unit, while time windows stay in milliseconds
+    pub weight: Weight,
+    /// Give each node its extent for icicle and sunburst charts
+    pub layout: bool,
+    /// Compute `percentage` relative to the parent frame instead of the
+    /// total, roots staying relative to the total
+    pub percent_of_parent: bool,
+    /// Hash node ids from the path of names from the root rather than
+    /// passing call ids through, see [`path_ids`]
+    pub stable_ids: bool,
+    /// Keep only this many of the largest children of each frame, merging
+    /// the rest like pruned children
+    pub max_children: Option<u32>,
+    /// Only keep the calls whose `Class.method` name starts with, or whose
+    /// file path contains, one of these patterns; the children of the
+    /// others take their place
+    pub include_patterns: Vec<String>,
+    /// Leave out the calls matching one of these patterns likewise
+    pub exclude_patterns: Vec<String>,
+    /// Remove the self time of left out calls from their ancestors instead
+    /// of counting it as the self time of their parent
+    pub drop_elided_time: bool,
+}
+
+/// Settings of SVG rendering
+#[derive(Debug, Clone)]
+pub struct SvgConfig {
+    /// Width of the image in pixels
+    pub width: u32,
+    /// Height of one frame in pixels
+    pub frame_height: u32,
+    /// Heading drawn above the frames
+    pub title: Option<String>,
+}
+
+impl Default for SvgConfig {
+    fn default() -> Self {
+        Self {
+            width: 1200,
+            frame_height: 16,
+            title: None,
+        }
+    }
+}
+
+/// Frames narrower than this many pixels are left out with their children
+const SVG_MIN_FRAME_WIDTH: f64 = 0.1;
+/// Approximate width of a label character in the 12px monospace font
+const SVG_CHAR_WIDTH: f64 = 7.2;
+/// Height reserved for the title
+const SVG_TITLE_HEIGHT: u32 = 28;
+
 /// Flame graph node for visualization
+///
+/// Children are written after the other fields by [`write_nodes`] as a
+/// `children` array, omitted when empty.
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct FlameGraphNode {
-    id: String,
+struct FlameGraphNode<'a> {
+    id: &'a str,
+    /// First call of the node, when ids are hashed from paths
+    #[serde(skip_serializing_if = "Option::is_none")]
+    call_id: Option<&'a str>,
     name: String,
     value: f64,
     self_value: f64,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    children: Vec<FlameGraphNode>,
     depth: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
-    file_path: Option<String>,
+    file_path: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
     line: Option<u32>,
     percentage: f64,
+    /// Calls merged into the node
+    count: u32,
+    /// Whether the name matches the search
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    matched: bool,
+    /// Calls left out below the depth cap, however deep
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_children: Option<u32>,
+    /// Time of the children left out below the depth cap
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_time: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tags: Option<&'a Map<String, Value>>,
+    /// Index of the [`Category`] of the frame, stable across runs for
+    /// consistent colors
+    category: u8,
+    /// Milliseconds between consecutive children not spent in any child,
+    /// e.g. awaiting the network
+    gap_time: f64,
+    /// Spread of the durations of the merged calls, in aggregated mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<DurationStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    layout: Option<NodeLayout>,
+    /// Whether the call continues asynchronous work of its parent rather
+    /// than being called by it
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    async_continuation: bool,
+    /// Recordings with calls merged into the node, when merging recordings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    run_count: Option<u32>,
+}
+
+/// Extent of a node along the total value, children packed from the start
+/// of their parent
+///
+/// Icicle charts span `x0..x1` horizontally at row `depth`; sunburst charts
+/// span `startAngle..endAngle` at ring `depth`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeLayout {
+    /// Start as a fraction of the total value
+    x0: f64,
+    /// End as a fraction of the total value
+    x1: f64,
+    /// Start angle in radians, clockwise from the top
+    start_angle: f64,
+    /// End angle in radians
+    end_angle: f64,
+}
+
+impl NodeLayout {
+    fn new(offset: f64, value: f64, total: f64) -> Self {
+        let (x0, x1) = if total > 0.0 {
+            (offset / total, (offset + value) / total)
+        } else {
+            (0.0, 0.0)
+        };
+        Self {
+            x0,
+            x1,
+            start_angle: x0 * std::f64::consts::TAU,
+            end_angle: x1 * std::f64::consts::TAU,
+        }
+    }
+}
+
+/// Durations of the calls merged into a frame, in milliseconds
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DurationStats {
+    min: f64,
+    max: f64,
+    avg: f64,
+    /// Nearest-rank 95th percentile
+    p95: f64,
+}
+
+impl DurationStats {
+    fn new(durations: &[f64]) -> Option<Self> {
+        let mut sorted = durations.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let rank = ((sorted.len() as f64 * 0.95).ceil() as usize).max(1);
+
+        Some(Self {
+            min: *sorted.first()?,
+            max: *sorted.last()?,
+            avg: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p95: sorted[rank - 1],
+        })
+    }
+}
+
+/// Idle time between two consecutive children of a call
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Gap<'a> {
+    call_id: &'a str,
+    name: String,
+    start: f64,
+    end: f64,
+    duration: f64,
+}
+
+/// Number of gaps listed in `largestGaps`
+const LARGEST_GAPS: usize = 10;
+
+/// Origin of a frame, used as a coloring hint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Category {
+    App = 0,
+    Angular = 1,
+    Rxjs = 2,
+    Zone = 3,
+    /// Other installed packages
+    Library = 4,
+    /// Frames standing for several calls, such as `(other: N calls)`
+    Synthetic = 5,
+}
+
+/// Angular framework classes recognized without a file path
+const ANGULAR_CLASSES: &[&str] = &[
+    "NgZone",
+    "NoopNgZone",
+    "ApplicationRef",
+    "ChangeDetectorRef",
+    "ViewRef",
+    "Router",
+    "ActivatedRoute",
+    "HttpClient",
+    "HttpHandler",
+    "HttpXhrBackend",
+    "FormControl",
+    "FormGroup",
+    "Renderer2",
+    "DomRenderer",
+];
+
+/// Zone.js classes recognized without a file path
+const ZONE_CLASSES: &[&str] = &[
+    "Zone",
+    "ZoneDelegate",
+    "ZoneTask",
+    "ZoneAwarePromise",
+    "MacroTask",
+    "MicroTask",
+    "EventTask",
+];
+
+/// RxJS classes recognized without a file path
+const RXJS_CLASSES: &[&str] = &[
+    "Observable",
+    "Subject",
+    "BehaviorSubject",
+    "ReplaySubject",
+    "AsyncSubject",
+    "Subscriber",
+    "Subscription",
+    "SafeSubscriber",
+    "OperatorSubscriber",
+];
+
+impl Category {
+    /// Categorize a call by the package of its file, or else by well-known
+    /// framework class names
+    fn of(call: &CallStackInput) -> Self {
+        if let Some(file_path) = &call.file_path {
+            let file_path = file_path.replace('\\', "/");
+            if let Some((_, package)) = file_path.rsplit_once("node_modules/") {
+                return if package.starts_with("@angular/") {
+                    Self::Angular
+                } else if package.starts_with("rxjs/") {
+                    Self::Rxjs
+                } else if package.starts_with("zone.js/") {
+                    Self::Zone
+                } else {
+                    Self::Library
+                };
+            }
+        }
+
+        let class_name = &*call.class_name;
+        if ZONE_CLASSES.contains(&class_name) {
+            Self::Zone
+        } else if ANGULAR_CLASSES.contains(&class_name) {
+            Self::Angular
+        } else if RXJS_CLASSES.contains(&class_name) {
+            Self::Rxjs
+        } else {
+            Self::App
+        }
+    }
+}
+
+/// A flame graph node before serialization: one call, or in aggregated mode
+/// every call of a `Class.method` under the same parent frame
+struct Frame<'a> {
+    /// First call of the frame, which gives its id and location
+    call: &'a CallStackInput,
+    kind: FrameKind,
+    name: String,
+    value: f64,
+    count: u32,
+    /// Earliest start time of the calls
+    start_time: f64,
+    /// Tags shared by every call of the frame
+    tags: Option<Map<String, Value>>,
+    /// Gaps between the children of the calls, in milliseconds
+    gap_time: f64,
+    /// Durations of the merged calls, in aggregated mode
+    durations: Option<Vec<f64>>,
+    /// Recordings the calls come from, when merging recordings
+    runs: Option<HashSet<u32>>,
+    children: Vec<usize>,
+}
+
+impl Frame<'_> {
+    fn category(&self) -> Category {
+        match self.kind {
+            FrameKind::Calls => Category::of(self.call),
+            _ => Category::Synthetic,
+        }
+    }
+}
+
+/// What a frame stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrameKind {
+    /// Calls of one method
+    Calls,
+    /// Siblings pruned for their size
+    Other,
+    /// Root of the calls whose parent is missing from the input
+    Orphaned,
+    /// Root of the whole recording
+    Session,
+}
+
+/// Speedscope document, see https://www.speedscope.app/file-format-schema.json
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpeedscopeFile<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    shared: SpeedscopeShared<'a>,
+    profiles: Vec<SpeedscopeProfile>,
+    name: &'static str,
+    active_profile_index: u32,
+    exporter: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct SpeedscopeShared<'a> {
+    frames: Vec<SpeedscopeFrame<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct SpeedscopeFrame<'a> {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpeedscopeProfile {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    name: &'static str,
+    unit: &'static str,
+    start_value: f64,
+    end_value: f64,
+    events: Vec<SpeedscopeEvent>,
+}
+
+/// Opening (`O`) or closing (`C`) of a frame
+#[derive(Debug, Serialize)]
+struct SpeedscopeEvent {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    frame: usize,
+    at: f64,
+}
+
+/// Trace Event Format document, as opened by chrome://tracing and Perfetto
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceFile<'a> {
+    trace_events: Vec<TraceEvent<'a>>,
+    display_time_unit: &'static str,
+}
+
+/// Complete (`X`) event of one call, times in microseconds
+#[derive(Debug, Serialize)]
+struct TraceEvent<'a> {
+    name: String,
+    cat: &'static str,
+    ph: &'static str,
+    ts: f64,
+    dur: f64,
+    pid: u32,
+    tid: u32,
+    args: TraceEventArgs<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceEventArgs<'a> {
+    call_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_path: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+}
+
+/// Self time of every call of one method
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Hotspot<'a> {
+    name: String,
+    class_name: &'a str,
+    method_name: &'a str,
+    self_time: f64,
+    call_count: u32,
+    average_self_time: f64,
+    /// Location of the first call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_path: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+}
+
+/// Calls on a timeline, see [`build_flame_chart_data`]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FlameChart<'a> {
+    events: Vec<ChartEvent<'a>>,
+    /// Rows needed by each lane: the deepest depth in it plus one
+    lane_depths: Vec<u32>,
+    start_time: f64,
+    end_time: f64,
+    orphaned_calls: usize,
+    warnings: &'a [String],
+}
+
+/// One call at its recorded time
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChartEvent<'a> {
+    id: &'a str,
+    name: String,
+    start: f64,
+    end: f64,
+    duration: f64,
+    /// Depth below the root of the lane
+    depth: u32,
+    lane: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_id: Option<&'a str>,
+    category: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_path: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tags: Option<&'a Map<String, Value>>,
+}
+
+/// Shape of the written nodes, to pick the initial zoom and spot runaway
+/// recursion
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Summary {
+    /// Deepest depth written, roots being at depth 0
+    max_depth: u32,
+    node_count: u32,
+    /// Self value of the nodes at each depth
+    self_time_by_depth: Vec<f64>,
+    /// Call node with the largest value, the deepest one on ties so that
+    /// chains of wrappers are skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    widest_frame: Option<WidestFrame>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WidestFrame {
+    id: String,
+    name: String,
+    value: f64,
+    depth: u32,
+}
+
+/// Step of the depth-first walk of [`write_nodes`]
+enum Step {
+    /// Write a frame, then its children
+    Open {
+        frame: usize,
+        depth: u32,
+        /// Whether an ancestor matched the search
+        in_match: bool,
+        /// Start of the frame along the total value, for the layout
+        offset: f64,
+        /// Value of the parent frame, or the total value for roots
+        parent_value: f64,
+    },
+    /// Close the children array of a frame
+    Close,
+}
+
+/// Build flame graph data from call stack nodes
+///
+/// # Arguments
+/// * `call_stack_json` - JSON string containing array of CallStackInput
+/// * `config` - How to lay out the nodes
+///
+/// # Returns
+/// JSON string containing flame graph data structure
+pub fn build_flame_graph_data(
+    call_stack_json: String,
+    config: &FlameGraphConfig,
+) -> Result<String> {
+    // Parse input
+    let calls: Vec<CallStackInput> = serde_json::from_str(&call_stack_json)
+        .map_err(|e| Error::from_reason(format!("JSON parse error: {}", e)))?;
+
+    build_flame_graph(calls, config)
+}
+
+/// Build one aggregated flame graph from several recordings, e.g. repeated
+/// runs of the same user flow
+///
+/// Call ids only need to be unique within a recording. Nodes are merged
+/// across recordings as in aggregated mode, whatever the config says, and
+/// report how many recordings they occur in as `runCount`; values are the
+/// sums over all recordings.
+///
+/// # Arguments
+/// * `recordings_json` - JSON string containing an array of CallStackInput
+///   arrays, one per recording
+/// * `config` - How to lay out the nodes
+///
+/// # Returns
+/// JSON string containing flame graph data structure
+pub fn merge_call_stacks(recordings_json: String, config: &FlameGraphConfig) -> Result<String> {
+    let recordings: Vec<Vec<CallStackInput>> = serde_json::from_str(&recordings_json)
+        .map_err(|e| Error::from_reason(format!("JSON parse error: {}", e)))?;
+
+    let mut calls = Vec::new();
+    for (run, recording) in recordings.into_iter().enumerate() {
+        calls.extend(recording.into_iter().map(|mut call| {
+            call.call_id = format!("{}:{}", run, call.call_id);
+            call.parent_call_id = call
+                .parent_call_id
+                .map(|parent_id| format!("{}:{}", run, parent_id));
+            call.run = Some(run as u32);
+            call
+        }));
+    }
+
+    let config = FlameGraphConfig {
+        aggregate: true,
+        ..config.clone()
+    };
+    build_flame_graph(calls, &config)
+}
+
+/// Build flame graph data from binary call records
+///
+/// # Arguments
+/// * `records` - Call records in the layout read by [`decode_call_records`]
+/// * `config` - How to lay out the nodes
+///
+/// # Returns
+/// JSON string containing flame graph data structure
+pub fn build_flame_graph_data_from_records(
+    records: &[u8],
+    config: &FlameGraphConfig,
+) -> Result<String> {
+    let calls = decode_call_records(records)
+        .map_err(|e| Error::from_reason(format!("Invalid call records: {}", e)))?;
+
+    build_flame_graph(calls, config)
+}
+
+/// Calls collected from binary record batches, for recordings streamed in
+/// chunks rather than sent at once
+#[derive(Default)]
+pub struct CallRecordCollector {
+    calls: Vec<CallStackInput>,
+}
+
+impl CallRecordCollector {
+    /// Decode and keep a batch of binary call records
+    ///
+    /// Each batch is self-contained, with its own string table, in the
+    /// layout read by [`decode_call_records`]. A malformed batch is
+    /// rejected whole.
+    ///
+    /// # Returns
+    /// The number of calls of the batch
+    pub fn add_records(&mut self, records: &[u8]) -> std::result::Result<usize, String> {
+        let calls = decode_call_records(records)?;
+        let count = calls.len();
+        self.calls.extend(calls);
+        Ok(count)
+    }
+
+    /// Number of calls collected so far
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Build flame graph data from every call collected
+    pub fn build(self, config: &FlameGraphConfig) -> Result<String> {
+        build_flame_graph(self.calls, config)
+    }
+}
+
+fn build_flame_graph(calls: Vec<CallStackInput>, config: &FlameGraphConfig) -> Result<String> {
+    let calls = clip_calls(calls, config.start_time, config.end_time);
+    let tree = CallTree::new(calls);
+    let (frames, roots, total_duration) = prepare_frames(&tree, config);
+    let search = search_pattern(config)?;
+
+    let mut nodes = String::new();
+    let (matched_time, summary) = write_nodes(
+        &mut nodes,
+        &frames,
+        &roots,
+        total_duration,
+        search.as_ref(),
+        config,
+    )?;
+
+    let matched_time = match search {
+        Some(_) => format!(",\"matchedTime\":{}", to_json(&matched_time)?),
+        None => String::new(),
+    };
+    Ok(format!(
+        "{{\"nodes\":[{}],\"totalDuration\":{},\"orphanedCalls\":{},\"warnings\":{},\"largestGaps\":{},\"summary\":{}{}}}",
+        nodes,
+        to_json(&total_duration)?,
+        tree.orphans.len(),
+        to_json(&tree.warnings)?,
+        to_json(&largest_gaps(&tree))?,
+        to_json(&summary)?,
+        matched_time
+    ))
+}
+
+/// Calls left out of the frames, their children taking their place
+#[derive(Debug, Default)]
+struct CallFilter<'a> {
+    include: &'a [String],
+    exclude: &'a [String],
+    /// Remove the self time of elided calls from their ancestors instead of
+    /// leaving it to their parent
+    drop_time: bool,
+}
+
+impl<'a> CallFilter<'a> {
+    fn new(config: &'a FlameGraphConfig) -> Self {
+        Self {
+            include: &config.include_patterns,
+            exclude: &config.exclude_patterns,
+            drop_time: config.drop_elided_time,
+        }
+    }
+
+    /// Whether a call matches no include pattern, when there are some, or
+    /// matches an exclude pattern
+    fn elides(&self, call: &CallStackInput, name: &str) -> bool {
+        let matches = |pattern: &String| {
+            name.starts_with(pattern.as_str())
+                || call.file_path.as_ref().is_some_and(|file_path| {
+                    file_path.replace('\\', "/").contains(pattern.as_str())
+                })
+        };
+        (!self.include.is_empty() && !self.include.iter().any(matches))
+            || self.exclude.iter().any(matches)
+    }
+}
+
+/// Turn the call tree into sorted frames, with the virtual root and pruning
+/// of the config applied
+///
+/// # Returns
+/// The frames, the root frames and the total value of the roots
+fn prepare_frames<'a>(
+    tree: &'a CallTree,
+    config: &FlameGraphConfig,
+) -> (Vec<Frame<'a>>, Vec<usize>, f64) {
+    let (mut frames, mut roots) = build_frames(
+        tree,
+        config.aggregate,
+        config.weight,
+        &CallFilter::new(config),
+    );
+
+    // Calculate total duration from root nodes
+    let total_duration: f64 = roots.iter().map(|&root| frames[root].value).sum();
+
+    if let (true, Some(&first)) = (config.virtual_root, roots.first()) {
+        frames.push(Frame {
+            call: frames[first].call,
+            kind: FrameKind::Session,
+            name: "(session)".to_string(),
+            value: total_duration,
+            count: roots.iter().map(|&root| frames[root].count).sum(),
+            start_time: roots
+                .iter()
+                .map(|&root| frames[root].start_time)
+                .fold(f64::INFINITY, f64::min),
+            tags: None,
+            gap_time: 0.0,
+            durations: None,
+            runs: None,
+            children: roots,
+        });
+        roots = vec![frames.len() - 1];
+    }
+
+    let min_value = config
+        .min_duration_ms
+        .unwrap_or(0.0)
+        .max(config.min_percentage.unwrap_or(0.0) / 100.0 * total_duration);
+    if min_value > 0.0 || config.max_children.is_some() {
+        prune_children(&mut frames, min_value, config.max_children);
+    }
+    sort_children(&mut frames, config.child_order);
+
+    (frames, roots, total_duration)
+}
+
+/// Compile the search of the config, matching a substring unless it is a
+/// regular expression
+fn search_pattern(config: &FlameGraphConfig) -> Result<Option<Regex>> {
+    let Some(search) = &config.search else {
+        return Ok(None);
+    };
+    let pattern = if config.search_regex {
+        search.clone()
+    } else {
+        regex::escape(search)
+    };
+    Regex::new(&pattern)
+        .map(Some)
+        .map_err(|e| Error::from_reason(format!("Invalid search pattern: {}", e)))
+}
+
+/// Export call stacks in the collapsed format of `flamegraph.pl`
+///
+/// Each line holds a stack of `Class.method` frames from the root, separated
+/// by `;`, and the self time spent in it in whole microseconds. Identical
+/// stacks are merged and stacks without self time are left out.
+///
+/// # Arguments
+/// * `call_stack_json` - JSON string containing array of CallStackInput
+///
+/// # Returns
+/// The folded stacks, one per line
+pub fn export_folded_stacks(call_stack_json: String) -> Result<String> {
+    let calls: Vec<CallStackInput> = serde_json::from_str(&call_stack_json)
+        .map_err(|e| Error::from_reason(format!("JSON parse error: {}", e)))?;
+
+    let tree = CallTree::new(calls);
+    let (mut frames, roots) = build_frames(&tree, true, Weight::Duration, &CallFilter::default());
+    sort_children(&mut frames, ChildOrder::Chronological);
+
+    let mut output = String::new();
+    let mut path: Vec<String> = Vec::new();
+    let mut stack: Vec<(usize, usize)> = roots.iter().rev().map(|&root| (root, 0)).collect();
+
+    while let Some((index, depth)) = stack.pop() {
+        let frame = &frames[index];
+        path.truncate(depth);
+        // `;` separates frames and whitespace ends the stack
+        path.push(frame.name.replace([';', ' ', '\t', '\n', '\r'], "_"));
+
+        let children_time: f64 = frame
+            .children
+            .iter()
+            .map(|&child| frames[child].value)
+            .sum();
+        let self_micros = ((frame.value - children_time).max(0.0) * 1000.0).round();
+        if self_micros > 0.0 {
+            output.push_str(&format!("{} {}\n", path.join(";"), self_micros as u64));
+        }
+
+        stack.extend(frame.children.iter().rev().map(|&child| (child, depth + 1)));
+    }
+
+    Ok(output)
+}
+
+/// Export call stacks as a speedscope document with one evented profile
+///
+/// Calls open at their start time and close at their end time, in
+/// chronological order. Times are clamped so that they never go backwards
+/// and children close before their parent, as speedscope requires.
+///
+/// # Arguments
+/// * `call_stack_json` - JSON string containing array of CallStackInput
+///
+/// # Returns
+/// JSON string of the speedscope file
+pub fn export_speedscope(call_stack_json: String) -> Result<String> {
+    let calls: Vec<CallStackInput> = serde_json::from_str(&call_stack_json)
+        .map_err(|e| Error::from_reason(format!("JSON parse error: {}", e)))?;
+
+    let tree = CallTree::new(calls);
+    let (mut frames, mut roots) =
+        build_frames(&tree, false, Weight::Duration, &CallFilter::default());
+    sort_children(&mut frames, ChildOrder::Chronological);
+    roots.sort_by(|&a, &b| frames[a].start_time.total_cmp(&frames[b].start_time));
+
+    let mut shared: Vec<SpeedscopeFrame> = Vec::new();
+    let mut shared_index: HashMap<(&str, Option<&str>, Option<u32>), usize> = HashMap::new();
+    let mut events: Vec<SpeedscopeEvent> = Vec::new();
+    let mut last = f64::NEG_INFINITY;
+    // Frame to open, or shared frame to close
+    let mut stack: Vec<(usize, Option<usize>)> =
+        roots.iter().rev().map(|&root| (root, None)).collect();
+
+    while let Some((index, open_frame)) = stack.pop() {
+        let frame = &frames[index];
+        let (kind, shared_frame, at) = match open_frame {
+            Some(shared_frame) => ("C", shared_frame, frame.call.end_time),
+            None => {
+                let file = frame.call.file_path.as_deref();
+                let key = (frame.name.as_str(), file, frame.call.line);
+                let shared_frame = *shared_index.entry(key).or_insert_with(|| {
+                    shared.push(SpeedscopeFrame {
+                        name: frame.name.clone(),
+                        file,
+                        line: frame.call.line,
+                    });
+                    shared.len() - 1
+                });
+                stack.push((index, Some(shared_frame)));
+                stack.extend(frame.children.iter().rev().map(|&child| (child, None)));
+                ("O", shared_frame, frame.start_time)
+            }
+        };
+
+        last = at.max(last);
+        events.push(SpeedscopeEvent {
+            kind,
+            frame: shared_frame,
+            at: last,
+        });
+    }
+
+    to_json(&SpeedscopeFile {
+        schema: "https://www.speedscope.app/file-format-schema.json",
+        shared: SpeedscopeShared { frames: shared },
+        profiles: vec![SpeedscopeProfile {
+            kind: "evented",
+            name: "Angular X-Ray",
+            unit: "milliseconds",
+            start_value: events.first().map_or(0.0, |event| event.at),
+            end_value: events.last().map_or(0.0, |event| event.at),
+            events,
+        }],
+        name: "Angular X-Ray",
+        active_profile_index: 0,
+        exporter: "angular-xray",
+    })
+}
+
+/// Export call stacks in the Trace Event Format
+///
+/// Every call becomes a complete event. Viewers nest the events of a thread
+/// by time, so root calls that overlap, such as concurrent asynchronous
+/// work, are put on separate threads: one per flame chart lane, the calls
+/// below a root on its thread. Events are sorted by start, enclosing calls
+/// first.
+///
+/// # Arguments
+/// * `call_stack_json` - JSON string containing array of CallStackInput
+///
+/// # Returns
+/// JSON string of the trace
+pub fn export_trace_events(call_stack_json: String) -> Result<String> {
+    let calls: Vec<CallStackInput> = serde_json::from_str(&call_stack_json)
+        .map_err(|e| Error::from_reason(format!("JSON parse error: {}", e)))?;
+
+    let tree = CallTree::new(calls);
+    let mut threaded: Vec<(&CallStackInput, u32)> = Vec::with_capacity(tree.calls.len());
+    let mut visited: HashSet<&str> = HashSet::new();
+    for (lane, mut stack) in tree.lanes().into_iter().enumerate() {
+        while let Some(call) = stack.pop() {
+            if !visited.insert(&call.call_id) {
+                continue;
+            }
+            threaded.push((call, lane as u32 + 1));
+            stack.extend(
+                tree.children
+                    .get(&call.call_id)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|id| tree.calls.get(id)),
+            );
+        }
+    }
+
+    let length = |call: &CallStackInput| call.end_time - call.start_time;
+    threaded.sort_by(|(a, _), (b, _)| {
+        a.start_time
+            .total_cmp(&b.start_time)
+            .then(length(b).total_cmp(&length(a)))
+    });
+
+    to_json(&TraceFile {
+        trace_events: threaded
+            .into_iter()
+            .map(|(call, tid)| TraceEvent {
+                name: format!("{}.{}", call.class_name, call.method_name),
+                cat: "angular",
+                ph: "X",
+                ts: call.start_time * 1000.0,
+                dur: length(call).max(0.0) * 1000.0,
+                pid: 1,
+                tid,
+                args: TraceEventArgs {
+                    call_id: &call.call_id,
+                    file_path: call.file_path.as_deref(),
+                    line: call.line,
+                },
+            })
+            .collect(),
+        display_time_unit: "ms",
+    })
+}
+
+/// First bytes of a binary call record batch
+const RECORDS_MAGIC: &[u8; 4] = b"XRCR";
+/// Layout version of binary call records
+const RECORDS_VERSION: u32 = 1;
+/// String index standing for a missing optional string
+const NO_STRING: u32 = u32::MAX;
+
+/// Decode a batch of binary call records
+///
+/// Serializing hundreds of thousands of calls to JSON dominates the cost of
+/// large recordings, so they can be sent as a compact buffer instead. All
+/// integers are little-endian `u32` and all times little-endian `f64`:
+///
+/// - the magic `XRCR` and the layout version (1)
+/// - the string table: a count, then each string as a byte length followed
+///   by its UTF-8 bytes
+/// - the records: a count, then 48 bytes per call, holding the string
+///   indexes of the call id, class name, method name, parent call id and
+///   file path (`0xFFFFFFFF` when absent), the 1-based line (0 when absent),
+///   then the duration, start time and end time
+///
+/// Strings are validated once and interned: the class names, method names
+/// and file paths of the records referencing the same string share one
+/// allocation. Call ids, unique per call, are owned. Records carry no tags
+/// nor allocations.
+///
+/// # Returns
+/// The calls in record order, or why the buffer is malformed
+fn decode_call_records(bytes: &[u8]) -> std::result::Result<Vec<CallStackInput>, String> {
+    let mut reader = RecordReader { bytes, offset: 0 };
+
+    if reader.take(4)? != RECORDS_MAGIC {
+        return Err("missing XRCR header".to_string());
+    }
+    let version = reader.u32()?;
+    if version != RECORDS_VERSION {
+        return Err(format!("unsupported version {}", version));
+    }
+
+    let string_count = reader.u32()? as usize;
+    // Every string takes at least its length
+    let mut strings: Vec<&str> = Vec::with_capacity(string_count.min(reader.remaining() / 4));
+    for index in 0..string_count {
+        let length = reader.u32()? as usize;
+        let string = std::str::from_utf8(reader.take(length)?)
+            .map_err(|e| format!("string {} is not UTF-8: {}", index, e))?;
+        strings.push(string);
+    }
+    let text = |index: u32| -> std::result::Result<Option<&str>, String> {
+        match index {
+            NO_STRING => Ok(None),
+            _ => strings
+                .get(index as usize)
+                .map(|string| Some(*string))
+                .ok_or_else(|| format!("string index {} out of range", index)),
+        }
+    };
+    // Allocated on first use, then shared
+    let mut interned: Vec<Option<Arc<str>>> = vec![None; strings.len()];
+    let mut shared = |index: u32| -> std::result::Result<Option<Arc<str>>, String> {
+        let Some(string) = text(index)? else {
+            return Ok(None);
+        };
+        Ok(Some(
+            interned[index as usize]
+                .get_or_insert_with(|| Arc::from(string))
+                .clone(),
+        ))
+    };
+    let missing = |field: &str| format!("missing {}", field);
+
+    let record_count = reader.u32()? as usize;
+    if record_count > reader.remaining() / 48 {
+        return Err(format!("{} records do not fit in the buffer", record_count));
+    }
+    let mut calls = Vec::with_capacity(record_count);
+    for _ in 0..record_count {
+        let (call_id, class_name, method_name) = (reader.u32()?, reader.u32()?, reader.u32()?);
+        let (parent_call_id, file_path, line) = (reader.u32()?, reader.u32()?, reader.u32()?);
+        calls.push(CallStackInput {
+            call_id: text(call_id)?
+                .ok_or_else(|| missing("call id"))?
+                .to_string(),
+            class_name: shared(class_name)?.ok_or_else(|| missing("class name"))?,
+            method_name: shared(method_name)?.ok_or_else(|| missing("method name"))?,
+            parent_call_id: text(parent_call_id)?.map(str::to_string),
+            file_path: shared(file_path)?,
+            line: (line != 0).then_some(line),
+            tags: None,
+            allocated_bytes: None,
+            async_id: None,
+            trigger_async_id: None,
+            async_continuation: false,
+            run: None,
+            duration: reader.f64()?,
+            start_time: reader.f64()?,
+            end_time: reader.f64()?,
+        });
+    }
+
+    if reader.remaining() > 0 {
+        return Err(format!("{} trailing bytes", reader.remaining()));
+    }
+    Ok(calls)
+}
+
+/// Cursor over a binary call record batch
+struct RecordReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> RecordReader<'a> {
+    fn take(&mut self, length: usize) -> std::result::Result<&'a [u8], String> {
+        let bytes = self
+            .offset
+            .checked_add(length)
+            .and_then(|end| self.bytes.get(self.offset..end))
+            .ok_or_else(|| format!("truncated at byte {}", self.offset))?;
+        self.offset += length;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> std::result::Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn f64(&mut self) -> std::result::Result<f64, String> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(f64::from_le_bytes(bytes))
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+}
+
+fn gap_time(gaps: &[(f64, f64)]) -> f64 {
+    gaps.iter().map(|(start, end)| end - start).sum()
+}
+
+/// The longest gaps between consecutive children over every call, longest
+/// first
+fn largest_gaps(tree: &CallTree) -> Vec<Gap<'_>> {
+    let mut gaps: Vec<Gap> = tree
+        .calls
+        .values()
+        .flat_map(|call| {
+            tree.child_gaps(&call.call_id)
+                .into_iter()
+                .map(move |(start, end)| Gap {
+                    call_id: &call.call_id,
+                    name: format!("{}.{}", call.class_name, call.method_name),
+                    start,
+                    end,
+                    duration: end - start,
+                })
+        })
+        .collect();
+    gaps.sort_by(|a, b| {
+        b.duration
+            .total_cmp(&a.duration)
+            .then(a.start.total_cmp(&b.start))
+            .then_with(|| a.call_id.cmp(b.call_id))
+    });
+    gaps.truncate(LARGEST_GAPS);
+    gaps
+}
+
+/// Keep the calls overlapping a time window, clipped to it
+///
+/// A clipped call lasts as long as its part inside the window.
+fn clip_calls(
+    calls: Vec<CallStackInput>,
+    start: Option<f64>,
+    end: Option<f64>,
+) -> Vec<CallStackInput> {
+    if start.is_none() && end.is_none() {
+        return calls;
+    }
+    let start = start.unwrap_or(f64::NEG_INFINITY);
+    let end = end.unwrap_or(f64::INFINITY);
+
+    calls
+        .into_iter()
+        .filter(|call| call.start_time <= end && call.end_time >= start)
+        .map(|mut call| {
+            if call.start_time < start || call.end_time > end {
+                call.start_time = call.start_time.max(start);
+                call.end_time = call.end_time.min(end);
+                call.duration = call.end_time - call.start_time;
+            }
+            call
+        })
+        .collect()
+}
+
+/// Rank methods by the total time spent in their own code
+///
+/// The self time of a call is its duration minus the durations of its
+/// children. Methods are ranked by total self time, ties by name.
+///
+/// # Arguments
+/// * `call_stack_json` - JSON string containing array of CallStackInput
+/// * `limit` - Maximum number of methods returned
+///
+/// # Returns
+/// JSON string containing array of hotspots
+pub fn top_self_time(call_stack_json: String, limit: u32) -> Result<String> {
+    let calls: Vec<CallStackInput> = serde_json::from_str(&call_stack_json)
+        .map_err(|e| Error::from_reason(format!("JSON parse error: {}", e)))?;
+
+    let tree = CallTree::new(calls);
+    let mut hotspots: HashMap<(&str, &str), Hotspot> = HashMap::new();
+
+    for call in tree.calls.values() {
+        let children_time: f64 = tree
+            .children
+            .get(&call.call_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| tree.calls.get(id))
+            .map(|child| child.duration)
+            .sum();
+        let self_time = (call.duration - children_time).max(0.0);
+
+        let hotspot = hotspots
+            .entry((&call.class_name, &call.method_name))
+            .or_insert_with(|| Hotspot {
+                name: format!("{}.{}", call.class_name, call.method_name),
+                class_name: &call.class_name,
+                method_name: &call.method_name,
+                self_time: 0.0,
+                call_count: 0,
+                average_self_time: 0.0,
+                file_path: call.file_path.as_deref(),
+                line: call.line,
+            });
+        hotspot.self_time += self_time;
+        hotspot.call_count += 1;
+    }
+
+    let mut hotspots: Vec<Hotspot> = hotspots.into_values().collect();
+    for hotspot in &mut hotspots {
+        hotspot.average_self_time = hotspot.self_time / f64::from(hotspot.call_count);
+    }
+    hotspots.sort_by(|a, b| {
+        b.self_time
+            .total_cmp(&a.self_time)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    hotspots.truncate(limit as usize);
+
+    to_json(&hotspots)
+}
+
+/// Build flame chart data: every call at its recorded time
+///
+/// Unlike flame graphs nothing is merged or reordered. Root calls that
+/// overlap, such as concurrent asynchronous work, are put in separate lanes,
+/// each root taking the first lane free at its start; the calls below a
+/// root share its lane, one row per depth.
+///
+/// # Arguments
+/// * `call_stack_json` - JSON string containing array of CallStackInput
+/// * `start_time` - Only keep the calls, clipped, after this time
+/// * `end_time` - Only keep the calls, clipped, before this time
+///
+/// # Returns
+/// JSON string containing the events, sorted by lane then depth-first
+pub fn build_flame_chart_data(
+    call_stack_json: String,
+    start_time: Option<f64>,
+    end_time: Option<f64>,
+) -> Result<String> {
+    let calls: Vec<CallStackInput> = serde_json::from_str(&call_stack_json)
+        .map_err(|e| Error::from_reason(format!("JSON parse error: {}", e)))?;
+
+    let calls = clip_calls(calls, start_time, end_time);
+    let tree = CallTree::new(calls);
+
+    let mut events = Vec::new();
+    let mut lane_depths = Vec::new();
+    let mut visited: HashSet<&str> = HashSet::new();
+    for (lane, roots) in tree.lanes().into_iter().enumerate() {
+        let mut lane_depth = 0;
+        let mut stack: Vec<(&CallStackInput, u32, Option<&str>)> = roots
+            .into_iter()
+            .rev()
+            .map(|root| (root, 0, None))
+            .collect();
+        while let Some((call, depth, parent_id)) = stack.pop() {
+            if !visited.insert(&call.call_id) {
+                continue;
+            }
+            lane_depth = lane_depth.max(depth + 1);
+            events.push(ChartEvent {
+                id: &call.call_id,
+                name: format!("{}.{}", call.class_name, call.method_name),
+                start: call.start_time,
+                end: call.end_time,
+                duration: call.duration,
+                depth,
+                lane: lane as u32,
+                parent_id,
+                category: Category::of(call) as u8,
+                file_path: call.file_path.as_deref(),
+                line: call.line,
+                tags: call.tags.as_ref(),
+            });
+
+            let mut children: Vec<&CallStackInput> = tree
+                .children
+                .get(&call.call_id)
+                .into_iter()
+                .flatten()
+                .filter_map(|id| tree.calls.get(id))
+                .collect();
+            children.sort_by(by_start_then_longest);
+            stack.extend(
+                children
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, depth + 1, Some(call.call_id.as_str()))),
+            );
+        }
+        lane_depths.push(lane_depth);
+    }
+
+    let (start_time, end_time) = events
+        .iter()
+        .fold(None, |range: Option<(f64, f64)>, event| {
+            Some(range.map_or((event.start, event.end), |(start, end)| {
+                (start.min(event.start), end.max(event.end))
+            }))
+        })
+        .unwrap_or((0.0, 0.0));
+
+    to_json(&FlameChart {
+        events,
+        lane_depths,
+        start_time,
+        end_time,
+        orphaned_calls: tree.orphans.len(),
+        warnings: &tree.warnings,
+    })
+}
+
+/// Render call stacks as a standalone SVG flame graph
+///
+/// Frames are laid out bottom-up with widths proportional to their value
+/// and colored by category. Each frame has a `<title>` giving its full name,
+/// value and share of the total, which browsers show as a tooltip. Frames
+/// matching the search are drawn in a highlight color.
+///
+/// # Arguments
+/// * `call_stack_json` - JSON string containing array of CallStackInput
+/// * `config` - Flame graph construction settings
+/// * `svg` - Image settings
+///
+/// # Returns
+/// SVG document
+pub fn render_flame_graph_svg(
+    call_stack_json: String,
+    config: &FlameGraphConfig,
+    svg: &SvgConfig,
+) -> Result<String> {
+    let calls: Vec<CallStackInput> = serde_json::from_str(&call_stack_json)
+        .map_err(|e| Error::from_reason(format!("JSON parse error: {}", e)))?;
+
+    let calls = clip_calls(calls, config.start_time, config.end_time);
+    let tree = CallTree::new(calls);
+    let (frames, roots, total_duration) = prepare_frames(&tree, config);
+    let search = search_pattern(config)?;
+
+    let width = f64::from(svg.width.max(1));
+    let frame_height = svg.frame_height.max(1);
+    let scale = if total_duration > 0.0 {
+        width / total_duration
+    } else {
+        0.0
+    };
+
+    // Boxes as (frame, depth, x, width), parents before children
+    let mut boxes = Vec::new();
+    let mut stack: Vec<(usize, u32, f64)> = Vec::new();
+    let push_row = |stack: &mut Vec<(usize, u32, f64)>, row: &[usize], depth, x: f64| {
+        let start = stack.len();
+        let mut x = x;
+        for &frame in row {
+            stack.push((frame, depth, x));
+            x += frames[frame].value * scale;
+        }
+        stack[start..].reverse();
+    };
+    push_row(&mut stack, &roots, 0, 0.0);
+    while let Some((index, depth, x)) = stack.pop() {
+        let frame = &frames[index];
+        let frame_width = frame.value * scale;
+        if frame_width < SVG_MIN_FRAME_WIDTH {
+            continue;
+        }
+        boxes.push((index, depth, x, frame_width));
+        if config.max_depth.is_none_or(|max_depth| depth < max_depth) {
+            push_row(&mut stack, &frame.children, depth + 1, x);
+        }
+    }
+
+    let levels = boxes
+        .iter()
+        .map(|&(_, depth, ..)| depth + 1)
+        .max()
+        .unwrap_or(0);
+    let title_height = if svg.title.is_some() {
+        SVG_TITLE_HEIGHT
+    } else {
+        0
+    };
+    let height = title_height + levels * frame_height;
+    let unit = match config.weight {
+        Weight::Duration => "ms",
+        Weight::Count => "calls",
+        Weight::Allocation => "bytes",
+    };
+
+    let mut output = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"monospace\" font-size=\"12\">\n\
+         <style>g:hover rect{{stroke:#000;stroke-width:1}}</style>\n",
+        svg.width.max(1),
+        height
+    );
+    if let Some(title) = &svg.title {
+        output.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"18\" text-anchor=\"middle\" font-size=\"16\">{}</text>\n",
+            width / 2.0,
+            xml_escape(title)
+        ));
+    }
+
+    for (index, depth, x, frame_width) in boxes {
+        let frame = &frames[index];
+        let y = height - (depth + 1) * frame_height;
+        let matched = frame.kind == FrameKind::Calls
+            && search
+                .as_ref()
+                .is_some_and(|search| search.is_match(&frame.name));
+        let fill = if matched {
+            "rgb(230,0,230)".to_string()
+        } else {
+            svg_color(frame.category(), &frame.name)
+        };
+        let percentage = if total_duration > 0.0 {
+            frame.value / total_duration * 100.0
+        } else {
+            0.0
+        };
+
+        output.push_str(&format!(
+            "<g><title>{} ({} {}, {:.2}%)</title><rect x=\"{:.2}\" y=\"{}\" width=\"{:.2}\" height=\"{}\" fill=\"{}\" rx=\"2\"/>",
+            xml_escape(&frame.name),
+            format_value(frame.value),
+            unit,
+            percentage,
+            x,
+            y,
+            frame_width,
+            frame_height.saturating_sub(1).max(1),
+            fill
+        ));
+        // Labels are cut to the characters fitting the frame, if any
+        let fitting = ((frame_width - 6.0) / SVG_CHAR_WIDTH).floor().max(0.0) as usize;
+        if fitting >= 3 {
+            let label = if frame.name.chars().count() > fitting {
+                let cut: String = frame.name.chars().take(fitting - 2).collect();
+                cut + ".."
+            } else {
+                frame.name.clone()
+            };
+            output.push_str(&format!(
+                "<text x=\"{:.2}\" y=\"{}\">{}</text>",
+                x + 3.0,
+                y + frame_height.saturating_sub(4).max(1),
+                xml_escape(&label)
+            ));
+        }
+        output.push_str("</g>\n");
+    }
+
+    output.push_str("</svg>\n");
+    Ok(output)
+}
+
+/// Fill color of a frame: a hue per category, varied by a hash of the name
+/// so that neighbouring frames stand apart while staying stable between
+/// renders
+fn svg_color(category: Category, name: &str) -> String {
+    let hash = fnv1a(FNV_OFFSET, name.as_bytes()) as u32;
+    let (hue, saturation) = match category {
+        Category::App => (10, 85),
+        Category::Angular => (345, 70),
+        Category::Rxjs => (290, 55),
+        Category::Zone => (200, 60),
+        Category::Library => (100, 50),
+        Category::Synthetic => (0, 0),
+    };
+    let hue = (hue + hash % 30) % 360;
+    let lightness = 55 + (hash >> 8) % 15;
+    format!("hsl({},{}%,{}%)", hue, saturation, lightness)
+}
+
+/// Ids of the frames hashed from the names on their path from the root, so
+/// the same node has the same id in every recording
+///
+/// Siblings of the same name are told apart by their rank in start order,
+/// which keeps the ids independent of the child order. Pruned siblings count
+/// as one `(other)` frame whatever their number.
+///
+/// # Returns
+/// The id of each frame reachable from the roots, empty for the others
+fn path_ids(frames: &[Frame], roots: &[usize]) -> Vec<String> {
+    let mut ids = vec![String::new(); frames.len()];
+    let mut stack: Vec<(u64, &[usize])> = vec![(FNV_OFFSET, roots)];
+
+    while let Some((parent_hash, siblings)) = stack.pop() {
+        let mut ranked = siblings.to_vec();
+        ranked.sort_by(|&a, &b| frames[a].start_time.total_cmp(&frames[b].start_time));
+        let mut occurrences: HashMap<&str, u32> = HashMap::new();
+        for index in ranked {
+            let frame = &frames[index];
+            let key = match frame.kind {
+                FrameKind::Other => "(other)",
+                _ => frame.name.as_str(),
+            };
+            let occurrence = occurrences.entry(key).or_insert(0);
+            let hash = fnv1a(fnv1a(parent_hash, key.as_bytes()), &[0]);
+            let hash = fnv1a(hash, &occurrence.to_le_bytes());
+            *occurrence += 1;
+
+            ids[index] = format!("{:016x}", hash);
+            stack.push((hash, &frame.children));
+        }
+    }
+
+    ids
+}
+
+/// A value without a fraction when it is whole
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Calls indexed by id, with their children by parent id
+struct CallTree {
+    calls: HashMap<String, CallStackInput>,
+    /// Ids of the calls without parent, in input order
+    roots: Vec<String>,
+    /// Child call ids by parent id, in input order
+    children: HashMap<String, Vec<String>>,
+    /// Ids of the calls whose parent is missing, in input order
+    orphans: Vec<String>,
+    /// Problems found in the input, such as broken parent cycles
+    warnings: Vec<String>,
+}
+
+/// Order calls by start, enclosing calls first
+fn by_start_then_longest(a: &&CallStackInput, b: &&CallStackInput) -> Ordering {
+    a.start_time
+        .total_cmp(&b.start_time)
+        .then(b.end_time.total_cmp(&a.end_time))
+}
+
+impl CallTree {
+    /// Root and orphaned calls split into lanes of calls that do not
+    /// overlap, each root taking the first lane free at its start; the
+    /// roots of each lane are in start order
+    fn lanes(&self) -> Vec<Vec<&CallStackInput>> {
+        let mut roots: Vec<&CallStackInput> = self
+            .roots
+            .iter()
+            .chain(&self.orphans)
+            .filter_map(|id| self.calls.get(id))
+            .collect();
+        roots.sort_by(by_start_then_longest);
+
+        // End of the last root of each lane
+        let mut lane_ends: Vec<f64> = Vec::new();
+        let mut lanes: Vec<Vec<&CallStackInput>> = Vec::new();
+        for root in roots {
+            let lane = match lane_ends.iter().position(|&end| end <= root.start_time) {
+                Some(lane) => lane,
+                None => {
+                    lane_ends.push(f64::NEG_INFINITY);
+                    lanes.push(Vec::new());
+                    lanes.len() - 1
+                }
+            };
+            lane_ends[lane] = root.end_time.max(root.start_time);
+            lanes[lane].push(root);
+        }
+        lanes
+    }
+
+    /// Intervals between consecutive children of a call that no child
+    /// covers, in start order
+    fn child_gaps(&self, call_id: &str) -> Vec<(f64, f64)> {
+        let mut spans: Vec<(f64, f64)> = self
+            .children
+            .get(call_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.calls.get(id))
+            .map(|child| (child.start_time, child.end_time))
+            .collect();
+        spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut gaps = Vec::new();
+        let mut spans = spans.into_iter();
+        let Some((_, mut covered)) = spans.next() else {
+            return gaps;
+        };
+        for (start, end) in spans {
+            if start > covered {
+                gaps.push((covered, start));
+            }
+            covered = covered.max(end);
+        }
+        gaps
+    }
+
+    fn new(mut inputs: Vec<CallStackInput>) -> Self {
+        link_async_continuations(&mut inputs);
+
+        let mut calls: HashMap<String, CallStackInput> = HashMap::new();
+        let mut roots: Vec<String> = Vec::new();
+        let mut children: HashMap<String, Vec<String>> = HashMap::new();
+        let mut linked: Vec<(String, String)> = Vec::new();
+
+        for call in inputs {
+            match &call.parent_call_id {
+                None => roots.push(call.call_id.clone()),
+                Some(parent_id) => {
+                    children
+                        .entry(parent_id.clone())
+                        .or_default()
+                        .push(call.call_id.clone());
+                    linked.push((call.call_id.clone(), parent_id.clone()));
+                }
+            }
+            calls.insert(call.call_id.clone(), call);
+        }
+
+        // Parents dropped with trimmed buffers or lost events
+        let orphans = linked
+            .iter()
+            .filter(|(_, parent_id)| !calls.contains_key(parent_id))
+            .map(|(call_id, _)| call_id.clone())
+            .collect();
+
+        // Corrupted parent links can loop, leaving calls unreachable from
+        // any root; each cycle is cut at the first call met, which becomes
+        // a root
+        let mut warnings = Vec::new();
+        for cycle in find_parent_cycles(&calls, linked.iter().map(|(call_id, _)| call_id)) {
+            let call_id = cycle[0].clone();
+            if let Some(parent_id) = calls[&call_id].parent_call_id.as_ref() {
+                if let Some(siblings) = children.get_mut(parent_id) {
+                    siblings.retain(|id| *id != call_id);
+                }
+            }
+            warnings.push(format!(
+                "Parent cycle {} -> {} broken at {}",
+                cycle.join(" -> "),
+                call_id,
+                call_id
+            ));
+            roots.push(call_id);
+        }
+
+        Self {
+            calls,
+            roots,
+            children,
+            orphans,
+            warnings,
+        }
+    }
+}
+
+/// Give each root call running in an asynchronous context scheduled by
+/// another context a parent: the earliest call of the scheduling context
+///
+/// Continuations of timers, promises or schedulers then sit under the work
+/// that awaited them instead of being disconnected roots. Cycles this may
+/// create are broken like other parent cycles.
+fn link_async_continuations(calls: &mut [CallStackInput]) {
+    // Earliest call of each context, contexts of merged recordings apart
+    let mut anchors: HashMap<(Option<u32>, u64), usize> = HashMap::new();
+    for (index, call) in calls.iter().enumerate() {
+        if let Some(async_id) = call.async_id {
+            let anchor = anchors.entry((call.run, async_id)).or_insert(index);
+            if call.start_time < calls[*anchor].start_time {
+                *anchor = index;
+            }
+        }
+    }
+
+    for index in 0..calls.len() {
+        let call = &calls[index];
+        let Some(trigger_async_id) = call.trigger_async_id else {
+            continue;
+        };
+        if call.parent_call_id.is_some() || call.async_id == Some(trigger_async_id) {
+            continue;
+        }
+        let Some(&anchor) = anchors.get(&(call.run, trigger_async_id)) else {
+            continue;
+        };
+        calls[index].parent_call_id = Some(calls[anchor].call_id.clone());
+        calls[index].async_continuation = true;
+    }
+}
+
+/// Replace the children of every frame lasting less than `min_value`, and
+/// those beyond the `max_children` largest, with one `(other: N calls)` frame
+/// summing them
+fn prune_children(frames: &mut Vec<Frame>, min_value: f64, max_children: Option<u32>) {
+    for index in 0..frames.len() {
+        let (mut small, mut kept): (Vec<usize>, Vec<usize>) = frames[index]
+            .children
+            .iter()
+            .partition(|&&child| frames[child].value < min_value);
+        if let Some(max_children) = max_children.map(|max| max as usize) {
+            if kept.len() > max_children {
+                let mut ranked = kept.clone();
+                ranked.sort_by(|&a, &b| frames[b].value.total_cmp(&frames[a].value));
+                let dropped: HashSet<usize> = ranked[max_children..].iter().copied().collect();
+                small.extend(kept.iter().filter(|child| dropped.contains(child)));
+                kept.retain(|child| !dropped.contains(child));
+            }
+        }
+        let Some(&first) = small.first() else {
+            continue;
+        };
+
+        let count: u32 = small.iter().map(|&child| frames[child].count).sum();
+        let other = Frame {
+            call: frames[first].call,
+            kind: FrameKind::Other,
+            name: format!(
+                "(other: {} call{})",
+                count,
+                if count == 1 { "" } else { "s" }
+            ),
+            value: small.iter().map(|&child| frames[child].value).sum(),
+            count,
+            start_time: small
+                .iter()
+                .map(|&child| frames[child].start_time)
+                .fold(f64::INFINITY, f64::min),
+            tags: None,
+            gap_time: 0.0,
+            durations: None,
+            runs: None,
+            children: Vec::new(),
+        };
+        let mut children = kept;
+        children.push(frames.len());
+        frames[index].children = children;
+        frames.push(other);
+    }
+}
+
+/// Find the cycles of parent links, following each link once
+///
+/// # Returns
+/// The call ids of each cycle, from the first call met to the parent of the
+/// last, in the order the cycles are met
+fn find_parent_cycles<'a>(
+    calls: &'a HashMap<String, CallStackInput>,
+    call_ids: impl Iterator<Item = &'a String>,
+) -> Vec<Vec<String>> {
+    // Calls whose chain of parents is fully known
+    let mut done: HashSet<&str> = HashSet::new();
+    let mut cycles = Vec::new();
+
+    for call_id in call_ids {
+        let mut path: Vec<&str> = Vec::new();
+        let mut current = call_id.as_str();
+        loop {
+            if done.contains(current) {
+                break;
+            }
+            if let Some(start) = path.iter().position(|id| *id == current) {
+                cycles.push(path[start..].iter().map(|id| id.to_string()).collect());
+                break;
+            }
+            path.push(current);
+            match calls
+                .get(current)
+                .and_then(|call| call.parent_call_id.as_deref())
+                .filter(|parent_id| calls.contains_key(*parent_id))
+            {
+                Some(parent_id) => current = parent_id,
+                None => break,
+            }
+        }
+        done.extend(path);
+    }
+
+    cycles
+}
+
+/// Sort the children of every frame; the sorts are stable, so ties keep the
+/// call order
+fn sort_children(frames: &mut [Frame], order: ChildOrder) {
+    for index in 0..frames.len() {
+        let mut children = std::mem::take(&mut frames[index].children);
+        match order {
+            ChildOrder::Chronological => {
+                children.sort_by(|&a, &b| frames[a].start_time.total_cmp(&frames[b].start_time))
+            }
+            ChildOrder::LeftHeavy => children.sort_by(|&a, &b| {
+                frames[b]
+                    .value
+                    .total_cmp(&frames[a].value)
+                    .then(frames[a].start_time.total_cmp(&frames[b].start_time))
+            }),
+        }
+        frames[index].children = children;
+    }
+}
+
+/// Turn the call tree into frames, walking it with an explicit stack
+///
+/// A call id repeated in the input is visited once. Calls whose parent is
+/// missing go under a last `(orphaned)` root frame.
+///
+/// # Returns
+/// The frames, with children in call order, and the root frames
+fn build_frames<'a>(
+    tree: &'a CallTree,
+    aggregate: bool,
+    weight: Weight,
+    filter: &CallFilter,
+) -> (Vec<Frame<'a>>, Vec<usize>) {
+    let mut frames: Vec<Frame> = Vec::new();
+    // Parent of each frame, to drop the time of elided calls
+    let mut parents: Vec<Option<usize>> = Vec::new();
+    let mut root_frames = Vec::new();
+    // Frame by parent frame and name, in aggregated mode
+    let mut merged: HashMap<(Option<usize>, String), usize> = HashMap::new();
+    let mut visited: HashSet<&str> = HashSet::new();
+
+    let orphaned = tree
+        .orphans
+        .first()
+        .and_then(|id| tree.calls.get(id))
+        .map(|call| {
+            frames.push(Frame {
+                call,
+                kind: FrameKind::Orphaned,
+                name: "(orphaned)".to_string(),
+                value: 0.0,
+                count: 0,
+                start_time: f64::INFINITY,
+                tags: None,
+                gap_time: 0.0,
+                durations: None,
+                runs: None,
+                children: Vec::new(),
+            });
+            parents.push(None);
+            0
+        });
+    let mut stack: Vec<(&str, Option<usize>)> = tree
+        .orphans
+        .iter()
+        .rev()
+        .map(|id| (id.as_str(), orphaned))
+        .chain(tree.roots.iter().rev().map(|id| (id.as_str(), None)))
+        .collect();
+
+    while let Some((call_id, parent)) = stack.pop() {
+        let Some(call) = tree.calls.get(call_id) else {
+            continue;
+        };
+        if !visited.insert(call_id) {
+            continue;
+        }
+
+        let name = format!("{}.{}", call.class_name, call.method_name);
+        let children = tree.children.get(call_id).into_iter().flatten().rev();
+        // Elided calls leave their children to their parent frame
+        if filter.elides(call, &name) {
+            // Durations and allocations include the children, so the parent
+            // already holds the folded time; counts are summed from children
+            // below, so the call itself is added
+            match weight {
+                Weight::Count if !filter.drop_time => {
+                    if let Some(parent) = parent {
+                        frames[parent].value += 1.0;
+                    }
+                }
+                Weight::Duration | Weight::Allocation if filter.drop_time => {
+                    let children_value: f64 = tree
+                        .children
+                        .get(call_id)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|id| tree.calls.get(id))
+                        .map(|child| weight.of(child))
+                        .sum();
+                    let self_value = (weight.of(call) - children_value).max(0.0);
+                    let mut ancestor = parent;
+                    while let Some(index) = ancestor {
+                        frames[index].value -= self_value;
+                        ancestor = parents[index];
+                    }
+                }
+                _ => {}
+            }
+            stack.extend(children.map(|id| (id.as_str(), parent)));
+            continue;
+        }
+
+        let existing = if aggregate {
+            merged.get(&(parent, name.clone())).copied()
+        } else {
+            None
+        };
+        let index = match existing {
+            Some(index) => {
+                let frame = &mut frames[index];
+                frame.value += weight.of(call);
+                frame.gap_time += gap_time(&tree.child_gaps(call_id));
+                if let Some(durations) = &mut frame.durations {
+                    durations.push(call.duration);
+                }
+                if let (Some(runs), Some(run)) = (&mut frame.runs, call.run) {
+                    runs.insert(run);
+                }
+                frame.count += 1;
+                frame.start_time = frame.start_time.min(call.start_time);
+                if let Some(tags) = &mut frame.tags {
+                    tags.retain(|key, value| {
+                        call.tags.as_ref().and_then(|tags| tags.get(key)) == Some(value)
+                    });
+                    if tags.is_empty() {
+                        frame.tags = None;
+                    }
+                }
+                index
+            }
+            None => {
+                let index = frames.len();
+                match parent {
+                    Some(parent) => frames[parent].children.push(index),
+                    None => root_frames.push(index),
+                }
+                parents.push(parent);
+                if aggregate {
+                    merged.insert((parent, name.clone()), index);
+                }
+                frames.push(Frame {
+                    call,
+                    kind: FrameKind::Calls,
+                    name,
+                    value: weight.of(call),
+                    count: 1,
+                    start_time: call.start_time,
+                    tags: call.tags.clone(),
+                    gap_time: gap_time(&tree.child_gaps(call_id)),
+                    durations: aggregate.then(|| vec![call.duration]),
+                    runs: call.run.map(|run| HashSet::from([run])),
+                    children: Vec::new(),
+                });
+                index
+            }
+        };
+
+        stack.extend(children.map(|id| (id.as_str(), Some(index))));
+    }
+
+    // Children are created after their parent, so a reverse pass sees every
+    // child before its parent
+    if weight == Weight::Count {
+        for index in (0..frames.len()).rev() {
+            let children: f64 = frames[index]
+                .children
+                .iter()
+                .map(|&child| frames[child].value)
+                .sum();
+            frames[index].value += children;
+        }
+    }
+
+    if let Some(orphaned) = orphaned {
+        let (value, start_time) = frames[orphaned].children.iter().fold(
+            (0.0, f64::INFINITY),
+            |(value, start_time), &child| {
+                (
+                    value + frames[child].value,
+                    start_time.min(frames[child].start_time),
+                )
+            },
+        );
+        let frame = &mut frames[orphaned];
+        frame.value = value;
+        frame.start_time = start_time;
+        frame.count = tree.orphans.len() as u32;
+        root_frames.push(orphaned);
+    }
+
+    (frames, root_frames)
+}
+
+/// Number of calls in the frames below the given ones, themselves included
+fn subtree_count(frames: &[Frame], roots: &[usize]) -> u32 {
+    let mut stack = roots.to_vec();
+    let mut count = 0;
+    while let Some(index) = stack.pop() {
+        count += frames[index].count;
+        stack.extend(&frames[index].children);
+    }
+    count
 }
 
-/// Build flame graph data from call stack nodes
+/// Write the flame graph nodes of the given roots as comma separated JSON
 ///
-/// # Arguments
-/// * `call_stack_json` - JSON string containing array of CallStackInput
+/// Walks the frames with an explicit stack and writes each node as soon as
+/// it is reached, so call chains of any depth neither overflow the stack
+/// while building nor while serializing.
+///
+/// Children of nodes at `max_depth` are left out and summarized on the node.
 ///
 /// # Returns
-/// JSON string containing flame graph data structure
-pub fn build_flame_graph_data(call_stack_json: String) -> Result<String> {
-    // Parse input
-    let calls: Vec<CallStackInput> = serde_json::from_str(&call_stack_json)
-        .map_err(|e| Error::from_reason(format!("JSON parse error: {}", e)))?;
+/// The time of the nodes matching the search, nested matches counted once,
+/// and the summary of the nodes written
+fn write_nodes(
+    output: &mut String,
+    frames: &[Frame],
+    roots: &[usize],
+    total_duration: f64,
+    search: Option<&Regex>,
+    config: &FlameGraphConfig,
+) -> Result<(f64, Summary)> {
+    let mut stack = Vec::new();
+    push_steps(&mut stack, frames, roots, 0, false, 0.0, total_duration);
+    // Whether the next node opens its array
+    let mut first = true;
+    let mut matched_time = 0.0;
+    let mut summary = Summary::default();
+    let ids = config.stable_ids.then(|| path_ids(frames, roots));
 
-    if calls.is_empty() {
-        return Ok(serde_json::json!({
-            "nodes": [],
-            "totalDuration": 0.0
-        })
-        .to_string());
-    }
+    while let Some(step) = stack.pop() {
+        let (index, depth, in_match, offset, parent_value) = match step {
+            Step::Open {
+                frame,
+                depth,
+                in_match,
+                offset,
+                parent_value,
+            } => (frame, depth, in_match, offset, parent_value),
+            Step::Close => {
+                output.push_str("]}");
+                first = false;
+                continue;
+            }
+        };
+        let frame = &frames[index];
+
+        // Calculate self time (time excluding children)
+        let children_time: f64 = frame
+            .children
+            .iter()
+            .map(|&child| frames[child].value)
+            .sum();
+        let self_time = frame.value - children_time;
+
+        // Synthetic frames have no location of their own
+        let other_id;
+        let call_id = (frame.kind == FrameKind::Calls).then_some(frame.call.call_id.as_str());
+        let (id, file_path, line) = match frame.kind {
+            FrameKind::Calls => (
+                frame.call.call_id.as_str(),
+                frame.call.file_path.as_deref(),
+                frame.call.line,
+            ),
+            FrameKind::Other => {
+                other_id = format!("{}:other", frame.call.call_id);
+                (other_id.as_str(), None, None)
+            }
+            FrameKind::Orphaned => ("orphaned", None, None),
+            FrameKind::Session => ("session", None, None),
+        };
+        let (id, call_id) = match &ids {
+            Some(ids) => (ids[index].as_str(), call_id),
+            None => (id, None),
+        };
 
-    // Build call map for O(1) lookups
-    let mut call_map: HashMap<String, CallStackInput> = HashMap::new();
-    let mut roots: Vec<String> = Vec::new();
+        let matched = frame.kind == FrameKind::Calls
+            && search.is_some_and(|search| search.is_match(&frame.name));
+        let category = frame.category();
+        if matched && !in_match {
+            matched_time += frame.value;
+        }
 
-    for call in calls {
-        if call.parent_call_id.is_none() {
-            roots.push(call.call_id.clone());
+        summary.max_depth = summary.max_depth.max(depth);
+        summary.node_count += 1;
+        let level = depth as usize;
+        if summary.self_time_by_depth.len() <= level {
+            summary.self_time_by_depth.resize(level + 1, 0.0);
+        }
+        summary.self_time_by_depth[level] += self_time.max(0.0);
+        let wider = summary.widest_frame.as_ref().is_none_or(|widest| {
+            frame.value > widest.value || (frame.value == widest.value && depth > widest.depth)
+        });
+        if frame.kind == FrameKind::Calls && wider {
+            summary.widest_frame = Some(WidestFrame {
+                id: id.to_string(),
+                name: frame.name.clone(),
+                value: frame.value,
+                depth,
+            });
         }
-        call_map.insert(call.call_id.clone(), call);
-    }
 
-    // Calculate total duration from root nodes
-    let total_duration: f64 = roots
-        .iter()
-        .filter_map(|id| call_map.get(id))
-        .map(|c| c.duration)
-        .sum();
+        let truncated = config.max_depth.is_some_and(|max_depth| depth >= max_depth)
+            && !frame.children.is_empty();
+        let children: &[usize] = if truncated { &[] } else { &frame.children };
 
-    // Build flame graph nodes
-    let flame_nodes: Vec<FlameGraphNode> = roots
-        .iter()
-        .filter_map(|id| build_node(id, &call_map, 0, total_duration))
-        .collect();
+        let base = if config.percent_of_parent {
+            parent_value
+        } else {
+            total_duration
+        };
+        let node = to_json(&FlameGraphNode {
+            id,
+            call_id,
+            name: frame.name.clone(),
+            value: frame.value,
+            self_value: self_time.max(0.0), // Ensure non-negative
+            depth,
+            file_path,
+            line,
+            percentage: if base > 0.0 {
+                (frame.value / base) * 100.0
+            } else {
+                0.0
+            },
+            count: frame.count,
+            matched,
+            truncated_children: truncated.then(|| subtree_count(frames, &frame.children)),
+            truncated_time: truncated.then_some(children_time),
+            tags: frame.tags.as_ref(),
+            category: category as u8,
+            gap_time: frame.gap_time,
+            stats: frame.durations.as_deref().and_then(DurationStats::new),
+            layout: config
+                .layout
+                .then(|| NodeLayout::new(offset, frame.value, total_duration)),
+            async_continuation: frame.kind == FrameKind::Calls && frame.call.async_continuation,
+            run_count: frame.runs.as_ref().map(|runs| runs.len() as u32),
+        })?;
 
-    // Create result
-    let result = serde_json::json!({
-        "nodes": flame_nodes,
-        "totalDuration": total_duration
-    });
+        if !first {
+            output.push(',');
+        }
+        if children.is_empty() {
+            output.push_str(&node);
+            first = false;
+        } else {
+            // Reopen the serialized object to append its children
+            output.push_str(&node[..node.len() - 1]);
+            output.push_str(",\"children\":[");
+            first = true;
+            stack.push(Step::Close);
+            push_steps(
+                &mut stack,
+                frames,
+                children,
+                depth + 1,
+                in_match || matched,
+                offset,
+                frame.value,
+            );
+        }
+    }
 
-    serde_json::to_string(&result)
-        .map_err(|e| Error::from_reason(format!("JSON stringify error: {}", e)))
+    Ok((matched_time, summary))
 }
 
-/// Recursively build flame graph node
-fn build_node(
-    call_id: &str,
-    call_map: &HashMap<String, CallStackInput>,
+/// Queue sibling frames to be opened in order, packed from `offset`
+fn push_steps(
+    stack: &mut Vec<Step>,
+    frames: &[Frame],
+    siblings: &[usize],
     depth: u32,
-    total_duration: f64,
-) -> Option<FlameGraphNode> {
-    let call = call_map.get(call_id)?;
-
-    // Find children
-    let children: Vec<FlameGraphNode> = call_map
-        .values()
-        .filter(|c| c.parent_call_id.as_deref() == Some(call_id))
-        .filter_map(|c| build_node(&c.call_id, call_map, depth + 1, total_duration))
-        .collect();
+    in_match: bool,
+    offset: f64,
+    parent_value: f64,
+) {
+    let start = stack.len();
+    let mut offset = offset;
+    for &frame in siblings {
+        stack.push(Step::Open {
+            frame,
+            depth,
+            in_match,
+            offset,
+            parent_value,
+        });
+        offset += frames[frame].value;
+    }
+    stack[start..].reverse();
+}
 
-    // Calculate self time (time excluding children)
-    let children_time: f64 = children.iter().map(|c| c.value).sum();
-    let self_time = call.duration - children_time;
-
-    Some(FlameGraphNode {
-        id: call.call_id.clone(),
-        name: format!("{}.{}", call.class_name, call.method_name),
-        value: call.duration,
-        self_value: self_time.max(0.0), // Ensure non-negative
-        children,
-        depth,
-        file_path: call.file_path.clone(),
-        line: call.line,
-        percentage: if total_duration > 0.0 {
-            (call.duration / total_duration) * 100.0
-        } else {
-            0.0
-        },
-    })
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value)
+        .map_err(|e| Error::from_reason(format!("JSON stringify error: {}", e)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Call input of the `Class.method` `name` from `start` to `end`
+    fn call(id: &str, name: &str, start: f64, end: f64, parent: Option<&str>) -> serde_json::Value {
+        let (class_name, method_name) = name.split_once('.').unwrap();
+        serde_json::json!({
+            "callId": id,
+            "className": class_name,
+            "methodName": method_name,
+            "duration": end - start,
+            "startTime": start,
+            "endTime": end,
+            "parentCallId": parent
+        })
+    }
+
+    /// `call` with one more field, such as `filePath` or `tags`
+    fn with(
+        mut call: serde_json::Value,
+        field: &str,
+        value: impl Into<serde_json::Value>,
+    ) -> serde_json::Value {
+        call[field] = value.into();
+        call
+    }
+
     #[test]
     fn test_empty_call_stack() {
-        let result = build_flame_graph_data("[]".to_string()).unwrap();
+        let result =
+            build_flame_graph_data("[]".to_string(), &FlameGraphConfig::default()).unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
         assert_eq!(parsed["totalDuration"], 0.0);
         assert!(parsed["nodes"].as_array().unwrap().is_empty());
@@ -151,7 +2327,8 @@ mod tests {
             "parentCallId": null
         }]"#;
 
-        let result = build_flame_graph_data(input.to_string()).unwrap();
+        let result =
+            build_flame_graph_data(input.to_string(), &FlameGraphConfig::default()).unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
 
         assert_eq!(parsed["totalDuration"], 100.0);
@@ -183,7 +2360,8 @@ mod tests {
             }
         ]"#;
 
-        let result = build_flame_graph_data(input.to_string()).unwrap();
+        let result =
+            build_flame_graph_data(input.to_string(), &FlameGraphConfig::default()).unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
 
         assert_eq!(parsed["totalDuration"], 100.0);
@@ -192,4 +2370,1374 @@ mod tests {
         assert_eq!(parsed["nodes"][0]["children"].as_array().unwrap().len(), 1);
         assert_eq!(parsed["nodes"][0]["children"][0]["value"], 60.0);
     }
+
+    #[test]
+    fn test_wide_call_tree() {
+        let mut calls = vec![serde_json::json!({
+            "callId": "root",
+            "className": "App",
+            "methodName": "render",
+            "duration": 100000.0,
+            "startTime": 0.0,
+            "endTime": 100000.0,
+            "parentCallId": null
+        })];
+        for i in 0..20_000 {
+            calls.push(serde_json::json!({
+                "callId": format!("call_{}", i),
+                "className": "Row",
+                "methodName": format!("render{}", i),
+                "duration": 1.0,
+                "startTime": i as f64,
+                "endTime": i as f64 + 1.0,
+                "parentCallId": "root"
+            }));
+        }
+
+        let result = build_flame_graph_data(
+            serde_json::Value::from(calls).to_string(),
+            &FlameGraphConfig::default(),
+        )
+        .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        let children = parsed["nodes"][0]["children"].as_array().unwrap();
+        assert_eq!(children.len(), 20_000);
+        // Children in start order
+        assert_eq!(children[0]["name"], "Row.render0");
+        assert_eq!(children[19_999]["name"], "Row.render19999");
+        assert_eq!(parsed["nodes"][0]["selfValue"], 80000.0);
+    }
+
+    #[test]
+    fn test_deep_call_chain() {
+        let depth = 100_000;
+        let calls: Vec<_> = (0..depth)
+            .map(|i| {
+                serde_json::json!({
+                    "callId": format!("call_{}", i),
+                    "className": "Tree",
+                    "methodName": "visit",
+                    "duration": (depth - i) as f64,
+                    "startTime": i as f64,
+                    "endTime": depth as f64,
+                    "parentCallId": if i == 0 { None } else { Some(format!("call_{}", i - 1)) }
+                })
+            })
+            .collect();
+
+        let result = build_flame_graph_data(
+            serde_json::Value::from(calls).to_string(),
+            &FlameGraphConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(result.matches("\"children\":[").count(), depth - 1);
+        // Every children array is closed
+        assert_eq!(result.matches("]}").count(), depth - 1);
+        assert!(result.starts_with(r#"{"nodes":[{"id":"call_0","name":"Tree.visit","value":100000.0,"selfValue":1.0,"depth":0,"#));
+        assert!(result.contains(
+            r#"{"id":"call_99999","name":"Tree.visit","value":1.0,"selfValue":1.0,"depth":99999"#
+        ));
+        assert!(result.contains(
+            r#"],"totalDuration":100000.0,"orphanedCalls":0,"warnings":[],"largestGaps":[],"summary":{"maxDepth":99999,"nodeCount":100000,"selfTimeByDepth":[1.0,"#
+        ));
+        assert!(result.ends_with(
+            r#"1.0],"widestFrame":{"id":"call_0","name":"Tree.visit","value":100000.0,"depth":0}}}"#
+        ));
+    }
+
+    #[test]
+    fn test_child_order() {
+        let input = serde_json::json!([
+            call("root", "App.root", 0.0, 100.0, None),
+            call("late", "App.late", 70.0, 80.0, Some("root")),
+            call("early", "App.early", 5.0, 25.0, Some("root")),
+            call("wide", "App.wide", 30.0, 70.0, Some("root")),
+            call("tied", "App.tied", 50.0, 60.0, Some("root")),
+        ])
+        .to_string();
+        let names = |order: ChildOrder| {
+            let config = FlameGraphConfig {
+                child_order: order,
+                ..Default::default()
+            };
+            let result = build_flame_graph_data(input.clone(), &config).unwrap();
+            let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+            parsed["nodes"][0]["children"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|child| child["name"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(ChildOrder::Chronological),
+            vec!["App.early", "App.wide", "App.tied", "App.late"]
+        );
+        assert_eq!(
+            names(ChildOrder::LeftHeavy),
+            vec!["App.wide", "App.early", "App.tied", "App.late"]
+        );
+        assert_eq!(ChildOrder::parse("left-heavy"), Some(ChildOrder::LeftHeavy));
+        assert_eq!(ChildOrder::parse("random"), None);
+    }
+
+    #[test]
+    fn test_aggregated_frames() {
+        let call =
+            |id: &str, method: &str, duration: f64, start_time: f64, parent: Option<&str>| {
+                serde_json::json!({
+                    "callId": id,
+                    "className": "List",
+                    "methodName": method,
+                    "duration": duration,
+                    "startTime": start_time,
+                    "endTime": start_time + duration,
+                    "parentCallId": parent
+                })
+            };
+        let input = serde_json::json!([
+            call("root", "render", 100.0, 0.0, None),
+            call("row_1", "renderRow", 20.0, 10.0, Some("root")),
+            call("cell_1", "format", 5.0, 12.0, Some("row_1")),
+            call("sort", "sort", 10.0, 1.0, Some("root")),
+            call("row_2", "renderRow", 30.0, 40.0, Some("root")),
+            call("cell_2", "format", 15.0, 45.0, Some("row_2")),
+        ])
+        .to_string();
+        let config = FlameGraphConfig {
+            aggregate: true,
+            ..Default::default()
+        };
+
+        let result = build_flame_graph_data(input, &config).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        let root = &parsed["nodes"][0];
+        assert_eq!(root["count"], 1);
+        assert_eq!(root["selfValue"], 40.0);
+        let children = root["children"].as_array().unwrap();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0]["name"], "List.sort");
+        assert_eq!(children[1]["id"], "row_1");
+        assert_eq!(children[1]["name"], "List.renderRow");
+        assert_eq!(children[1]["count"], 2);
+        assert_eq!(children[1]["value"], 50.0);
+        assert_eq!(children[1]["percentage"], 50.0);
+        assert_eq!(children[1]["selfValue"], 30.0);
+        assert_eq!(children[1]["children"][0]["count"], 2);
+        assert_eq!(children[1]["children"][0]["value"], 20.0);
+    }
+
+    #[test]
+    fn test_export_folded_stacks() {
+        let call =
+            |id: &str, method: &str, duration: f64, start_time: f64, parent: Option<&str>| {
+                serde_json::json!({
+                    "callId": id,
+                    "className": "List",
+                    "methodName": method,
+                    "duration": duration,
+                    "startTime": start_time,
+                    "endTime": start_time + duration,
+                    "parentCallId": parent
+                })
+            };
+        let input = serde_json::json!([
+            call("root", "render", 100.0, 0.0, None),
+            call("row_1", "renderRow", 20.0, 10.0, Some("root")),
+            call("cell_1", "format", 5.0, 12.0, Some("row_1")),
+            call("row_2", "renderRow", 30.0, 40.0, Some("root")),
+            call("cell_2", "format", 15.0, 45.0, Some("row_2")),
+            call("sort", "sort; by name", 0.5, 1.0, Some("root")),
+            call("noop", "noop", 0.0, 2.0, Some("root")),
+        ])
+        .to_string();
+
+        assert_eq!(
+            export_folded_stacks(input).unwrap(),
+            "List.render 49500\n\
+             List.render;List.sort__by_name 500\n\
+             List.render;List.renderRow 30000\n\
+             List.render;List.renderRow;List.format 20000\n"
+        );
+        assert_eq!(export_folded_stacks("[]".to_string()).unwrap(), "");
+    }
+
+    #[test]
+    fn test_export_speedscope() {
+        let input = r#"[
+            { "callId": "late", "className": "App", "methodName": "tick", "duration": 5.0,
+              "startTime": 50.0, "endTime": 55.0, "parentCallId": null },
+            { "callId": "root", "className": "App", "methodName": "render", "duration": 20.0,
+              "startTime": 10.0, "endTime": 30.0, "parentCallId": null,
+              "filePath": "src/app.component.ts", "line": 12 },
+            { "callId": "child", "className": "App", "methodName": "tick", "duration": 25.0,
+              "startTime": 15.0, "endTime": 40.0, "parentCallId": "root" }
+        ]"#;
+
+        let result = export_speedscope(input.to_string()).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(
+            parsed["$schema"],
+            "https://www.speedscope.app/file-format-schema.json"
+        );
+        assert_eq!(
+            parsed["shared"]["frames"],
+            serde_json::json!([
+                { "name": "App.render", "file": "src/app.component.ts", "line": 12 },
+                { "name": "App.tick" },
+            ])
+        );
+        let profile = &parsed["profiles"][0];
+        assert_eq!(profile["type"], "evented");
+        assert_eq!(profile["startValue"], 10.0);
+        assert_eq!(profile["endValue"], 55.0);
+        let events: Vec<_> = profile["events"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| {
+                (
+                    event["type"].as_str().unwrap(),
+                    event["frame"].as_u64().unwrap(),
+                    event["at"].as_f64().unwrap(),
+                )
+            })
+            .collect();
+        // The child ends after its parent, so the parent closes with it
+        assert_eq!(
+            events,
+            vec![
+                ("O", 0, 10.0),
+                ("O", 1, 15.0),
+                ("C", 1, 40.0),
+                ("C", 0, 40.0),
+                ("O", 1, 50.0),
+                ("C", 1, 55.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_export_trace_events() {
+        let input = r#"[
+            { "callId": "child", "className": "App", "methodName": "tick", "duration": 5.0,
+              "startTime": 10.0, "endTime": 15.0, "parentCallId": "root" },
+            { "callId": "root", "className": "App", "methodName": "render", "duration": 20.5,
+              "startTime": 10.0, "endTime": 30.5, "parentCallId": null,
+              "filePath": "src/app.component.ts", "line": 12 },
+            { "callId": "fetch", "className": "Api", "methodName": "load", "duration": 8.0,
+              "startTime": 12.0, "endTime": 20.0, "parentCallId": null }
+        ]"#;
+
+        let result = export_trace_events(input.to_string()).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(parsed["displayTimeUnit"], "ms");
+        assert_eq!(
+            parsed["traceEvents"],
+            serde_json::json!([
+                {
+                    "name": "App.render", "cat": "angular", "ph": "X",
+                    "ts": 10000.0, "dur": 20500.0, "pid": 1, "tid": 1,
+                    "args": { "callId": "root", "filePath": "src/app.component.ts", "line": 12 }
+                },
+                {
+                    "name": "App.tick", "cat": "angular", "ph": "X",
+                    "ts": 10000.0, "dur": 5000.0, "pid": 1, "tid": 1,
+                    "args": { "callId": "child" }
+                },
+                {
+                    "name": "Api.load", "cat": "angular", "ph": "X",
+                    "ts": 12000.0, "dur": 8000.0, "pid": 1, "tid": 2,
+                    "args": { "callId": "fetch" }
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_time_range() {
+        let input = r#"[
+            { "callId": "root", "className": "App", "methodName": "render", "duration": 100.0,
+              "startTime": 0.0, "endTime": 100.0, "parentCallId": null },
+            { "callId": "before", "className": "App", "methodName": "init", "duration": 10.0,
+              "startTime": 5.0, "endTime": 15.0, "parentCallId": "root" },
+            { "callId": "across", "className": "App", "methodName": "click", "duration": 30.0,
+              "startTime": 30.0, "endTime": 60.0, "parentCallId": "root" },
+            { "callId": "inside", "className": "App", "methodName": "save", "duration": 5.0,
+              "startTime": 52.0, "endTime": 57.0, "parentCallId": "across" },
+            { "callId": "idle", "className": "App", "methodName": "idle", "duration": 10.0,
+              "startTime": 200.0, "endTime": 210.0, "parentCallId": null }
+        ]"#;
+        let config = FlameGraphConfig {
+            start_time: Some(50.0),
+            end_time: Some(80.0),
+            ..Default::default()
+        };
+
+        let result = build_flame_graph_data(input.to_string(), &config).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(parsed["totalDuration"], 30.0);
+        let nodes = parsed["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0]["value"], 30.0);
+        let children = nodes[0]["children"].as_array().unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0]["name"], "App.click");
+        assert_eq!(children[0]["value"], 10.0);
+        assert_eq!(children[0]["selfValue"], 5.0);
+        assert_eq!(children[0]["children"][0]["value"], 5.0);
+    }
+
+    #[test]
+    fn test_prune_small_children() {
+        let mut calls = vec![serde_json::json!({
+            "callId": "root", "className": "App", "methodName": "render", "duration": 100.0,
+            "startTime": 0.0, "endTime": 100.0, "parentCallId": null
+        })];
+        for (i, duration) in [50.0, 0.5, 2.0, 0.2].into_iter().enumerate() {
+            calls.push(serde_json::json!({
+                "callId": format!("call_{}", i), "className": "Row", "methodName": "render",
+                "duration": duration, "startTime": i as f64, "endTime": i as f64 + duration,
+                "parentCallId": "root"
+            }));
+        }
+        calls.push(serde_json::json!({
+            "callId": "nested", "className": "Cell", "methodName": "render", "duration": 0.1,
+            "startTime": 1.0, "endTime": 1.1, "parentCallId": "call_1"
+        }));
+        let input = serde_json::Value::from(calls).to_string();
+        let children = |config: FlameGraphConfig| {
+            let result = build_flame_graph_data(input.clone(), &config).unwrap();
+            let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+            parsed["nodes"][0]["children"].as_array().unwrap().clone()
+        };
+
+        let pruned = children(FlameGraphConfig {
+            min_percentage: Some(1.0),
+            ..Default::default()
+        });
+        assert_eq!(pruned.len(), 3);
+        assert_eq!(pruned[0]["id"], "call_0");
+        assert_eq!(pruned[1]["id"], "call_1:other");
+        assert_eq!(pruned[1]["name"], "(other: 2 calls)");
+        assert!((pruned[1]["value"].as_f64().unwrap() - 0.7).abs() < 1e-9);
+        assert!(pruned[1].get("children").is_none());
+        assert_eq!(pruned[2]["id"], "call_2");
+
+        let pruned = children(FlameGraphConfig {
+            min_duration_ms: Some(1.0),
+            min_percentage: Some(2.5),
+            ..Default::default()
+        });
+        assert_eq!(pruned.len(), 2);
+        assert_eq!(pruned[1]["name"], "(other: 3 calls)");
+        assert_eq!(children(FlameGraphConfig::default()).len(), 4);
+    }
+
+    #[test]
+    fn test_top_self_time() {
+        let input = serde_json::json!([
+            call("root", "List.render", 0.0, 100.0, None),
+            call("row_1", "List.renderRow", 0.0, 30.0, Some("root")),
+            call("cell_1", "List.format", 0.0, 25.0, Some("row_1")),
+            call("row_2", "List.renderRow", 0.0, 20.0, Some("root")),
+            call("cell_2", "List.format", 0.0, 15.0, Some("row_2")),
+            call("sort", "List.sort", 0.0, 1.0, Some("root")),
+        ])
+        .to_string();
+
+        let result = top_self_time(input, 2).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(
+            parsed,
+            serde_json::json!([
+                {
+                    "name": "List.render", "className": "List", "methodName": "render",
+                    "selfTime": 49.0, "callCount": 1, "averageSelfTime": 49.0
+                },
+                {
+                    "name": "List.format", "className": "List", "methodName": "format",
+                    "selfTime": 40.0, "callCount": 2, "averageSelfTime": 20.0
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_search() {
+        let input = serde_json::json!([
+            call("root", "AppComponent.request", 0.0, 100.0, None),
+            call("http", "HttpClient.request", 0.0, 40.0, Some("root")),
+            call("nested", "HttpClient.request", 0.0, 10.0, Some("http")),
+            call("store", "UserStore.request", 0.0, 30.0, Some("root")),
+            call("backend", "HttpBackend.request", 0.0, 20.0, Some("store")),
+        ])
+        .to_string();
+        let search = |search: &str, search_regex: bool| {
+            let config = FlameGraphConfig {
+                search: Some(search.to_string()),
+                search_regex,
+                ..Default::default()
+            };
+            build_flame_graph_data(input.clone(), &config)
+        };
+
+        let parsed: serde_json::Value =
+            serde_json::from_str(&search("HttpClient", false).unwrap()).unwrap();
+        assert_eq!(parsed["matchedTime"], 40.0);
+        let root = &parsed["nodes"][0];
+        assert!(root.get("matched").is_none());
+        assert_eq!(root["children"][0]["matched"], true);
+        assert_eq!(root["children"][0]["children"][0]["matched"], true);
+        assert!(root["children"][1].get("matched").is_none());
+
+        let parsed: serde_json::Value =
+            serde_json::from_str(&search("^Http(Client|Backend)\\.", true).unwrap()).unwrap();
+        assert_eq!(parsed["matchedTime"], 60.0);
+
+        assert!(search("(", true).is_err());
+        let parsed: serde_json::Value = serde_json::from_str(&search("(", false).unwrap()).unwrap();
+        assert_eq!(parsed["matchedTime"], 0.0);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let calls: Vec<_> = (0..10)
+            .map(|i| {
+                serde_json::json!({
+                    "callId": format!("call_{}", i),
+                    "className": "Tree",
+                    "methodName": "visit",
+                    "duration": (10 - i) as f64,
+                    "startTime": 0.0,
+                    "endTime": (10 - i) as f64,
+                    "parentCallId": if i == 0 { None } else { Some(format!("call_{}", i - 1)) }
+                })
+            })
+            .collect();
+        let config = FlameGraphConfig {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+
+        let result =
+            build_flame_graph_data(serde_json::Value::from(calls).to_string(), &config).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        let first = &parsed["nodes"][0]["children"][0];
+        assert!(first.get("truncatedChildren").is_none());
+        let last = &first["children"][0];
+        assert_eq!(last["depth"], 2);
+        assert!(last.get("children").is_none());
+        assert_eq!(last["truncatedChildren"], 7);
+        assert_eq!(last["truncatedTime"], 7.0);
+        assert_eq!(last["selfValue"], 1.0);
+    }
+
+    #[test]
+    fn test_orphaned_calls() {
+        let input = r#"[
+            { "callId": "root", "className": "App", "methodName": "render", "duration": 10.0,
+              "startTime": 0.0, "endTime": 10.0, "parentCallId": null },
+            { "callId": "lost_1", "className": "App", "methodName": "save", "duration": 4.0,
+              "startTime": 20.0, "endTime": 24.0, "parentCallId": "trimmed" },
+            { "callId": "child", "className": "App", "methodName": "validate", "duration": 1.0,
+              "startTime": 21.0, "endTime": 22.0, "parentCallId": "lost_1" },
+            { "callId": "lost_2", "className": "App", "methodName": "load", "duration": 6.0,
+              "startTime": 15.0, "endTime": 21.0, "parentCallId": "dropped" }
+        ]"#;
+
+        let result =
+            build_flame_graph_data(input.to_string(), &FlameGraphConfig::default()).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(parsed["orphanedCalls"], 2);
+        assert_eq!(parsed["totalDuration"], 20.0);
+        let nodes = parsed["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0]["id"], "root");
+        assert_eq!(nodes[1]["id"], "orphaned");
+        assert_eq!(nodes[1]["name"], "(orphaned)");
+        assert_eq!(nodes[1]["value"], 10.0);
+        assert_eq!(nodes[1]["selfValue"], 0.0);
+        assert_eq!(nodes[1]["percentage"], 50.0);
+        let orphans = nodes[1]["children"].as_array().unwrap();
+        assert_eq!(orphans[0]["name"], "App.load");
+        assert_eq!(orphans[1]["name"], "App.save");
+        assert_eq!(orphans[1]["children"][0]["name"], "App.validate");
+
+        assert_eq!(
+            export_folded_stacks(input.to_string()).unwrap(),
+            "App.render 10000\n\
+             (orphaned);App.load 6000\n\
+             (orphaned);App.save 3000\n\
+             (orphaned);App.save;App.validate 1000\n"
+        );
+    }
+
+    #[test]
+    fn test_parent_cycles() {
+        let input = serde_json::json!([
+            call("root", "App.root", 0.0, 1.0, None),
+            call("a", "App.a", 0.0, 1.0, Some("c")),
+            call("b", "App.b", 0.0, 1.0, Some("a")),
+            call("c", "App.c", 0.0, 1.0, Some("b")),
+            call("leaf", "App.leaf", 0.0, 1.0, Some("b")),
+            call("self", "App.self", 0.0, 1.0, Some("self")),
+        ])
+        .to_string();
+
+        let result = build_flame_graph_data(input, &FlameGraphConfig::default()).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(
+            parsed["warnings"],
+            serde_json::json!([
+                "Parent cycle a -> c -> b -> a broken at a",
+                "Parent cycle self -> self broken at self",
+            ])
+        );
+        let nodes = parsed["nodes"].as_array().unwrap();
+        let ids: Vec<_> = nodes
+            .iter()
+            .map(|node| node["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["root", "a", "self"]);
+        assert_eq!(nodes[1]["children"][0]["id"], "b");
+        assert_eq!(nodes[1]["children"][0]["children"][0]["id"], "c");
+        assert_eq!(nodes[1]["children"][0]["children"][1]["id"], "leaf");
+        assert!(nodes[2].get("children").is_none());
+    }
+
+    #[test]
+    fn test_virtual_root() {
+        let input = r#"[
+            { "callId": "second", "className": "App", "methodName": "tick", "duration": 25.0,
+              "startTime": 50.0, "endTime": 75.0, "parentCallId": null },
+            { "callId": "first", "className": "App", "methodName": "render", "duration": 75.0,
+              "startTime": 0.0, "endTime": 75.0, "parentCallId": null }
+        ]"#;
+        let config = FlameGraphConfig {
+            virtual_root: true,
+            ..Default::default()
+        };
+
+        let result = build_flame_graph_data(input.to_string(), &config).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        let nodes = parsed["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0]["id"], "session");
+        assert_eq!(nodes[0]["name"], "(session)");
+        assert_eq!(nodes[0]["value"], 100.0);
+        assert_eq!(nodes[0]["selfValue"], 0.0);
+        assert_eq!(nodes[0]["percentage"], 100.0);
+        assert_eq!(nodes[0]["count"], 2);
+        let children = nodes[0]["children"].as_array().unwrap();
+        assert_eq!(children[0]["id"], "first");
+        assert_eq!(children[0]["depth"], 1);
+        assert_eq!(children[0]["percentage"], 75.0);
+        assert_eq!(children[1]["id"], "second");
+
+        let result = build_flame_graph_data("[]".to_string(), &config).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(parsed["nodes"].as_array().unwrap().is_empty());
+    }
+
+    /// Encode calls in the binary record layout, one string table entry per
+    /// distinct string
+    fn encode_call_records(calls: &[serde_json::Value]) -> Vec<u8> {
+        let mut strings: Vec<String> = Vec::new();
+        let mut index = |value: &serde_json::Value| match value.as_str() {
+            Some(text) => match strings.iter().position(|string| string == text) {
+                Some(position) => position as u32,
+                None => {
+                    strings.push(text.to_string());
+                    strings.len() as u32 - 1
+                }
+            },
+            None => NO_STRING,
+        };
+        let mut records = Vec::new();
+        for call in calls {
+            for field in [
+                "callId",
+                "className",
+                "methodName",
+                "parentCallId",
+                "filePath",
+            ] {
+                records.extend(index(&call[field]).to_le_bytes());
+            }
+            records.extend((call["line"].as_u64().unwrap_or(0) as u32).to_le_bytes());
+            for field in ["duration", "startTime", "endTime"] {
+                records.extend(call[field].as_f64().unwrap().to_le_bytes());
+            }
+        }
+
+        let mut bytes = RECORDS_MAGIC.to_vec();
+        bytes.extend(RECORDS_VERSION.to_le_bytes());
+        bytes.extend((strings.len() as u32).to_le_bytes());
+        for string in &strings {
+            bytes.extend((string.len() as u32).to_le_bytes());
+            bytes.extend(string.as_bytes());
+        }
+        bytes.extend((calls.len() as u32).to_le_bytes());
+        bytes.extend(records);
+        bytes
+    }
+
+    #[test]
+    fn test_binary_call_records() {
+        let calls = vec![
+            serde_json::json!({
+                "callId": "root", "className": "App", "methodName": "render", "duration": 100.0,
+                "startTime": 0.0, "endTime": 100.0, "parentCallId": null,
+                "filePath": "src/app.component.ts", "line": 12
+            }),
+            serde_json::json!({
+                "callId": "child", "className": "App", "methodName": "tick", "duration": 60.5,
+                "startTime": 10.0, "endTime": 70.5, "parentCallId": "root"
+            }),
+        ];
+        let records = encode_call_records(&calls);
+        let config = FlameGraphConfig::default();
+
+        assert_eq!(
+            build_flame_graph_data_from_records(&records, &config).unwrap(),
+            build_flame_graph_data(serde_json::Value::from(calls).to_string(), &config).unwrap()
+        );
+
+        // Records naming the same class share its string
+        let decoded = decode_call_records(&records).unwrap();
+        assert!(Arc::ptr_eq(&decoded[0].class_name, &decoded[1].class_name));
+
+        let error = |bytes: &[u8]| decode_call_records(bytes).err().unwrap();
+        assert_eq!(error(b"JSON"), "missing XRCR header");
+        assert_eq!(
+            error(&records[..records.len() - 1]),
+            "2 records do not fit in the buffer"
+        );
+        assert_eq!(error(&records[..10]), "truncated at byte 8");
+        let mut trailing = records.clone();
+        trailing.push(0);
+        assert_eq!(error(&trailing), "1 trailing bytes");
+        let mut out_of_range = records.clone();
+        // Class name of the first record
+        let class_name = records.len() - 2 * 48 + 4;
+        out_of_range[class_name..class_name + 4].copy_from_slice(&99u32.to_le_bytes());
+        assert_eq!(error(&out_of_range), "string index 99 out of range");
+    }
+
+    #[test]
+    fn test_call_record_collector() {
+        let first = vec![call("root", "App.root", 0.0, 100.0, None)];
+        let second = vec![
+            call("child", "App.child", 0.0, 40.0, Some("root")),
+            call("leaf", "App.leaf", 0.0, 5.0, Some("child")),
+        ];
+
+        let mut collector = CallRecordCollector::default();
+        assert_eq!(collector.add_records(&encode_call_records(&first)), Ok(1));
+        assert_eq!(collector.add_records(&encode_call_records(&second)), Ok(2));
+        assert!(collector.add_records(b"XRCR").is_err());
+        assert_eq!(collector.len(), 3);
+
+        let all: Vec<_> = first.into_iter().chain(second).collect();
+        let config = FlameGraphConfig::default();
+        assert_eq!(
+            collector.build(&config).unwrap(),
+            build_flame_graph_data(serde_json::Value::from(all).to_string(), &config).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_tags() {
+        let input = serde_json::json!([
+            with(
+                call("root", "App.load", 0.0, 1.0, None),
+                "tags",
+                serde_json::json!({ "route": "/users", "zone": "angular" })
+            ),
+            with(
+                call("a", "App.load", 0.0, 1.0, Some("root")),
+                "tags",
+                serde_json::json!({ "httpUrl": "/api/users", "retry": 0 })
+            ),
+            with(
+                call("b", "App.load", 0.0, 1.0, Some("root")),
+                "tags",
+                serde_json::json!({ "httpUrl": "/api/users", "retry": 1 })
+            ),
+            call("c", "App.load", 0.0, 1.0, Some("a")),
+        ])
+        .to_string();
+        let nodes = |aggregate: bool| {
+            let config = FlameGraphConfig {
+                aggregate,
+                ..Default::default()
+            };
+            let result = build_flame_graph_data(input.clone(), &config).unwrap();
+            let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+            parsed["nodes"][0].clone()
+        };
+
+        let root = nodes(false);
+        assert_eq!(
+            root["tags"],
+            serde_json::json!({ "route": "/users", "zone": "angular" })
+        );
+        assert_eq!(root["children"][1]["tags"]["retry"], 1);
+        assert!(root["children"][0]["children"][0].get("tags").is_none());
+
+        // Merged calls keep the tags they agree on
+        let root = nodes(true);
+        assert_eq!(
+            root["children"][0]["tags"],
+            serde_json::json!({ "httpUrl": "/api/users" })
+        );
+    }
+
+    #[test]
+    fn test_categories() {
+        let category = |class_name: &str, file_path: Option<&str>| {
+            let input = call("call", &format!("{}.run", class_name), 0.0, 1.0, None);
+            Category::of(&serde_json::from_value(with(input, "filePath", file_path)).unwrap())
+        };
+
+        assert_eq!(
+            category("UserComponent", Some("src/app/user.ts")),
+            Category::App
+        );
+        assert_eq!(
+            category(
+                "Cdk",
+                Some("C:\\app\\node_modules\\@angular\\cdk\\a11y.mjs")
+            ),
+            Category::Angular
+        );
+        assert_eq!(
+            category("Map", Some("node_modules/rxjs/dist/operators.js")),
+            Category::Rxjs
+        );
+        assert_eq!(
+            category("Task", Some("node_modules/zone.js/fesm2015/zone.js")),
+            Category::Zone
+        );
+        assert_eq!(
+            category("Chart", Some("node_modules/chart.js/dist/chart.js")),
+            Category::Library
+        );
+        assert_eq!(category("NgZone", None), Category::Angular);
+        assert_eq!(category("ApplicationRef", None), Category::Angular);
+        assert_eq!(category("BehaviorSubject", None), Category::Rxjs);
+        assert_eq!(category("ZoneDelegate", None), Category::Zone);
+        assert_eq!(category("ZoneSelectorComponent", None), Category::App);
+        assert_eq!(category("NgxChartsWrapper", None), Category::App);
+
+        let input = r#"[{ "callId": "root", "className": "NgZone", "methodName": "run",
+            "duration": 1.0, "startTime": 0.0, "endTime": 1.0, "parentCallId": null }]"#;
+        let config = FlameGraphConfig {
+            virtual_root: true,
+            ..Default::default()
+        };
+        let result = build_flame_graph_data(input.to_string(), &config).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["nodes"][0]["category"], 5);
+        assert_eq!(parsed["nodes"][0]["children"][0]["category"], 1);
+    }
+
+    #[test]
+    fn test_weights() {
+        let input = serde_json::json!([
+            with(
+                call("root", "List.render", 0.0, 10.0, None),
+                "allocatedBytes",
+                4096.0
+            ),
+            with(
+                call("row_1", "List.renderRow", 0.0, 10.0, Some("root")),
+                "allocatedBytes",
+                1024.0
+            ),
+            with(
+                call("row_2", "List.renderRow", 0.0, 10.0, Some("root")),
+                "allocatedBytes",
+                2048.0
+            ),
+            with(
+                call("cell", "List.format", 0.0, 10.0, Some("row_2")),
+                "allocatedBytes",
+                512.0
+            ),
+        ])
+        .to_string();
+        let root = |weight: Weight, aggregate: bool| {
+            let config = FlameGraphConfig {
+                weight,
+                aggregate,
+                ..Default::default()
+            };
+            let result = build_flame_graph_data(input.clone(), &config).unwrap();
+            let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+            parsed["nodes"][0].clone()
+        };
+
+        let counts = root(Weight::Count, false);
+        assert_eq!(counts["value"], 4.0);
+        assert_eq!(counts["selfValue"], 1.0);
+        assert_eq!(counts["children"][1]["value"], 2.0);
+        assert_eq!(counts["children"][1]["percentage"], 50.0);
+        let merged = root(Weight::Count, true);
+        assert_eq!(merged["children"][0]["value"], 3.0);
+        assert_eq!(merged["children"][0]["selfValue"], 2.0);
+
+        let allocations = root(Weight::Allocation, false);
+        assert_eq!(allocations["value"], 4096.0);
+        assert_eq!(allocations["selfValue"], 1024.0);
+        assert_eq!(Weight::parse("count"), Some(Weight::Count));
+        assert_eq!(Weight::parse("memory"), None);
+    }
+
+    #[test]
+    fn test_child_gaps() {
+        let input = serde_json::json!([
+            call("load", "UserService.load", 0.0, 100.0, None),
+            call("prepare", "UserService.prepare", 5.0, 20.0, Some("load")),
+            call("overlap", "UserService.overlap", 15.0, 30.0, Some("load")),
+            call("parse", "UserService.parse", 80.0, 90.0, Some("load")),
+            call(
+                "validate",
+                "UserService.validate",
+                82.0,
+                84.0,
+                Some("parse")
+            ),
+            call("store", "UserService.store", 87.0, 89.0, Some("parse")),
+        ])
+        .to_string();
+
+        let result = build_flame_graph_data(input, &FlameGraphConfig::default()).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        // Only the time between children counts, not before the first or
+        // after the last
+        assert_eq!(parsed["nodes"][0]["gapTime"], 50.0);
+        assert_eq!(parsed["nodes"][0]["children"][2]["gapTime"], 3.0);
+        assert_eq!(parsed["nodes"][0]["children"][0]["gapTime"], 0.0);
+        assert_eq!(
+            parsed["largestGaps"],
+            serde_json::json!([
+                { "callId": "load", "name": "UserService.load",
+                  "start": 30.0, "end": 80.0, "duration": 50.0 },
+                { "callId": "parse", "name": "UserService.parse",
+                  "start": 84.0, "end": 87.0, "duration": 3.0 },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_merged_frame_stats() {
+        let mut calls = vec![serde_json::json!({
+            "callId": "root", "className": "App", "methodName": "render", "duration": 1000.0,
+            "startTime": 0.0, "endTime": 1000.0, "parentCallId": null
+        })];
+        for i in 0..20 {
+            let duration = if i == 7 { 500.0 } else { (i + 1) as f64 };
+            calls.push(serde_json::json!({
+                "callId": format!("call_{}", i), "className": "Row", "methodName": "render",
+                "duration": duration, "startTime": i as f64, "endTime": i as f64 + duration,
+                "parentCallId": "root"
+            }));
+        }
+        let input = serde_json::Value::from(calls).to_string();
+        let root = |aggregate: bool| {
+            let config = FlameGraphConfig {
+                aggregate,
+                ..Default::default()
+            };
+            let result = build_flame_graph_data(input.clone(), &config).unwrap();
+            let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+            parsed["nodes"][0].clone()
+        };
+
+        let merged = root(true);
+        assert_eq!(
+            merged["stats"],
+            serde_json::json!({
+                "min": 1000.0, "max": 1000.0, "avg": 1000.0, "p95": 1000.0
+            })
+        );
+        let rows = &merged["children"][0];
+        assert_eq!(rows["count"], 20);
+        assert_eq!(rows["stats"]["min"], 1.0);
+        assert_eq!(rows["stats"]["max"], 500.0);
+        assert_eq!(rows["stats"]["p95"], 20.0);
+        assert_eq!(rows["stats"]["avg"], rows["value"].as_f64().unwrap() / 20.0);
+
+        assert!(root(false).get("stats").is_none());
+        assert_eq!(DurationStats::new(&[]), None);
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let input = serde_json::json!([
+            { "callId": "1", "className": "AppComponent", "methodName": "ngOnInit",
+              "duration": 100.0, "startTime": 0.0, "endTime": 100.0, "parentCallId": null },
+            { "callId": "2", "className": "Store<T>", "methodName": "load",
+              "duration": 60.0, "startTime": 10.0, "endTime": 70.0, "parentCallId": "1" },
+            { "callId": "3", "className": "UserService", "methodName": "tiny",
+              "duration": 0.001, "startTime": 80.0, "endTime": 80.001, "parentCallId": "1" },
+        ])
+        .to_string();
+        let config = FlameGraphConfig {
+            search: Some("load".to_string()),
+            ..Default::default()
+        };
+        let svg = SvgConfig {
+            width: 1000,
+            frame_height: 20,
+            title: Some("Startup & load".to_string()),
+        };
+
+        let output = render_flame_graph_svg(input, &config, &svg).unwrap();
+        assert!(output
+            .starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"1000\" height=\"68\""));
+        assert!(output.contains(">Startup &amp; load</text>"));
+        assert!(output.contains(
+            "<title>AppComponent.ngOnInit (100 ms, 100.00%)</title><rect x=\"0.00\" y=\"48\" width=\"1000.00\""
+        ));
+        // Children are packed from the left of their parent; the matched one
+        // is highlighted and its name escaped
+        assert!(output.contains(
+            "<title>Store&lt;T&gt;.load (60 ms, 60.00%)</title><rect x=\"0.00\" y=\"28\" width=\"600.00\" height=\"19\" fill=\"rgb(230,0,230)\""
+        ));
+        // Frames under a tenth of a pixel are left out
+        assert!(!output.contains("tiny"));
+        assert!(output.ends_with("</svg>\n"));
+
+        let narrow = SvgConfig {
+            width: 40,
+            ..Default::default()
+        };
+        let input = serde_json::json!([
+            { "callId": "1", "className": "AppComponent", "methodName": "ngOnInit",
+              "duration": 100.0, "startTime": 0.0, "endTime": 100.0, "parentCallId": null },
+        ])
+        .to_string();
+        let output = render_flame_graph_svg(input, &FlameGraphConfig::default(), &narrow).unwrap();
+        assert!(output.contains(">Ap..</text>"));
+    }
+
+    #[test]
+    fn test_layout() {
+        let input = serde_json::json!([
+            { "callId": "1", "className": "App", "methodName": "a", "duration": 75.0,
+              "startTime": 0.0, "endTime": 75.0, "parentCallId": null },
+            { "callId": "2", "className": "App", "methodName": "b", "duration": 25.0,
+              "startTime": 80.0, "endTime": 105.0, "parentCallId": null },
+            { "callId": "3", "className": "App", "methodName": "c", "duration": 20.0,
+              "startTime": 5.0, "endTime": 25.0, "parentCallId": "1" },
+            { "callId": "4", "className": "App", "methodName": "d", "duration": 30.0,
+              "startTime": 40.0, "endTime": 70.0, "parentCallId": "1" },
+        ])
+        .to_string();
+        let config = FlameGraphConfig {
+            layout: true,
+            ..Default::default()
+        };
+
+        let result = build_flame_graph_data(input.clone(), &config).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let extent = |node: &serde_json::Value| {
+            (
+                node["layout"]["x0"].as_f64().unwrap(),
+                node["layout"]["x1"].as_f64().unwrap(),
+            )
+        };
+        assert_eq!(extent(&parsed["nodes"][0]), (0.0, 0.75));
+        assert_eq!(extent(&parsed["nodes"][1]), (0.75, 1.0));
+        // Children are packed from the start of their parent, gaps ignored
+        assert_eq!(extent(&parsed["nodes"][0]["children"][0]), (0.0, 0.2));
+        assert_eq!(extent(&parsed["nodes"][0]["children"][1]), (0.2, 0.5));
+        assert_eq!(
+            parsed["nodes"][1]["layout"]["startAngle"],
+            0.75 * std::f64::consts::TAU
+        );
+        assert_eq!(
+            parsed["nodes"][1]["layout"]["endAngle"],
+            std::f64::consts::TAU
+        );
+
+        let result = build_flame_graph_data(input, &FlameGraphConfig::default()).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(parsed["nodes"][0].get("layout").is_none());
+    }
+
+    #[test]
+    fn test_summary() {
+        let input = serde_json::json!([
+            { "callId": "1", "className": "App", "methodName": "bootstrap", "duration": 100.0,
+              "startTime": 0.0, "endTime": 100.0, "parentCallId": null },
+            { "callId": "2", "className": "App", "methodName": "render", "duration": 100.0,
+              "startTime": 0.0, "endTime": 100.0, "parentCallId": "1" },
+            { "callId": "3", "className": "Row", "methodName": "render", "duration": 30.0,
+              "startTime": 0.0, "endTime": 30.0, "parentCallId": "2" },
+            { "callId": "4", "className": "Row", "methodName": "render", "duration": 50.0,
+              "startTime": 40.0, "endTime": 90.0, "parentCallId": "2" },
+            { "callId": "5", "className": "App", "methodName": "idle", "duration": 10.0,
+              "startTime": 100.0, "endTime": 110.0, "parentCallId": null },
+        ])
+        .to_string();
+        let config = FlameGraphConfig {
+            virtual_root: true,
+            ..Default::default()
+        };
+
+        let result = build_flame_graph_data(input, &config).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        // The session root is not a call; the equally wide render is deeper
+        // than bootstrap
+        assert_eq!(
+            parsed["summary"],
+            serde_json::json!({
+                "maxDepth": 3,
+                "nodeCount": 6,
+                "selfTimeByDepth": [0.0, 10.0, 20.0, 80.0],
+                "widestFrame": { "id": "2", "name": "App.render", "value": 100.0, "depth": 2 },
+            })
+        );
+    }
+
+    #[test]
+    fn test_max_children() {
+        let mut calls = vec![serde_json::json!({
+            "callId": "root", "className": "App", "methodName": "render", "duration": 100.0,
+            "startTime": 0.0, "endTime": 100.0, "parentCallId": null
+        })];
+        for (i, duration) in [5.0, 30.0, 10.0, 40.0, 15.0].into_iter().enumerate() {
+            calls.push(serde_json::json!({
+                "callId": format!("row_{}", i), "className": "Row", "methodName": format!("r{}", i),
+                "duration": duration, "startTime": i as f64 * 20.0,
+                "endTime": i as f64 * 20.0 + duration, "parentCallId": "root"
+            }));
+        }
+        calls.push(serde_json::json!({
+            "callId": "cell", "className": "Cell", "methodName": "render", "duration": 3.0,
+            "startTime": 81.0, "endTime": 84.0, "parentCallId": "row_4"
+        }));
+        let config = FlameGraphConfig {
+            max_children: Some(2),
+            ..Default::default()
+        };
+
+        let result =
+            build_flame_graph_data(serde_json::Value::from(calls).to_string(), &config).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let children = parsed["nodes"][0]["children"].as_array().unwrap();
+        let names: Vec<&str> = children
+            .iter()
+            .map(|child| child["name"].as_str().unwrap())
+            .collect();
+        // The two largest stay in chronological order, the rest and their
+        // descendants merge into one node
+        assert_eq!(names, ["(other: 3 calls)", "Row.r1", "Row.r3"]);
+        assert_eq!(children[0]["value"], 30.0);
+        assert!(children[0].get("children").is_none());
+        assert_eq!(parsed["nodes"][0]["value"], 100.0);
+    }
+
+    #[test]
+    fn test_async_continuations() {
+        let input = serde_json::json!([
+            { "callId": "1", "className": "UserComponent", "methodName": "ngOnInit",
+              "duration": 10.0, "startTime": 0.0, "endTime": 10.0, "parentCallId": null,
+              "asyncId": 1 },
+            { "callId": "2", "className": "UserService", "methodName": "load",
+              "duration": 5.0, "startTime": 2.0, "endTime": 7.0, "parentCallId": "1",
+              "asyncId": 1 },
+            { "callId": "3", "className": "UserService", "methodName": "onLoaded",
+              "duration": 4.0, "startTime": 50.0, "endTime": 54.0, "parentCallId": null,
+              "asyncId": 7, "triggerAsyncId": 1 },
+            { "callId": "4", "className": "UserComponent", "methodName": "render",
+              "duration": 2.0, "startTime": 60.0, "endTime": 62.0, "parentCallId": null,
+              "asyncId": 9, "triggerAsyncId": 7 },
+            { "callId": "5", "className": "Timer", "methodName": "tick",
+              "duration": 1.0, "startTime": 70.0, "endTime": 71.0, "parentCallId": null,
+              "asyncId": 11, "triggerAsyncId": 99 },
+        ])
+        .to_string();
+
+        let result = build_flame_graph_data(input, &FlameGraphConfig::default()).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let nodes = parsed["nodes"].as_array().unwrap();
+        // The tick was scheduled by an unknown context and stays a root
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[1]["id"], "5");
+
+        let children = nodes[0]["children"].as_array().unwrap();
+        assert_eq!(children[0]["id"], "2");
+        assert!(children[0].get("asyncContinuation").is_none());
+        assert_eq!(children[1]["id"], "3");
+        assert_eq!(children[1]["asyncContinuation"], true);
+        assert_eq!(children[1]["children"][0]["id"], "4");
+        assert_eq!(children[1]["children"][0]["asyncContinuation"], true);
+    }
+
+    #[test]
+    fn test_elided_calls() {
+        let input = serde_json::json!([
+            with(
+                call("1", "AppComponent.run", 0.0, 100.0, None),
+                "filePath",
+                "src/app/app.ts"
+            ),
+            with(
+                call("2", "ZoneDelegate.run", 0.0, 80.0, Some("1")),
+                "filePath",
+                "node_modules\\zone.js\\zone.js"
+            ),
+            with(
+                call("3", "UserService.run", 0.0, 50.0, Some("2")),
+                "filePath",
+                "src/app/user.ts"
+            ),
+            with(
+                call("4", "UserService.run", 0.0, 20.0, Some("1")),
+                "filePath",
+                "src/app/user.ts"
+            ),
+        ])
+        .to_string();
+        let build = |config: FlameGraphConfig| {
+            let result = build_flame_graph_data(input.clone(), &config).unwrap();
+            let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+            parsed["nodes"][0].clone()
+        };
+
+        // The zone frame is elided by path, its child merging with the direct
+        // call of the same method; its time stays in the root
+        let folded = build(FlameGraphConfig {
+            aggregate: true,
+            exclude_patterns: vec!["node_modules/zone.js/".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(folded["value"], 100.0);
+        assert_eq!(folded["selfValue"], 30.0);
+        assert_eq!(folded["children"].as_array().unwrap().len(), 1);
+        assert_eq!(folded["children"][0]["name"], "UserService.run");
+        assert_eq!(folded["children"][0]["value"], 70.0);
+
+        let dropped = build(FlameGraphConfig {
+            exclude_patterns: vec!["Zone".to_string()],
+            drop_elided_time: true,
+            ..Default::default()
+        });
+        assert_eq!(dropped["value"], 70.0);
+        assert_eq!(dropped["selfValue"], 0.0);
+
+        // Only application code, the root being elided too
+        let included = build(FlameGraphConfig {
+            include_patterns: vec!["UserService.".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(included["id"], "3");
+    }
+
+    #[test]
+    fn test_percent_of_parent() {
+        let input = serde_json::json!([
+            { "callId": "1", "className": "App", "methodName": "a", "duration": 50.0,
+              "startTime": 0.0, "endTime": 50.0, "parentCallId": null },
+            { "callId": "2", "className": "App", "methodName": "b", "duration": 150.0,
+              "startTime": 50.0, "endTime": 200.0, "parentCallId": null },
+            { "callId": "3", "className": "App", "methodName": "c", "duration": 10.0,
+              "startTime": 0.0, "endTime": 10.0, "parentCallId": "1" },
+        ])
+        .to_string();
+        let percentages = |percent_of_parent: bool| {
+            let config = FlameGraphConfig {
+                percent_of_parent,
+                ..Default::default()
+            };
+            let result = build_flame_graph_data(input.clone(), &config).unwrap();
+            let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+            (
+                parsed["nodes"][0]["percentage"].clone(),
+                parsed["nodes"][0]["children"][0]["percentage"].clone(),
+            )
+        };
+
+        assert_eq!(percentages(false), (25.0.into(), 5.0.into()));
+        assert_eq!(percentages(true), (25.0.into(), 20.0.into()));
+    }
+
+    #[test]
+    fn test_flame_chart() {
+        let input = serde_json::json!([
+            call("fetch", "App.fetch", 10.0, 60.0, None),
+            call("click", "App.click", 0.0, 40.0, None),
+            call("render", "App.render", 5.0, 20.0, Some("click")),
+            call("detect", "App.detect", 6.0, 8.0, Some("render")),
+            call("timer", "App.timer", 45.0, 50.0, None),
+            call("late", "App.late", 70.0, 80.0, Some("missing")),
+        ])
+        .to_string();
+
+        let result = build_flame_chart_data(input.clone(), None, None).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let events: Vec<(&str, u64, u64)> = parsed["events"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| {
+                (
+                    event["id"].as_str().unwrap(),
+                    event["lane"].as_u64().unwrap(),
+                    event["depth"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        // The fetch overlapping the click gets its own lane; the timer and
+        // the orphaned call fit back in the first one
+        assert_eq!(
+            events,
+            [
+                ("click", 0, 0),
+                ("render", 0, 1),
+                ("detect", 0, 2),
+                ("timer", 0, 0),
+                ("late", 0, 0),
+                ("fetch", 1, 0),
+            ]
+        );
+        assert_eq!(parsed["events"][1]["parentId"], "click");
+        assert_eq!(parsed["events"][1]["start"], 5.0);
+        assert!(parsed["events"][4].get("parentId").is_none());
+        assert_eq!(parsed["laneDepths"], serde_json::json!([3, 1]));
+        assert_eq!(parsed["startTime"], 0.0);
+        assert_eq!(parsed["endTime"], 80.0);
+        assert_eq!(parsed["orphanedCalls"], 1);
+
+        let result = build_flame_chart_data(input, Some(30.0), Some(55.0)).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        // Both clipped roots start at 30, the longer first
+        assert_eq!(parsed["events"][0]["id"], "fetch");
+        assert_eq!(parsed["events"][0]["start"], 30.0);
+        assert_eq!(parsed["events"][1]["id"], "click");
+        assert_eq!(parsed["events"][1]["lane"], 1);
+        assert_eq!(parsed["laneDepths"], serde_json::json!([1, 1]));
+    }
+
+    #[test]
+    fn test_merge_call_stacks() {
+        let input = serde_json::json!([
+            [
+                call("1", "App.click", 0.0, 10.0, None),
+                call("2", "App.render", 0.0, 4.0, Some("1"))
+            ],
+            [
+                call("1", "App.click", 0.0, 20.0, None),
+                call("2", "App.render", 0.0, 6.0, Some("1")),
+                call("3", "App.render", 0.0, 2.0, Some("1"))
+            ],
+            [
+                call("1", "App.click", 0.0, 30.0, None),
+                call("2", "App.retry", 0.0, 5.0, Some("1"))
+            ],
+        ])
+        .to_string();
+
+        let result = merge_call_stacks(input, &FlameGraphConfig::default()).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let nodes = parsed["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0]["value"], 60.0);
+        assert_eq!(nodes[0]["count"], 3);
+        assert_eq!(nodes[0]["runCount"], 3);
+        let render = &nodes[0]["children"][0];
+        assert_eq!(render["name"], "App.render");
+        assert_eq!(render["count"], 3);
+        assert_eq!(render["runCount"], 2);
+        assert_eq!(nodes[0]["children"][1]["runCount"], 1);
+
+        // Single recordings have no run counts
+        let single = serde_json::json!([call("1", "App.click", 0.0, 1.0, None)]).to_string();
+        let result = build_flame_graph_data(single, &FlameGraphConfig::default()).unwrap();
+        assert!(!result.contains("runCount"));
+    }
+
+    #[test]
+    fn test_stable_ids() {
+        let recording = |prefix: &str| {
+            let id = |n: u32| format!("{}{}", prefix, n);
+            serde_json::json!([
+                call(&id(1), "App.click", 0.0, 10.0, None),
+                call(&id(2), "App.render", 1.0, 11.0, Some(&id(1))),
+                call(&id(3), "App.render", 2.0, 12.0, Some(&id(1))),
+                call(&id(4), "App.detect", 3.0, 13.0, Some(&id(3))),
+            ])
+            .to_string()
+        };
+        let config = FlameGraphConfig {
+            stable_ids: true,
+            ..Default::default()
+        };
+        let parse = |input: String, config: &FlameGraphConfig| -> serde_json::Value {
+            serde_json::from_str(&build_flame_graph_data(input, config).unwrap()).unwrap()
+        };
+
+        let first = parse(recording("a"), &config);
+        let second = parse(recording("b"), &config);
+        let ids = |parsed: &serde_json::Value| {
+            let root = &parsed["nodes"][0];
+            [
+                root["id"].clone(),
+                root["children"][0]["id"].clone(),
+                root["children"][1]["id"].clone(),
+                root["children"][1]["children"][0]["id"].clone(),
+            ]
+        };
+        // Same paths, other call ids
+        assert_eq!(ids(&first), ids(&second));
+        assert_eq!(first["nodes"][0]["callId"], "a1");
+        assert_eq!(second["nodes"][0]["callId"], "b1");
+        // Siblings of the same name differ by occurrence
+        assert_ne!(ids(&first)[1], ids(&first)[2]);
+        assert_eq!(ids(&first)[0].as_str().unwrap().len(), 16);
+
+        // The child order does not change the ids
+        let left_heavy = parse(
+            recording("a"),
+            &FlameGraphConfig {
+                child_order: ChildOrder::LeftHeavy,
+                ..config.clone()
+            },
+        );
+        let mut ids: Vec<serde_json::Value> = left_heavy["nodes"][0]["children"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|child| child["id"].clone())
+            .collect();
+        ids.sort_by_key(|id| id.to_string());
+        let mut expected = vec![
+            first["nodes"][0]["children"][0]["id"].clone(),
+            first["nodes"][0]["children"][1]["id"].clone(),
+        ];
+        expected.sort_by_key(|id| id.to_string());
+        assert_eq!(ids, expected);
+
+        let passthrough = parse(recording("a"), &FlameGraphConfig::default());
+        assert_eq!(passthrough["nodes"][0]["id"], "a1");
+        assert!(passthrough["nodes"][0].get("callId").is_none());
+    }
 }
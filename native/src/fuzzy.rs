@@ -0,0 +1,174 @@
+/// Prefixes the Angular compiler and bundlers put in front of class names
+const MANGLED_PREFIXES: &[&str] = &["ɵ", "_", "$"];
+
+/// Similarity of an indexed class name to a (possibly mangled) query,
+/// from `0.0` to `1.0`
+///
+/// Takes the best of three measures: normalized edit distance, camel-hump
+/// abbreviation (`AppCmp` → `AppComponent`) and containment, which recovers
+/// names with generated prefixes or suffixes (`_AppComponent_1`).
+pub fn similarity(query: &str, candidate: &str) -> f64 {
+    if query == candidate {
+        return 1.0;
+    }
+
+    let query = normalize(query);
+    let candidate = normalize(candidate);
+    if query.is_empty() || candidate.is_empty() {
+        return 0.0;
+    }
+    if query == candidate {
+        // Only generated decoration differed
+        return 0.95;
+    }
+
+    edit_similarity(&query, &candidate)
+        .max(hump_similarity(&query, &candidate))
+        .max(containment_similarity(&query, &candidate))
+}
+
+/// Strip generated prefixes and numeric suffixes (`_1`, `$2`)
+///
+/// Digits without a separator are part of the name (`Sha256`, `Vector2`).
+fn normalize(name: &str) -> String {
+    let mut name = name;
+    while let Some(rest) = MANGLED_PREFIXES
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+    {
+        name = rest;
+    }
+
+    let unnumbered = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    if unnumbered.len() < name.len() && unnumbered.ends_with(['_', '$']) {
+        name = unnumbered;
+    }
+    name.trim_end_matches(['_', '$']).to_string()
+}
+
+/// `1 - levenshtein / longest length`, case-insensitively
+fn edit_similarity(query: &str, candidate: &str) -> f64 {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let longest = query.len().max(candidate.len());
+
+    1.0 - levenshtein(&query, &candidate) as f64 / longest as f64
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// Split a PascalCase / camelCase name into its humps
+fn humps(name: &str) -> Vec<String> {
+    let mut humps: Vec<String> = Vec::new();
+    let mut boundary = true;
+    for c in name.chars() {
+        if c == '_' {
+            boundary = true;
+            continue;
+        }
+        match humps.last_mut() {
+            Some(hump) if !boundary && !c.is_uppercase() => hump.push(c),
+            _ => humps.push(c.to_string()),
+        }
+        boundary = false;
+    }
+    humps
+}
+
+/// Score the query as an abbreviation of the candidate's humps
+///
+/// Every query hump must start like the matching candidate hump and spell
+/// a subsequence of it (`Cmp` → `Component`); the score grows with how much
+/// of the candidate the query covers.
+fn hump_similarity(query: &str, candidate: &str) -> f64 {
+    let query_humps = humps(query);
+    let candidate_humps = humps(candidate);
+    if query_humps.len() != candidate_humps.len() || query_humps.len() < 2 {
+        return 0.0;
+    }
+
+    let abbreviates = query_humps
+        .iter()
+        .zip(&candidate_humps)
+        .all(|(q, c)| abbreviates_hump(q, c));
+    if !abbreviates {
+        return 0.0;
+    }
+
+    let coverage = query.chars().count() as f64 / candidate.chars().count() as f64;
+    0.6 + 0.3 * coverage
+}
+
+fn abbreviates_hump(query: &str, candidate: &str) -> bool {
+    let query = query.to_lowercase();
+    let candidate = candidate.to_lowercase();
+    if query.chars().next() != candidate.chars().next() {
+        return false;
+    }
+
+    let mut remaining = candidate.chars();
+    query.chars().all(|q| remaining.any(|c| c == q))
+}
+
+/// Score a name that contains the other one, e.g. a suffixed query
+fn containment_similarity(query: &str, candidate: &str) -> f64 {
+    let (shorter, longer) = if query.len() <= candidate.len() {
+        (query, candidate)
+    } else {
+        (candidate, query)
+    };
+    // Short names are contained in too many unrelated ones
+    if shorter.chars().count() < 4 || !longer.contains(shorter) {
+        return 0.0;
+    }
+
+    let coverage = shorter.chars().count() as f64 / longer.chars().count() as f64;
+    0.5 + 0.4 * coverage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("AppComponent", "AppComponent"), 1.0);
+        assert_eq!(similarity("_AppComponent_1", "AppComponent"), 0.95);
+        assert!(similarity("AppComponnet", "AppComponent") > 0.8);
+        assert!(similarity("AppCmp", "AppComponent") > 0.6);
+        assert!(similarity("AppComponentFactory", "AppComponent") > 0.7);
+        assert!(similarity("UserService", "AppComponent") < 0.5);
+
+        // Digits that belong to the name are not generated suffixes
+        assert!(similarity("Sha256", "Sha1") < 0.95);
+        assert!(similarity("Vector2", "Vector3") < 0.95);
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("_AppComponent_1"), "AppComponent");
+        assert_eq!(normalize("ɵUserService$12"), "UserService");
+        assert_eq!(normalize("Sha256"), "Sha256");
+        assert_eq!(normalize("Vector2"), "Vector2");
+    }
+
+    #[test]
+    fn test_humps() {
+        assert_eq!(humps("HttpClientModule"), vec!["Http", "Client", "Module"]);
+        assert_eq!(humps("my_service"), vec!["my", "service"]);
+    }
+}
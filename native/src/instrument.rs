@@ -0,0 +1,384 @@
+use crate::parser::{MemberKind, MethodBodySpan, TypeScriptParser};
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// Comment tagging every piece of code inserted by [`instrument_methods`]
+pub const MARKER: &str = "/* @xray */";
+
+/// How selected methods are instrumented
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instrumentation {
+    /// Wrap the body between `performance.mark` and `performance.measure`
+    /// calls named `xray:<Class>.<method>`
+    PerformanceMark,
+    /// Apply a profiling decorator, e.g. `@Profile("Cart.total")`
+    Decorator {
+        name: String,
+        /// Module to import the decorator from, if it is not imported yet
+        import_from: Option<String>,
+    },
+}
+
+/// Replacement of the source between two byte offsets
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+/// Source rewritten by [`instrument_methods`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstrumentedSource {
+    pub source: String,
+    /// Edits of `source` that restore the original, last edit first, so
+    /// they can be applied in order
+    pub reverse_patch: Vec<TextEdit>,
+    /// Methods that were instrumented, in the order requested
+    pub instrumented: Vec<String>,
+    /// Methods left alone: not declared, without a body, constructors
+    /// (a `super()` call must stay first), marked `// @xray:ignore` or
+    /// already instrumented
+    pub skipped: Vec<String>,
+}
+
+/// Rewrite selected methods of a class to record their execution time
+///
+/// Only text is inserted, so the rest of the file keeps its formatting and
+/// every insertion is tagged with [`MARKER`] for later removal. Concise
+/// arrow properties (`load = () => this.http.get(url)`) get a block body.
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `class_name` - The name of the class declaring the methods
+/// * `method_names` - The methods to instrument; repeated names are
+///   instrumented once
+/// * `instrumentation` - What to insert
+///
+/// # Returns
+/// The rewritten source and the edits that undo the rewrite
+pub fn instrument_methods(
+    file_content: &str,
+    class_name: &str,
+    method_names: &[String],
+    instrumentation: &Instrumentation,
+) -> Result<InstrumentedSource, String> {
+    let parser = TypeScriptParser::new();
+    let bodies = parser.find_method_bodies(file_content, class_name, method_names)?;
+    let ignored = parser.find_ignored_methods(file_content, Some(class_name))?;
+
+    let mut insertions: Vec<(usize, String)> = Vec::new();
+    let mut instrumented = Vec::new();
+    let mut skipped = Vec::new();
+    let mut seen = HashSet::new();
+    for (method_name, body) in method_names.iter().zip(bodies) {
+        if !seen.insert(method_name) {
+            continue;
+        }
+        let is_ignored = ignored
+            .iter()
+            .any(|method| method.method_name == *method_name);
+        let Some(body) = body.filter(|body| {
+            body.kind != MemberKind::Constructor
+                && !is_ignored
+                && !is_instrumented(file_content, body)
+        }) else {
+            skipped.push(method_name.clone());
+            continue;
+        };
+
+        let label = format!("{}.{}", class_name, method_name);
+        match instrumentation {
+            Instrumentation::PerformanceMark => insertions.extend(mark_insertions(&body, &label)),
+            Instrumentation::Decorator { name, .. } => {
+                let start = body.member_start as usize;
+                let line_start = file_content[..start].rfind('\n').map_or(0, |i| i + 1);
+                let indent = &file_content[line_start..start];
+                let indent = if indent.trim().is_empty() { indent } else { "" };
+                let text = format!("@{}({}) {}\n{}", name, quote(&label), MARKER, indent);
+                insertions.push((start, text));
+            }
+        }
+        instrumented.push(method_name.clone());
+    }
+
+    if let Instrumentation::Decorator {
+        name,
+        import_from: Some(module),
+    } = instrumentation
+    {
+        if !instrumented.is_empty() {
+            let text = format!("import {{ {} }} from {}; {}\n", name, quote(module), MARKER);
+            insertions.push((0, text));
+        }
+    }
+
+    // Stable: insertions at the same offset keep the order they were made in
+    insertions.sort_by_key(|(offset, _)| *offset);
+
+    let mut source = String::with_capacity(file_content.len());
+    let mut reverse_patch = Vec::with_capacity(insertions.len());
+    let mut copied = 0;
+    for (offset, text) in insertions {
+        source.push_str(&file_content[copied..offset]);
+        copied = offset;
+        reverse_patch.push(TextEdit {
+            start: source.len(),
+            end: source.len() + text.len(),
+            text: String::new(),
+        });
+        source.push_str(&text);
+    }
+    source.push_str(&file_content[copied..]);
+    reverse_patch.reverse();
+
+    Ok(InstrumentedSource {
+        source,
+        reverse_patch,
+        instrumented,
+        skipped,
+    })
+}
+
+/// Text inserted around a body to time it
+fn mark_insertions(body: &MethodBodySpan, label: &str) -> [(usize, String); 2] {
+    let start_mark = quote(&format!("xray:{}:start", label));
+    let measure = quote(&format!("xray:{}", label));
+    let begin = format!("{} performance.mark({}); try {{", MARKER, start_mark);
+    let end = format!(
+        "}} finally {{ performance.measure({}, {}); }} {}",
+        measure, start_mark, MARKER
+    );
+
+    let (body_start, body_end) = (body.body_start as usize, body.body_end as usize);
+    if body.expression_body {
+        [
+            // Marked again inside, to tell the braces added here apart from
+            // a block body that starts with `return`
+            (body_start, format!("{{ {} return {} ", begin, MARKER)),
+            (body_end, format!("; {} {} }}", MARKER, end)),
+        ]
+    } else {
+        [
+            (body_start + 1, format!(" {}", begin)),
+            (body_end - 1, format!("{} ", end)),
+        ]
+    }
+}
+
+/// Remove the instrumentation inserted by [`instrument_methods`]
+///
+/// Works on the text alone, so it also cleans files that no longer parse,
+/// and tolerates whitespace changes made by a formatter since.
+///
+/// # Arguments
+/// * `file_content` - The content of the instrumented file
+///
+/// # Returns
+/// The source without any X-Ray wrapper, decorator or import
+pub fn strip_instrumentation(file_content: &str) -> String {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        let string = r#"(?:"(?:[^"\\]|\\.)*"|'(?:[^'\\]|\\.)*')"#;
+        let marker = regex::escape(MARKER);
+        let mark = format!(
+            r"{m}\s*performance\.mark\({s}\);\s*try\s*\{{",
+            m = marker,
+            s = string
+        );
+        let measure = format!(
+            r"\}}\s*finally\s*\{{\s*performance\.measure\({s},\s*{s}\);\s*\}}\s*{m}",
+            m = marker,
+            s = string
+        );
+        [
+            // Import of the decorator, with its line
+            format!(
+                r"(?m)^[ \t]*import\s*\{{[^}}]*\}}\s*from\s*{}\s*;?\s*{}[ \t]*\r?\n?",
+                string, marker
+            ),
+            // Decorator, with the line break and indent before the member
+            format!(r"@[\w$.]+\({}\)\s*{}[ \t]*(?:\r?\n[ \t]*)?", string, marker),
+            // Block wrapped around a concise arrow body
+            format!(r"\{{\s*{}\s*return\s*{}\s*", mark, marker),
+            format!(r"\s*;\s*{}\s*{}\s*\}}", marker, measure),
+            // Wrapper inside a block body
+            format!(r"[ \t]?{}", mark),
+            format!(r"{}[ \t]?", measure),
+        ]
+        .iter()
+        .map(|pattern| Regex::new(pattern).expect("valid instrumentation pattern"))
+        .collect()
+    });
+
+    patterns
+        .iter()
+        .fold(file_content.to_string(), |source, pattern| {
+            pattern.replace_all(&source, "").into_owned()
+        })
+}
+
+/// Check whether a member already carries X-Ray instrumentation
+fn is_instrumented(file_content: &str, body: &MethodBodySpan) -> bool {
+    file_content
+        .get(body.member_start as usize..body.body_end as usize)
+        .is_some_and(|member| member.contains(MARKER))
+}
+
+/// A double-quoted string literal
+fn quote(text: &str) -> String {
+    serde_json::to_string(text).unwrap_or_else(|_| format!("\"{}\"", text))
+}
+
+/// 1-based line and column of a byte offset
+pub fn line_column(source: &str, offset: usize) -> (u32, u32) {
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line as u32, column as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CART: &str = r#"export class Cart {
+    total() {
+        return this.items.length;
+    }
+
+    load = () => this.http.get(url);
+
+    @Input() clear() {}
+
+    constructor() {}
+}
+"#;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn revert(result: &InstrumentedSource) -> String {
+        let mut source = result.source.clone();
+        for edit in &result.reverse_patch {
+            source.replace_range(edit.start..edit.end, &edit.text);
+        }
+        source
+    }
+
+    #[test]
+    fn test_instrument_performance_mark() {
+        let methods = names(&["total", "load", "constructor", "missing", "poll"]);
+        let source = CART.replace(
+            "    constructor() {}",
+            "    constructor() {}\n\n    // @xray:ignore\n    poll() {}",
+        );
+        let result =
+            instrument_methods(&source, "Cart", &methods, &Instrumentation::PerformanceMark)
+                .unwrap();
+
+        assert_eq!(result.instrumented, names(&["total", "load"]));
+        assert_eq!(result.skipped, names(&["constructor", "missing", "poll"]));
+        assert!(result
+            .source
+            .contains(r#"total() { /* @xray */ performance.mark("xray:Cart.total:start"); try {"#));
+        assert!(result.source.contains(
+            r#"} finally { performance.measure("xray:Cart.total", "xray:Cart.total:start"); } /* @xray */ }"#
+        ));
+        assert!(result.source.contains(
+            r#"load = () => { /* @xray */ performance.mark("xray:Cart.load:start"); try { return /* @xray */ this.http.get(url); /* @xray */ }"#
+        ));
+        TypeScriptParser::new()
+            .find_member(&result.source, "total", None, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(revert(&result), source);
+
+        // Instrumenting twice leaves the methods alone
+        let again = instrument_methods(
+            &result.source,
+            "Cart",
+            &methods,
+            &Instrumentation::PerformanceMark,
+        )
+        .unwrap();
+        assert!(again.instrumented.is_empty());
+        assert_eq!(again.source, result.source);
+    }
+
+    #[test]
+    fn test_instrument_decorator() {
+        let instrumentation = Instrumentation::Decorator {
+            name: "Profile".to_string(),
+            import_from: Some("@xray/runtime".to_string()),
+        };
+        let result =
+            instrument_methods(CART, "Cart", &names(&["total", "clear"]), &instrumentation)
+                .unwrap();
+
+        assert!(result
+            .source
+            .starts_with("import { Profile } from \"@xray/runtime\"; /* @xray */\n"));
+        assert!(result
+            .source
+            .contains("    @Profile(\"Cart.total\") /* @xray */\n    total() {"));
+        assert!(result
+            .source
+            .contains("    @Profile(\"Cart.clear\") /* @xray */\n    @Input() clear() {}"));
+        assert_eq!(revert(&result), CART);
+    }
+
+    #[test]
+    fn test_instrument_repeated_name() {
+        let result = instrument_methods(
+            CART,
+            "Cart",
+            &names(&["total", "total"]),
+            &Instrumentation::PerformanceMark,
+        )
+        .unwrap();
+
+        assert_eq!(result.instrumented, names(&["total"]));
+        assert!(result.skipped.is_empty());
+        assert_eq!(result.source.matches("xray:Cart.total:start").count(), 2);
+        assert_eq!(revert(&result), CART);
+    }
+
+    #[test]
+    fn test_strip_instrumentation() {
+        // A block body starting with `return` must keep its braces
+        let source = CART.replace("    constructor() {}", "    save() { return 1; }");
+        let decorator = Instrumentation::Decorator {
+            name: "Profile".to_string(),
+            import_from: Some("@xray/runtime".to_string()),
+        };
+        let decorated =
+            instrument_methods(&source, "Cart", &names(&["clear"]), &decorator).unwrap();
+        let instrumented = instrument_methods(
+            &decorated.source,
+            "Cart",
+            &names(&["total", "load", "save"]),
+            &Instrumentation::PerformanceMark,
+        )
+        .unwrap();
+        assert_eq!(instrumented.instrumented.len(), 3);
+
+        assert_eq!(strip_instrumentation(&instrumented.source), source);
+        assert_eq!(strip_instrumentation(CART), CART);
+
+        // Formatters may split the wrapper over several lines
+        let formatted = "run() {\n    /* @xray */ performance.mark('xray:A.run:start');\n    try {\n      go();\n    } finally {\n      performance.measure('xray:A.run', 'xray:A.run:start');\n    } /* @xray */\n}";
+        let stripped = strip_instrumentation(formatted);
+        assert!(!stripped.contains("xray") && !stripped.contains("try"));
+        assert!(stripped.starts_with("run() {") && stripped.contains("go();"));
+    }
+
+    #[test]
+    fn test_line_column() {
+        assert_eq!(line_column("ab\ncé d", 7), (2, 4));
+        assert_eq!(line_column("ab", 0), (1, 1));
+    }
+}
@@ -3,25 +3,663 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
+mod change_detection;
+mod class_index;
 pub mod comparison;
 mod file_locator;
 pub mod flame_graph;
+mod fuzzy;
+mod instrument;
+mod match_cache;
+mod matcher;
+mod metadata;
+mod node_modules;
 mod parser;
+mod projects;
+mod sourcemap;
 pub mod storage;
+mod template;
+#[cfg(test)]
+mod test_support;
+mod tsconfig;
+mod walk;
+mod warm;
+mod watcher;
 
-use file_locator::FileLocator;
-use parser::TypeScriptParser;
+use file_locator::{
+    FileLocator, LocatorConfig, ProgressReporter, ScanStats, SymbolKind, DEFAULT_FUZZY_THRESHOLD,
+};
+use flame_graph::{CallRecordCollector, ChildOrder, FlameGraphConfig, SvgConfig, Weight};
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use parser::{MemberKind, MemberLocation, TypeScriptParser};
+use projects::ProjectScope;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tsconfig::TsConfig;
+use warm::{WarmLocator, DEFAULT_RECENT_CAPACITY};
+use watcher::ClassWatcher;
 
 #[napi(object)]
 pub struct FileLocation {
     pub file_path: String,
     pub found: bool,
+    /// Declaration line (1-based), when requested via `includeDeclaration`
+    pub line: Option<u32>,
+    /// Declaration column (1-based), when requested via `includeDeclaration`
+    pub column: Option<u32>,
+    /// Byte offset where the declaration starts, when requested
+    pub span_start: Option<u32>,
+    /// Byte offset just past the end of the declaration, when requested
+    pub span_end: Option<u32>,
+    /// The search hit `maxResults` or `timeoutMs` before finishing
+    pub truncated: bool,
+    /// Scan statistics of the lookup that produced this result
+    pub stats: Option<SearchStats>,
+    /// Similar class names, when `fuzzy` was requested and the exact name
+    /// was not found; the best one is reported as `filePath`
+    pub fuzzy_candidates: Option<Vec<FuzzyMatch>>,
+    /// The `extends` clause of the class, when requested via
+    /// `includeDeclaration`
+    pub super_class: Option<String>,
+    /// The `implements` clauses of the class, when requested via
+    /// `includeDeclaration`
+    pub interfaces: Option<Vec<String>>,
+}
+
+impl FileLocation {
+    fn found(file_path: String) -> Self {
+        Self {
+            file_path,
+            found: true,
+            line: None,
+            column: None,
+            span_start: None,
+            span_end: None,
+            truncated: false,
+            stats: None,
+            fuzzy_candidates: None,
+            super_class: None,
+            interfaces: None,
+        }
+    }
+
+    fn not_found() -> Self {
+        Self {
+            file_path: String::new(),
+            found: false,
+            line: None,
+            column: None,
+            span_start: None,
+            span_end: None,
+            truncated: false,
+            stats: None,
+            fuzzy_candidates: None,
+            super_class: None,
+            interfaces: None,
+        }
+    }
+
+    /// Mark the result as partial when a search limit was hit
+    fn with_truncated(mut self, truncated: bool) -> Self {
+        self.truncated = truncated;
+        self
+    }
+
+    /// Attach the statistics of the lookup that produced the result
+    fn with_stats(mut self, locator: &FileLocator) -> Self {
+        self.stats = Some(locator.stats().into());
+        self
+    }
+
+    /// Fill in the position and heritage of the class declaration inside the
+    /// found file
+    ///
+    /// Leaves them empty if the file cannot be read or parsed.
+    fn with_declaration(mut self, class_name: &str) -> Self {
+        if !self.found {
+            return self;
+        }
+
+        let declaration = std::fs::read_to_string(&self.file_path)
+            .ok()
+            .and_then(|content| {
+                TypeScriptParser::new()
+                    .describe_class(&content, class_name)
+                    .ok()
+                    .flatten()
+            });
+
+        if let Some((declaration, heritage)) = declaration {
+            self.line = Some(declaration.line);
+            self.column = Some(declaration.column);
+            self.span_start = Some(declaration.start);
+            self.span_end = Some(declaration.end);
+            self.super_class = heritage.super_class;
+            self.interfaces = Some(heritage.interfaces);
+        }
+
+        self
+    }
+}
+
+/// Progress of a running workspace scan
+#[napi(object)]
+pub struct SearchProgress {
+    pub files_scanned: u32,
+    pub current_directory: String,
+    pub matches: u32,
+    /// Files skipped for exceeding `maxFileSize`
+    pub skipped_large: u32,
+    /// Files skipped because their content is not text
+    pub skipped_binary: u32,
+}
+
+/// A class whose name resembles the searched one
+#[napi(object)]
+pub struct FuzzyMatch {
+    pub file_path: String,
+    pub class_name: String,
+    /// Name similarity from 0 to 1
+    pub score: f64,
+}
+
+/// Statistics of a finished lookup
+#[napi(object)]
+pub struct SearchStats {
+    /// Files visited by the workspace walk
+    pub files_visited: u32,
+    /// Files whose content was read
+    pub files_read: u32,
+    pub bytes_read: f64,
+    /// Files re-read to bring the class index up to date
+    pub files_reindexed: u32,
+    pub cache_hits: u32,
+    pub cache_misses: u32,
+    /// Share of match answers served from cache, from 0 to 1
+    pub cache_hit_rate: f64,
+    pub elapsed_ms: f64,
+}
+
+impl From<ScanStats> for SearchStats {
+    fn from(stats: ScanStats) -> Self {
+        Self {
+            files_visited: stats.files_visited as u32,
+            files_read: stats.files_read as u32,
+            bytes_read: stats.bytes_read as f64,
+            files_reindexed: stats.files_reindexed as u32,
+            cache_hits: stats.cache_hits as u32,
+            cache_misses: stats.cache_misses as u32,
+            cache_hit_rate: stats.cache_hit_rate(),
+            elapsed_ms: stats.elapsed.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+/// Optional settings for file location
+#[napi(object, object_to_js = false)]
+#[derive(Default)]
+pub struct LocateOptions {
+    /// Confirm candidates by parsing them, ignoring matches in comments or strings
+    pub verify_ast: Option<bool>,
+    /// Include/exclude globs relative to the workspace (e.g. `src/**`, `!**/*.spec.ts`)
+    pub globs: Option<Vec<String>>,
+    /// Also return the line, column and byte span of the class declaration
+    pub include_declaration: Option<bool>,
+    /// Fall back to similar class names (edit distance / camel humps) when
+    /// the exact name is not found, e.g. for names from minified builds
+    pub fuzzy: Option<bool>,
+    /// Minimum similarity of fuzzy candidates, from 0 to 1 (default 0.6)
+    pub fuzzy_threshold: Option<f64>,
+    /// Project name, or a path inside the project, from angular.json / Nx config
+    pub project: Option<String>,
+    /// `restrict` (default) limits the search to the project, `prioritize`
+    /// ranks its files first
+    pub project_scope: Option<String>,
+    /// Skip files larger than this many bytes (default 2 MiB)
+    pub max_file_size: Option<u32>,
+    /// Also search ambient declaration files (`.d.ts`, `.d.mts`, `.d.cts`)
+    pub scan_declaration_files: Option<bool>,
+    /// Persist class match answers under `.xray/index` across restarts
+    pub persist_match_cache: Option<bool>,
+    /// Examine at most this many candidate files per class
+    pub max_results: Option<u32>,
+    /// Stop searching after this many milliseconds, returning partial results
+    pub timeout_ms: Option<u32>,
+    /// Fall back to the typings and `fesm` bundles of installed packages when
+    /// the class is not declared in the workspace sources
+    pub search_node_modules: Option<bool>,
+    /// Follow symlinked files and directories, visiting each real directory once
+    pub follow_symlinks: Option<bool>,
+    /// Maximum directory depth below the workspace (default 32 when following
+    /// symlinks)
+    pub max_depth: Option<u32>,
+    /// Directory names to skip, replacing the default list (`node_modules`,
+    /// `dist`, `out`, `build`, `.git`, `.vscode`, `target`, `coverage`)
+    pub skip_dirs: Option<Vec<String>>,
+    /// Directory names to skip on top of the list (e.g. `storybook-static`)
+    pub extra_skip_dirs: Option<Vec<String>>,
+    /// Called with scan progress; only accepted by asynchronous searches,
+    /// since synchronous ones block the event loop until they return
+    pub on_progress: Option<ThreadsafeFunction<SearchProgress, ErrorStrategy::Fatal>>,
+}
+
+impl LocateOptions {
+    /// Options for a synchronous search, which cannot deliver progress
+    fn synchronous(options: Option<Self>) -> Result<Self> {
+        let options = options.unwrap_or_default();
+        if options.on_progress.is_some() {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "onProgress is only supported by asynchronous searches".to_string(),
+            ));
+        }
+        Ok(options)
+    }
+}
+
+impl TryFrom<LocateOptions> for LocatorConfig {
+    type Error = Error;
+
+    fn try_from(options: LocateOptions) -> Result<Self> {
+        Ok(LocatorConfig {
+            verify_ast: options.verify_ast.unwrap_or(false),
+            globs: options.globs.unwrap_or_default(),
+            cancellation: None,
+            project: options.project,
+            project_scope: parse_setting(
+                options.project_scope.as_deref(),
+                "project scope",
+                ProjectScope::parse,
+            )?,
+            max_file_size: options.max_file_size.map(u64::from),
+            scan_declaration_files: options.scan_declaration_files.unwrap_or(false),
+            persist_match_cache: options.persist_match_cache.unwrap_or(false),
+            max_results: options.max_results.map(|max| max as usize),
+            search_node_modules: options.search_node_modules.unwrap_or(false),
+            follow_symlinks: options.follow_symlinks.unwrap_or(false),
+            max_depth: options.max_depth.map(|depth| depth as usize),
+            skip_dirs: options.skip_dirs,
+            extra_skip_dirs: options.extra_skip_dirs.unwrap_or_default(),
+            timeout: options
+                .timeout_ms
+                .map(|ms| std::time::Duration::from_millis(u64::from(ms))),
+            progress: options.on_progress.map(|callback| {
+                ProgressReporter(Arc::new(move |progress| {
+                    callback.call(
+                        SearchProgress {
+                            files_scanned: progress.files_scanned as u32,
+                            current_directory: progress.current_directory.clone(),
+                            matches: progress.matches as u32,
+                            skipped_large: progress.skipped_large as u32,
+                            skipped_binary: progress.skipped_binary as u32,
+                        },
+                        ThreadsafeFunctionCallMode::NonBlocking,
+                    );
+                }))
+            }),
+        })
+    }
+}
+
+#[napi(object)]
+pub struct FileCandidate {
+    pub file_path: String,
+    /// Confidence that this file holds the searched class, from 0 to 1
+    pub score: f64,
+    /// Path heuristics that raised or lowered the score
+    pub reasons: Vec<String>,
+}
+
+#[napi(object)]
+pub struct ComponentLocation {
+    pub file_path: String,
+    pub class_name: String,
+    pub found: bool,
+}
+
+#[napi(object)]
+pub struct ClassLocation {
+    pub file_path: String,
+    pub class_name: String,
+}
+
+#[napi(object)]
+pub struct ClassSummary {
+    pub file_path: String,
+    pub class_name: String,
+    /// Decorator names without `@`, in source order
+    pub decorators: Vec<String>,
+    /// Methods declared in the class, excluding the constructor and accessors
+    pub method_count: u32,
+}
+
+#[napi(object)]
+pub struct ComponentRuntimeCost {
+    pub class_name: String,
+    /// Change detection runs recorded for the component
+    pub change_detection_count: u32,
+    /// Total time spent checking the component
+    pub duration_ms: f64,
+}
+
+#[napi(object)]
+pub struct OnPushBlockerInfo {
+    /// e.g. `ngOnInit mutates this.items`
+    pub reason: String,
+    /// Line of the offending input or write (1-based)
+    pub line: u32,
+}
+
+#[napi(object)]
+pub struct OnPushCandidateInfo {
+    pub file_path: String,
+    pub class_name: String,
+    /// Class line (1-based)
+    pub line: u32,
+    /// No blockers: the component can switch to `OnPush` as is
+    pub eligible: bool,
+    pub blockers: Vec<OnPushBlockerInfo>,
+    /// Runtime cost, when given for the component
+    pub change_detection_count: Option<u32>,
+    pub duration_ms: Option<f64>,
+}
+
+#[napi(object)]
+pub struct UnmanagedSubscriptionInfo {
+    pub file_path: String,
+    pub class_name: String,
+    /// Method or property containing the `subscribe()` call
+    pub member_name: String,
+    /// The subscribed observable, e.g. `this.route.params`
+    pub source: String,
+    /// Call line (1-based)
+    pub line: u32,
+    /// Call column (1-based)
+    pub column: u32,
+}
+
+#[napi(object)]
+pub struct ClassMethodLocation {
+    pub file_path: String,
+    /// Method line (1-based)
+    pub line: u32,
+    /// Method column (1-based)
+    pub column: u32,
+    pub found: bool,
+}
+
+#[napi(object)]
+pub struct ClassMethodInfo {
+    pub name: String,
+    /// `method`, `getter`, `setter` or `constructor`
+    pub kind: String,
+    /// First line of the method (1-based)
+    pub line: u32,
+    /// Last line of the method (1-based)
+    pub end_line: u32,
+    pub is_static: bool,
+    /// `public`, `protected` or `private`
+    pub visibility: String,
+    pub is_async: bool,
+}
+
+#[napi(object)]
+pub struct ComponentBindingInfo {
+    /// Class property name
+    pub name: String,
+    /// Public name, when aliased
+    pub alias: Option<String>,
+    /// `input`, `output` or `model`
+    pub direction: String,
+    /// Property line (1-based)
+    pub line: u32,
+    /// Declared type as written, if any
+    pub type_text: Option<String>,
+    pub required: bool,
+}
+
+#[napi(object)]
+pub struct LifecycleHookInfo {
+    /// Hook method name, e.g. `ngOnInit`
+    pub name: String,
+    /// First line of the hook (1-based)
+    pub line: u32,
+    /// Last line of the hook (1-based)
+    pub end_line: u32,
+    /// The class lists the hook interface (e.g. `OnInit`) in `implements`
+    pub declares_interface: bool,
+}
+
+#[napi(object)]
+pub struct MethodCallInfo {
+    /// Callee path with call arguments elided, e.g. `this.http.get().pipe`
+    pub callee: String,
+    /// Name of the called function or method, e.g. `pipe`
+    pub name: String,
+    /// Call line (1-based)
+    pub line: u32,
+    /// Call column (1-based)
+    pub column: u32,
+}
+
+#[napi(object)]
+pub struct ComponentImportInfo {
+    /// Symbol as written, e.g. `MatButtonModule`
+    pub symbol: String,
+    /// Module the symbol is imported from, when imported in the file
+    pub source: Option<String>,
+    /// The symbol is itself a list of imports (`...SHARED_IMPORTS`)
+    pub spread: bool,
+    /// Symbol line (1-based)
+    pub line: u32,
+    /// Symbol column (1-based)
+    pub column: u32,
+}
+
+#[napi(object)]
+pub struct PositionContextInfo {
+    pub class_name: String,
+    /// Member containing the position, if any
+    pub member_name: Option<String>,
+    /// `method`, `getter`, `setter` or `constructor`; unset for properties
+    pub member_kind: Option<String>,
+    /// `Class.member`, the key of the method in performance data
+    pub method_key: Option<String>,
+    /// Synthetic name of the anonymous callback containing the position
+    pub callback: Option<String>,
+    /// Identifier under the position
+    pub identifier: Option<String>,
+}
+
+#[napi(object)]
+pub struct IgnoredMethodInfo {
+    pub class_name: String,
+    pub method_name: String,
+    /// Method line (1-based)
+    pub line: u32,
+    /// Text following the `@xray:ignore` marker
+    pub reason: Option<String>,
+}
+
+#[napi(object)]
+pub struct AnonymousCallbackInfo {
+    /// Synthetic name, e.g. `UserService.load$.subscribe#2`
+    pub name: String,
+    pub class_name: String,
+    pub member_name: String,
+    /// Function receiving the callback, e.g. `subscribe`
+    pub callee: String,
+    /// Rank among the callbacks passed to `callee` in the member (1-based)
+    pub index: u32,
+    /// Callback line (1-based)
+    pub line: u32,
+    /// Callback column (1-based)
+    pub column: u32,
+    /// Line of the callback end (1-based)
+    pub end_line: u32,
+}
+
+#[napi(object)]
+pub struct PipeOperatorInfo {
+    /// Operator name, e.g. `debounceTime`
+    pub name: String,
+    /// Number of arguments given to the operator
+    pub arity: u32,
+    /// Operator line (1-based)
+    pub line: u32,
+}
+
+#[napi(object)]
+pub struct PipeChainInfo {
+    /// The piped observable, e.g. `this.http.get()`
+    pub source: String,
+    pub operators: Vec<PipeOperatorInfo>,
+    /// Line of the `pipe` call (1-based)
+    pub line: u32,
+    /// Column of the `pipe` call (1-based)
+    pub column: u32,
+}
+
+#[napi(object)]
+pub struct MethodMetrics {
+    pub cyclomatic_complexity: u32,
+    /// Statements in the body, not counting blocks
+    pub statement_count: u32,
+    /// Deepest nesting of control-flow statements
+    pub max_nesting_depth: u32,
+    /// Lines spanned by the method
+    pub line_count: u32,
+}
+
+#[napi(object)]
+pub struct SourcePosition {
+    /// Resolved path of the original source file
+    pub file_path: String,
+    /// Source as listed in the map
+    pub source: String,
+    /// Original line (1-based)
+    pub line: u32,
+    /// Original column (1-based)
+    pub column: u32,
+    /// Original identifier at the position, if recorded
+    pub name: Option<String>,
+}
+
+#[napi(object)]
+pub struct TemplateBindingInfo {
+    /// Template file containing the binding
+    pub file_path: String,
+    /// `event`, `property`, `twoWay`, `structural` or `interpolation`
+    pub kind: String,
+    /// Bound event, property or directive, e.g. `click`
+    pub name: Option<String>,
+    /// The bound expression, e.g. `save($event)`
+    pub expression: String,
+    /// Binding line (1-based)
+    pub line: u32,
+    /// Binding column (1-based)
+    pub column: u32,
+}
+
+#[napi(object)]
+pub struct TemplateWarningInfo {
+    /// `functionCall`, `missingTrackBy` or `largeStaticList`
+    pub kind: String,
+    /// The offending identifier, e.g. `getTotal`
+    pub identifier: String,
+    /// The bound expression
+    pub expression: String,
+    /// Binding line (1-based)
+    pub line: u32,
+    /// Binding column (1-based)
+    pub column: u32,
+}
+
+#[napi(object)]
+pub struct PropertyLocation {
+    /// Property line (1-based)
+    pub line: u32,
+    /// Property column (1-based)
+    pub column: u32,
+    /// Line of the end of the property and its initializer (1-based)
+    pub end_line: u32,
+    pub found: bool,
+}
+
+#[napi(object)]
+pub struct InstrumentOptions {
+    /// Apply this decorator, e.g. `Profile`, instead of wrapping bodies in
+    /// `performance.mark` calls
+    pub decorator: Option<String>,
+    /// Module the decorator is imported from, e.g. `@xray/runtime`
+    pub decorator_import: Option<String>,
+}
+
+#[napi(object)]
+pub struct SourceEdit {
+    /// Start line of the replaced text (1-based)
+    pub start_line: u32,
+    /// Start column of the replaced text (1-based)
+    pub start_column: u32,
+    /// End line of the replaced text (1-based)
+    pub end_line: u32,
+    /// Column just past the replaced text (1-based)
+    pub end_column: u32,
+    pub text: String,
+}
+
+#[napi(object)]
+pub struct InstrumentResult {
+    pub source: String,
+    /// Edits of `source` restoring the original, to apply in order
+    pub reverse_patch: Vec<SourceEdit>,
+    pub instrumented: Vec<String>,
+    /// Methods not declared, without a body, constructors or already
+    /// instrumented
+    pub skipped: Vec<String>,
 }
 
 #[napi(object)]
 pub struct MethodLocation {
+    /// Method line (1-based)
     pub line: u32,
+    /// Method column (1-based)
+    pub column: u32,
+    /// Line of the method end (1-based)
+    pub end_line: u32,
+    /// Column just past the method end (1-based)
+    pub end_column: u32,
     pub found: bool,
+    /// `method`, `getter`, `setter` or `constructor`, when found
+    pub kind: Option<String>,
+    /// Lines (1-based) of the overload signatures of an overloaded method;
+    /// `line` points at the implementation
+    pub overload_lines: Vec<u32>,
+    /// Decorators applied to the method, e.g. `@Debounce(300)`
+    pub decorators: Vec<MethodDecoratorInfo>,
+    /// Syntax errors worked around by `parseMethodTolerant`
+    pub diagnostics: Vec<ParseDiagnosticInfo>,
+    /// Text of the method's leading `/** */` comment
+    pub doc_comment: Option<String>,
+}
+
+#[napi(object)]
+pub struct ParseDiagnosticInfo {
+    pub message: String,
+    /// Error line (1-based)
+    pub line: u32,
+    /// Error column (1-based)
+    pub column: u32,
+}
+
+#[napi(object)]
+pub struct MethodDecoratorInfo {
+    /// Decorator name without arguments, e.g. `Debounce`
+    pub name: String,
+    /// Source text of the decorator, including `@` and arguments
+    pub text: String,
+    /// Decorator line (1-based)
+    pub line: u32,
 }
 
 /// Locates a TypeScript file containing the specified class
@@ -29,52 +667,1799 @@ pub struct MethodLocation {
 /// # Arguments
 /// * `class_name` - The name of the class to search for
 /// * `workspace_path` - The root workspace path to search in
+/// * `options` - Optional search settings
+///
+/// # Returns
+/// FileLocation with the path and whether it was found
+#[napi]
+pub fn locate_file(
+    class_name: String,
+    workspace_path: String,
+    options: Option<LocateOptions>,
+) -> Result<FileLocation> {
+    let options = LocateOptions::synchronous(options)?;
+    let include_declaration = options.include_declaration.unwrap_or(false);
+    let fuzzy = options.fuzzy.unwrap_or(false);
+    let fuzzy_threshold = options.fuzzy_threshold.unwrap_or(DEFAULT_FUZZY_THRESHOLD);
+    let locator = FileLocator::new(workspace_path).with_config(options.try_into()?);
+
+    let location = match locator.find_class(&class_name) {
+        Ok(Some(path)) if include_declaration => {
+            FileLocation::found(path).with_declaration(&class_name)
+        }
+        Ok(Some(path)) => FileLocation::found(path),
+        Ok(None) if fuzzy => {
+            let candidates = locator
+                .find_class_fuzzy(&class_name, fuzzy_threshold)
+                .map_err(|e| Error::from_reason(format!("Failed to locate file: {}", e)))?;
+            let mut location = match candidates.first() {
+                Some(best) if include_declaration => {
+                    FileLocation::found(best.path.clone()).with_declaration(&best.class_name)
+                }
+                Some(best) => FileLocation::found(best.path.clone()),
+                None => FileLocation::not_found(),
+            };
+            location.fuzzy_candidates = Some(
+                candidates
+                    .into_iter()
+                    .map(|candidate| FuzzyMatch {
+                        file_path: candidate.path,
+                        class_name: candidate.class_name,
+                        score: candidate.score,
+                    })
+                    .collect(),
+            );
+            location
+        }
+        Ok(None) => FileLocation::not_found(),
+        Err(e) => return Err(Error::from_reason(format!("Failed to locate file: {}", e))),
+    };
+
+    Ok(location
+        .with_truncated(locator.truncated())
+        .with_stats(&locator))
+}
+
+/// Cancellation handle for asynchronous searches
+///
+/// Mirror an `AbortSignal` onto it (`signal.addEventListener('abort', () =>
+/// handle.cancel())`) to interrupt a search that is already running; the
+/// signal alone can only cancel searches that have not started yet.
+#[napi]
+pub struct SearchCancellation {
+    flag: Arc<AtomicBool>,
+}
+
+#[napi]
+impl SearchCancellation {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self {
+            flag: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Requests that any search using this handle stops as soon as possible
+    #[napi]
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    #[napi(getter)]
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for SearchCancellation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Background task backing `locate_file_async`
+pub struct LocateFileTask {
+    class_name: String,
+    workspace_path: String,
+    config: LocatorConfig,
+    include_declaration: bool,
+}
+
+impl Task for LocateFileTask {
+    type Output = FileLocation;
+    type JsValue = FileLocation;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let locator = FileLocator::new(&self.workspace_path).with_config(self.config.clone());
+
+        let path = locator.find_class(&self.class_name).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::Interrupted {
+                Error::new(Status::Cancelled, "AbortError".to_string())
+            } else {
+                Error::from_reason(format!("Failed to locate file: {}", e))
+            }
+        })?;
+
+        let location = match path {
+            Some(path) if self.include_declaration => {
+                FileLocation::found(path).with_declaration(&self.class_name)
+            }
+            Some(path) => FileLocation::found(path),
+            None => FileLocation::not_found(),
+        };
+
+        Ok(location
+            .with_truncated(locator.truncated())
+            .with_stats(&locator))
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Locates a TypeScript file containing the specified class off the main thread
+///
+/// # Arguments
+/// * `class_name` - The name of the class to search for
+/// * `workspace_path` - The root workspace path to search in
+/// * `options` - Optional search settings
+/// * `cancellation` - Optional handle to interrupt the running search
+/// * `signal` - Optional AbortSignal cancelling the search before it starts
+///
+/// # Returns
+/// Promise of a FileLocation; rejects with `AbortError` when cancelled
+#[napi]
+pub fn locate_file_async(
+    class_name: String,
+    workspace_path: String,
+    options: Option<LocateOptions>,
+    cancellation: Option<ClassInstance<SearchCancellation>>,
+    signal: Option<AbortSignal>,
+) -> Result<AsyncTask<LocateFileTask>> {
+    let options = options.unwrap_or_default();
+    let include_declaration = options.include_declaration.unwrap_or(false);
+    let mut config: LocatorConfig = options.try_into()?;
+    config.cancellation = cancellation.map(|handle| Arc::clone(&handle.flag));
+
+    Ok(AsyncTask::with_optional_signal(
+        LocateFileTask {
+            class_name,
+            workspace_path,
+            config,
+            include_declaration,
+        },
+        signal,
+    ))
+}
+
+/// Locates the TypeScript files for many classes in one workspace traversal
+///
+/// # Arguments
+/// * `class_names` - The names of the classes to search for
+/// * `workspace_path` - The root workspace path to search in
+/// * `options` - Optional search settings
+///
+/// # Returns
+/// One FileLocation per class name, in the same order
+#[napi]
+pub fn locate_files(
+    class_names: Vec<String>,
+    workspace_path: String,
+    options: Option<LocateOptions>,
+) -> Result<Vec<FileLocation>> {
+    let options = LocateOptions::synchronous(options)?;
+    let locator = FileLocator::new(workspace_path).with_config(options.try_into()?);
+
+    match locator.find_classes(&class_names) {
+        Ok(paths) => Ok(paths
+            .into_iter()
+            .map(|path| {
+                match path {
+                    Some(path) => FileLocation::found(path),
+                    None => FileLocation::not_found(),
+                }
+                .with_truncated(locator.truncated())
+                .with_stats(&locator)
+            })
+            .collect()),
+        Err(e) => Err(Error::from_reason(format!("Failed to locate files: {}", e))),
+    }
+}
+
+/// Locates every TypeScript file containing the specified class
+///
+/// # Arguments
+/// * `class_name` - The name of the class to search for
+/// * `workspace_path` - The root workspace path to search in
+/// * `options` - Optional search settings
+///
+/// # Returns
+/// Candidate files ranked by confidence (highest first); empty if none found
+#[napi]
+pub fn locate_file_all(
+    class_name: String,
+    workspace_path: String,
+    options: Option<LocateOptions>,
+) -> Result<Vec<FileCandidate>> {
+    let options = LocateOptions::synchronous(options)?;
+    let locator = FileLocator::new(workspace_path).with_config(options.try_into()?);
+
+    match locator.find_class_all(&class_name) {
+        Ok(candidates) => Ok(candidates
+            .into_iter()
+            .map(|c| FileCandidate {
+                file_path: c.path,
+                score: c.score,
+                reasons: c.reasons,
+            })
+            .collect()),
+        Err(e) => Err(Error::from_reason(format!("Failed to locate file: {}", e))),
+    }
+}
+
+/// Locates a TypeScript file declaring a top-level symbol
+///
+/// # Arguments
+/// * `symbol_name` - The name of the symbol to search for
+/// * `kind` - One of `class`, `interface`, `enum`, `type`, `function` or `const`
+/// * `workspace_path` - The root workspace path to search in
 ///
 /// # Returns
 /// FileLocation with the path and whether it was found
 #[napi]
-pub fn locate_file(class_name: String, workspace_path: String) -> Result<FileLocation> {
+pub fn locate_symbol(
+    symbol_name: String,
+    kind: String,
+    workspace_path: String,
+) -> Result<FileLocation> {
+    let kind = SymbolKind::parse(&kind)
+        .ok_or_else(|| Error::from_reason(format!("Unknown symbol kind: {}", kind)))?;
+    let locator = FileLocator::new(workspace_path);
+
+    match locator.find_symbol(&symbol_name, kind) {
+        Ok(Some(path)) => Ok(FileLocation::found(path).with_stats(&locator)),
+        Ok(None) => Ok(FileLocation::not_found().with_stats(&locator)),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to locate symbol: {}",
+            e
+        ))),
+    }
+}
+
+/// Locates the Angular component declaring a DOM selector
+///
+/// # Arguments
+/// * `selector` - The component selector (e.g. `app-user-list`)
+/// * `workspace_path` - The root workspace path to search in
+///
+/// # Returns
+/// ComponentLocation with the path, class name and whether it was found
+#[napi]
+pub fn locate_component_by_selector(
+    selector: String,
+    workspace_path: String,
+) -> Result<ComponentLocation> {
     let locator = FileLocator::new(workspace_path);
 
-    match locator.find_class(&class_name) {
-        Ok(Some(path)) => Ok(FileLocation {
+    match locator.find_component_by_selector(&selector) {
+        Ok(Some((path, class_name))) => Ok(ComponentLocation {
             file_path: path,
+            class_name,
             found: true,
         }),
-        Ok(None) => Ok(FileLocation {
+        Ok(None) => Ok(ComponentLocation {
             file_path: String::new(),
+            class_name: String::new(),
             found: false,
         }),
-        Err(e) => Err(Error::from_reason(format!("Failed to locate file: {}", e))),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to locate component: {}",
+            e
+        ))),
     }
 }
 
-/// Parses TypeScript file content to find the line number of a method
+/// Locates the component class that uses an HTML template file
 ///
 /// # Arguments
-/// * `file_content` - The content of the TypeScript file
-/// * `method_name` - The name of the method to locate
+/// * `template_path` - The template file, absolute or relative to the workspace
+/// * `workspace_path` - The root workspace path to search in
 ///
 /// # Returns
-/// MethodLocation with the line number and whether it was found
+/// ComponentLocation with the path, class name and whether it was found
 #[napi]
-pub fn parse_method(file_content: String, method_name: String) -> Result<MethodLocation> {
-    let parser = TypeScriptParser::new();
+pub fn locate_component_for_template(
+    template_path: String,
+    workspace_path: String,
+) -> Result<ComponentLocation> {
+    let template = std::path::Path::new(&workspace_path).join(&template_path);
+    let locator = FileLocator::new(&workspace_path);
 
-    match parser.find_method_line(&file_content, &method_name) {
-        Ok(Some(line)) => Ok(MethodLocation { line, found: true }),
-        Ok(None) => Ok(MethodLocation {
-            line: 0,
+    match locator.find_component_for_template(&template) {
+        Ok(Some((path, class_name))) => Ok(ComponentLocation {
+            file_path: path,
+            class_name,
+            found: true,
+        }),
+        Ok(None) => Ok(ComponentLocation {
+            file_path: String::new(),
+            class_name: String::new(),
             found: false,
         }),
-        Err(e) => Err(Error::from_reason(format!("Failed to parse method: {}", e))),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to locate component: {}",
+            e
+        ))),
     }
 }
 
-// Wrapper functions to properly export NAPI functions from modules
+/// Locates every class carrying an Angular decorator
+///
+/// # Arguments
+/// * `decorator_name` - The decorator without `@` (e.g. `Injectable`, `Component`,
+///   `Directive`, `Pipe`)
+/// * `workspace_path` - The root workspace path to search in
+///
+/// # Returns
+/// The decorated classes and their files, in path order
 #[napi]
-pub fn build_flame_graph_data(call_stack_json: String) -> Result<String> {
-    flame_graph::build_flame_graph_data(call_stack_json)
+pub fn locate_by_decorator(
+    decorator_name: String,
+    workspace_path: String,
+) -> Result<Vec<ClassLocation>> {
+    let locator = FileLocator::new(workspace_path);
+
+    match locator.find_by_decorator(decorator_name.trim_start_matches('@')) {
+        Ok(classes) => Ok(classes
+            .into_iter()
+            .map(|(file_path, class_name)| ClassLocation {
+                file_path,
+                class_name,
+            })
+            .collect()),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to locate decorated classes: {}",
+            e
+        ))),
+    }
+}
+
+/// Lists every exported class of a workspace for bulk instrumentation
+///
+/// # Arguments
+/// * `workspace_path` - The root workspace path to search in
+///
+/// # Returns
+/// The exported classes with their files, decorators and method counts, in
+/// path order
+#[napi]
+pub fn enumerate_classes(workspace_path: String) -> Result<Vec<ClassSummary>> {
+    let locator = FileLocator::new(workspace_path);
+
+    match locator.enumerate_classes() {
+        Ok(classes) => Ok(classes
+            .into_iter()
+            .map(|(file_path, summary)| ClassSummary {
+                file_path,
+                class_name: summary.name,
+                decorators: summary.decorators,
+                method_count: summary.method_count as u32,
+            })
+            .collect()),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to enumerate classes: {}",
+            e
+        ))),
+    }
+}
+
+/// Suggests components that could switch to `OnPush` change detection
+///
+/// Every component using default change detection is reported, with what
+/// stands in the way: inputs of mutable types and lifecycle hooks writing
+/// component state. Runtime costs recorded by the probe rank the
+/// suggestions.
+///
+/// # Arguments
+/// * `workspace_path` - The root workspace path to search in
+/// * `runtime_costs` - Change detection cost of components, by class name
+///
+/// # Returns
+/// Eligible components first, then the others, each by decreasing runtime
+/// cost
+#[napi]
+pub fn find_on_push_candidates(
+    workspace_path: String,
+    runtime_costs: Option<Vec<ComponentRuntimeCost>>,
+) -> Result<Vec<OnPushCandidateInfo>> {
+    let locator = FileLocator::new(workspace_path);
+    let costs = runtime_costs
+        .unwrap_or_default()
+        .into_iter()
+        .map(|cost| {
+            let runtime = change_detection::RuntimeCost {
+                change_detection_count: cost.change_detection_count,
+                duration_ms: cost.duration_ms,
+            };
+            (cost.class_name, runtime)
+        })
+        .collect();
+
+    match change_detection::find_on_push_candidates(&locator, &costs) {
+        Ok(candidates) => Ok(candidates
+            .into_iter()
+            .map(|candidate| OnPushCandidateInfo {
+                file_path: candidate.file_path,
+                class_name: candidate.class_name,
+                line: candidate.line,
+                eligible: candidate.blockers.is_empty(),
+                blockers: candidate
+                    .blockers
+                    .into_iter()
+                    .map(|blocker| OnPushBlockerInfo {
+                        reason: blocker.reason,
+                        line: blocker.line,
+                    })
+                    .collect(),
+                change_detection_count: candidate
+                    .runtime
+                    .map(|runtime| runtime.change_detection_count),
+                duration_ms: candidate.runtime.map(|runtime| runtime.duration_ms),
+            })
+            .collect()),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to find OnPush candidates: {}",
+            e
+        ))),
+    }
+}
+
+/// Finds `subscribe()` calls whose subscription can leak
+///
+/// A subscription leaks when its result is discarded and the observable is
+/// not piped through `takeUntil`, `takeUntilDestroyed` or a similar
+/// operator; leaks commonly explain performance that degrades over a
+/// session.
+///
+/// # Arguments
+/// * `workspace_path` - The root workspace path to search in
+///
+/// # Returns
+/// The calls in path order, then source order
+#[napi]
+pub fn find_unmanaged_subscriptions(
+    workspace_path: String,
+) -> Result<Vec<UnmanagedSubscriptionInfo>> {
+    let locator = FileLocator::new(workspace_path);
+
+    match locator.find_unmanaged_subscriptions() {
+        Ok(subscriptions) => Ok(subscriptions
+            .into_iter()
+            .map(|(file_path, subscription)| UnmanagedSubscriptionInfo {
+                file_path,
+                class_name: subscription.class_name,
+                member_name: subscription.member_name,
+                source: subscription.source,
+                line: subscription.line,
+                column: subscription.column,
+            })
+            .collect()),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to find subscriptions: {}",
+            e
+        ))),
+    }
+}
+
+/// Finds the classes that directly extend a base class
+///
+/// # Arguments
+/// * `base_class_name` - The name of the base class
+/// * `workspace_path` - The root workspace path to search in
+///
+/// # Returns
+/// The subclasses and their files, in path order
+#[napi]
+pub fn find_subclasses(
+    base_class_name: String,
+    workspace_path: String,
+) -> Result<Vec<ClassLocation>> {
+    let locator = FileLocator::new(workspace_path);
+
+    match locator.find_subclasses(&base_class_name) {
+        Ok(classes) => Ok(classes
+            .into_iter()
+            .map(|(file_path, class_name)| ClassLocation {
+                file_path,
+                class_name,
+            })
+            .collect()),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to find subclasses: {}",
+            e
+        ))),
+    }
+}
+
+/// Locates the file of a class imported through a tsconfig path alias
+///
+/// # Arguments
+/// * `class_name` - The name of the class to search for
+/// * `import_path` - The aliased import path (e.g. `@shared/services/foo.service`)
+/// * `workspace_path` - The root workspace path containing `tsconfig.json`
+///   or `tsconfig.base.json`
+///
+/// # Returns
+/// FileLocation with the path and whether it was found. Without a readable
+/// tsconfig the class is searched for by name.
+#[napi]
+pub fn locate_file_with_tsconfig(
+    class_name: String,
+    import_path: String,
+    workspace_path: String,
+) -> Result<FileLocation> {
+    let tsconfig = TsConfig::load(&workspace_path);
+    let locator = FileLocator::new(workspace_path);
+    let found = match &tsconfig {
+        Ok(tsconfig) => locator.find_class_with_tsconfig(&class_name, &import_path, tsconfig),
+        Err(_) => locator.find_class(&class_name),
+    };
+
+    match found {
+        Ok(Some(path)) => Ok(FileLocation::found(path).with_stats(&locator)),
+        Ok(None) => Ok(FileLocation::not_found().with_stats(&locator)),
+        Err(e) => Err(Error::from_reason(format!("Failed to locate file: {}", e))),
+    }
+}
+
+/// Locates the spec file that tests a class
+///
+/// # Arguments
+/// * `class_name` - The name of the class under test
+/// * `workspace_path` - The root workspace path to search in
+///
+/// # Returns
+/// FileLocation with the spec file and whether it was found
+#[napi]
+pub fn locate_spec_for_class(class_name: String, workspace_path: String) -> Result<FileLocation> {
+    let locator = FileLocator::new(workspace_path);
+
+    match locator.find_spec_for_class(&class_name) {
+        Ok(Some(path)) => Ok(FileLocation::found(path).with_stats(&locator)),
+        Ok(None) => Ok(FileLocation::not_found().with_stats(&locator)),
+        Err(e) => Err(Error::from_reason(format!("Failed to locate spec: {}", e))),
+    }
+}
+
+/// Locates the file defining a class that is re-exported through a barrel
+///
+/// # Arguments
+/// * `class_name` - The exported name of the class
+/// * `barrel_path` - The barrel file or its directory, absolute or relative to
+///   the workspace
+/// * `workspace_path` - The root workspace path
+///
+/// # Returns
+/// FileLocation with the defining file and whether it was found
+#[napi]
+pub fn locate_file_from_barrel(
+    class_name: String,
+    barrel_path: String,
+    workspace_path: String,
+) -> FileLocation {
+    let barrel = std::path::Path::new(&workspace_path).join(&barrel_path);
+    let locator = FileLocator::new(&workspace_path);
+
+    match locator.find_class_via_barrel(&class_name, &barrel) {
+        Some(path) => FileLocation::found(path),
+        None => FileLocation::not_found(),
+    }
+    .with_stats(&locator)
+}
+
+/// Long-lived locator that keeps the class index warm via filesystem events
+///
+/// Intended for latency-sensitive callers such as hover providers: lookups
+/// are answered from memory instead of walking the workspace.
+#[napi]
+pub struct WorkspaceWatcher {
+    inner: Option<ClassWatcher>,
+}
+
+#[napi]
+impl WorkspaceWatcher {
+    /// Indexes the workspace and starts watching it for changes
+    #[napi(constructor)]
+    pub fn new(workspace_path: String) -> Result<Self> {
+        let inner = ClassWatcher::new(workspace_path).map_err(Error::from_reason)?;
+        Ok(Self { inner: Some(inner) })
+    }
+
+    /// Locates a class from the in-memory index
+    ///
+    /// Returns `found: false` once the watcher has been closed.
+    #[napi]
+    pub fn locate_file_cached(&self, class_name: String) -> FileLocation {
+        match self.inner.as_ref().and_then(|w| w.lookup(&class_name)) {
+            Some(path) => FileLocation::found(path),
+            None => FileLocation::not_found(),
+        }
+    }
+
+    /// Stops watching the workspace and releases the index
+    #[napi]
+    pub fn close(&mut self) {
+        self.inner = None;
+    }
+}
+
+/// Long-lived locator that remembers recent results
+///
+/// Lookups first check the directories of recently resolved classes and
+/// their sibling directories, falling back to a full search on a miss.
+#[napi]
+pub struct WorkspaceLocator {
+    inner: WarmLocator,
+    include_declaration: bool,
+}
+
+#[napi]
+impl WorkspaceLocator {
+    /// Creates a locator for a workspace
+    ///
+    /// # Arguments
+    /// * `workspace_path` - The root workspace path to search in
+    /// * `options` - Optional search settings applied to every lookup
+    /// * `capacity` - Number of recent results remembered (default 64)
+    #[napi(constructor)]
+    pub fn new(
+        workspace_path: String,
+        options: Option<LocateOptions>,
+        capacity: Option<u32>,
+    ) -> Result<Self> {
+        let options = LocateOptions::synchronous(options)?;
+        let include_declaration = options.include_declaration.unwrap_or(false);
+        let capacity = capacity.map_or(DEFAULT_RECENT_CAPACITY, |c| c as usize);
+
+        Ok(Self {
+            inner: WarmLocator::new(workspace_path, options.try_into()?, capacity),
+            include_declaration,
+        })
+    }
+
+    /// Locates a TypeScript file containing the specified class
+    #[napi]
+    pub fn locate_file(&mut self, class_name: String) -> Result<FileLocation> {
+        let (path, locator) = self
+            .inner
+            .lookup(&class_name)
+            .map_err(|e| Error::from_reason(format!("Failed to locate file: {}", e)))?;
+
+        let location = match path {
+            Some(path) if self.include_declaration => {
+                FileLocation::found(path).with_declaration(&class_name)
+            }
+            Some(path) => FileLocation::found(path),
+            None => FileLocation::not_found(),
+        };
+
+        Ok(location
+            .with_truncated(locator.truncated())
+            .with_stats(&locator))
+    }
+}
+
+/// Locates a class and one of its methods in a single native call
+///
+/// # Arguments
+/// * `class_name` - The name of the class declaring the method
+/// * `method_name` - The name of the method to locate
+/// * `workspace_path` - The root workspace path to search in
+///
+/// # Returns
+/// ClassMethodLocation with the file, line, column and whether it was found
+#[napi]
+pub fn locate_method(
+    class_name: String,
+    method_name: String,
+    workspace_path: String,
+) -> Result<ClassMethodLocation> {
+    let locator = FileLocator::new(workspace_path);
+
+    match locator.find_method(&class_name, &method_name) {
+        Ok(Some((file_path, span))) => Ok(ClassMethodLocation {
+            file_path,
+            line: span.line,
+            column: span.column,
+            found: true,
+        }),
+        Ok(None) => Ok(ClassMethodLocation {
+            file_path: String::new(),
+            line: 0,
+            column: 0,
+            found: false,
+        }),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to locate method: {}",
+            e
+        ))),
+    }
+}
+
+/// Parses TypeScript file content to find the line number of a method
+///
+/// Getters, setters and the constructor (as `"constructor"`) are found too;
+/// pass `kind` to pick one when several members share a name.
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `method_name` - The name of the method to locate
+/// * `kind` - Optional member kind: `method`, `getter`, `setter` or `constructor`
+/// * `class_name` - Optional class to search, when the file declares several
+///
+/// # Returns
+/// MethodLocation with the method's start and end position, whether it was
+/// found and its kind
+#[napi]
+pub fn parse_method(
+    file_content: String,
+    method_name: String,
+    kind: Option<String>,
+    class_name: Option<String>,
+) -> Result<MethodLocation> {
+    let kind = member_kind(kind)?;
+    let parser = TypeScriptParser::new();
+
+    match parser.find_member(&file_content, &method_name, kind, class_name.as_deref()) {
+        Ok(member) => Ok(method_location(member)),
+        Err(e) => Err(Error::from_reason(format!("Failed to parse method: {}", e))),
+    }
+}
+
+/// Finds a method like `parse_method`, in a file that may contain syntax errors
+///
+/// Files mid-edit are repaired line by line until they parse, so methods
+/// away from the error still resolve.
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `method_name` - The name of the method to locate
+/// * `kind` - Optional member kind: `method`, `getter`, `setter` or `constructor`
+/// * `class_name` - Optional class to search, when the file declares several
+///
+/// # Returns
+/// MethodLocation, best effort, with the syntax errors found in `diagnostics`
+#[napi]
+pub fn parse_method_tolerant(
+    file_content: String,
+    method_name: String,
+    kind: Option<String>,
+    class_name: Option<String>,
+) -> Result<MethodLocation> {
+    let kind = member_kind(kind)?;
+    let parser = TypeScriptParser::new();
+
+    let (member, diagnostics) =
+        parser.find_member_tolerant(&file_content, &method_name, kind, class_name.as_deref());
+    Ok(MethodLocation {
+        diagnostics: diagnostics
+            .into_iter()
+            .map(|diagnostic| ParseDiagnosticInfo {
+                message: diagnostic.message,
+                line: diagnostic.line,
+                column: diagnostic.column,
+            })
+            .collect(),
+        ..method_location(member)
+    })
+}
+
+/// Parses TypeScript file content once to find the lines of several methods
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `method_names` - The names of the methods to locate
+///
+/// # Returns
+/// One MethodLocation per name, in the order given
+#[napi]
+pub fn parse_methods(
+    file_content: String,
+    method_names: Vec<String>,
+) -> Result<Vec<MethodLocation>> {
+    let parser = TypeScriptParser::new();
+
+    match parser.find_members(&file_content, &method_names) {
+        Ok(members) => Ok(members.into_iter().map(method_location).collect()),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to parse methods: {}",
+            e
+        ))),
+    }
+}
+
+fn member_kind(kind: Option<String>) -> Result<Option<MemberKind>> {
+    kind.map(|kind| {
+        MemberKind::parse(&kind)
+            .ok_or_else(|| Error::from_reason(format!("Unknown member kind: {}", kind)))
+    })
+    .transpose()
+}
+
+fn method_location(member: Option<MemberLocation>) -> MethodLocation {
+    match member {
+        Some(member) => MethodLocation {
+            line: member.span.line,
+            column: member.span.column,
+            end_line: member.span.end_line,
+            end_column: member.span.end_column,
+            found: true,
+            kind: Some(member.kind.as_str().to_string()),
+            overload_lines: member.overload_lines,
+            decorators: member
+                .decorators
+                .into_iter()
+                .map(|decorator| MethodDecoratorInfo {
+                    name: decorator.name,
+                    text: decorator.text,
+                    line: decorator.line,
+                })
+                .collect(),
+            diagnostics: Vec::new(),
+            doc_comment: member.doc_comment,
+        },
+        None => MethodLocation {
+            line: 0,
+            column: 0,
+            end_line: 0,
+            end_column: 0,
+            found: false,
+            kind: None,
+            overload_lines: Vec::new(),
+            decorators: Vec::new(),
+            diagnostics: Vec::new(),
+            doc_comment: None,
+        },
+    }
+}
+
+/// Parses TypeScript file content to find the line of a class property
+///
+/// Finds fields such as signals, observables and form groups, whose
+/// initializers (e.g. `computed()` factories) show up in profiles, as well
+/// as constructor parameter properties.
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `class_name` - The name of the class
+/// * `property_name` - The name of the property
+///
+/// # Returns
+/// PropertyLocation with the property's position and whether it was found
+#[napi]
+pub fn find_property_line(
+    file_content: String,
+    class_name: String,
+    property_name: String,
+) -> Result<PropertyLocation> {
+    let parser = TypeScriptParser::new();
+
+    match parser.find_property(&file_content, &class_name, &property_name) {
+        Ok(Some(span)) => Ok(PropertyLocation {
+            line: span.line,
+            column: span.column,
+            end_line: span.end_line,
+            found: true,
+        }),
+        Ok(None) => Ok(PropertyLocation {
+            line: 0,
+            column: 0,
+            end_line: 0,
+            found: false,
+        }),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to parse property: {}",
+            e
+        ))),
+    }
+}
+
+/// Hashes the shape of a method body, ignoring whitespace and comments
+///
+/// Comparing hashes tells a method that got slower apart from one whose
+/// body actually changed.
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `class_name` - The name of the class
+/// * `method_name` - The name of the method
+///
+/// # Returns
+/// The hash as 16 hex digits, or `null` if the method is not declared
+#[napi]
+pub fn method_structural_hash(
+    file_content: String,
+    class_name: String,
+    method_name: String,
+) -> Result<Option<String>> {
+    let parser = TypeScriptParser::new();
+
+    parser
+        .method_structural_hash(&file_content, &class_name, &method_name)
+        .map_err(|e| Error::from_reason(format!("Failed to hash method: {}", e)))
+}
+
+/// Lists the symbols imported by a standalone component
+///
+/// Reads the `imports` array of the `@Component` decorator, so a component
+/// dependency map can be built without the Angular compiler.
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `class_name` - The name of the component class
+///
+/// # Returns
+/// The imported symbols in array order; empty if the class is not a
+/// component or imports nothing
+#[napi]
+pub fn find_component_imports(
+    file_content: String,
+    class_name: String,
+) -> Result<Vec<ComponentImportInfo>> {
+    let parser = TypeScriptParser::new();
+
+    match parser.find_component_imports(&file_content, &class_name) {
+        Ok(imports) => Ok(imports
+            .unwrap_or_default()
+            .into_iter()
+            .map(|import| ComponentImportInfo {
+                symbol: import.symbol,
+                source: import.source,
+                spread: import.spread,
+                line: import.line,
+                column: import.column,
+            })
+            .collect()),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to parse component imports: {}",
+            e
+        ))),
+    }
+}
+
+/// Evaluates the arguments of a class decorator to JSON
+///
+/// Shared configuration spread into `@Component({...})`, template literals
+/// and computed keys are resolved through the file's constants. Values that
+/// are not static are kept as `{"$expression": "<source>"}`.
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `class_name` - The name of the decorated class
+/// * `decorator_name` - The decorator, e.g. `Component`
+///
+/// # Returns
+/// JSON of the decorator's first argument, or `null` if the class does not
+/// use the decorator
+#[napi]
+pub fn extract_decorator_metadata(
+    file_content: String,
+    class_name: String,
+    decorator_name: String,
+) -> Result<Option<String>> {
+    let parser = TypeScriptParser::new();
+
+    parser
+        .find_decorator_metadata(&file_content, &class_name, &decorator_name)
+        .map(|metadata| metadata.map(|metadata| metadata.to_string()))
+        .map_err(|e| Error::from_reason(format!("Failed to parse decorator: {}", e)))
+}
+
+/// Lists the methods opted out of profiling with `// @xray:ignore`
+///
+/// `instrumentClassMethods` skips them already; comparison reports and the
+/// probe use the `Class.method` keys to leave them out as well.
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `class_name` - Only report the methods of this class
+///
+/// # Returns
+/// The ignored methods in source order
+#[napi]
+pub fn find_ignored_methods(
+    file_content: String,
+    class_name: Option<String>,
+) -> Result<Vec<IgnoredMethodInfo>> {
+    let parser = TypeScriptParser::new();
+
+    match parser.find_ignored_methods(&file_content, class_name.as_deref()) {
+        Ok(methods) => Ok(methods
+            .into_iter()
+            .map(|method| IgnoredMethodInfo {
+                class_name: method.class_name,
+                method_name: method.method_name,
+                line: method.line,
+                reason: method.reason,
+            })
+            .collect()),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to parse ignore markers: {}",
+            e
+        ))),
+    }
+}
+
+/// Resolves a source position to the class and method containing it
+///
+/// Used for flame graph frames that only carry a position, to recover their
+/// `Class.method` key.
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `line` - 1-based line
+/// * `column` - 1-based column
+///
+/// # Returns
+/// The enclosing class, member and callback and the identifier under the
+/// position, or `null` if the position is outside every class
+#[napi]
+pub fn find_identifier_at(
+    file_content: String,
+    line: u32,
+    column: u32,
+) -> Result<Option<PositionContextInfo>> {
+    let parser = TypeScriptParser::new();
+
+    match parser.find_identifier_at(&file_content, line, column) {
+        Ok(context) => Ok(context.map(|context| PositionContextInfo {
+            method_key: context
+                .member_name
+                .as_ref()
+                .map(|member| format!("{}.{}", context.class_name, member)),
+            class_name: context.class_name,
+            member_name: context.member_name,
+            member_kind: context.member_kind.map(|kind| kind.as_str().to_string()),
+            callback: context.callback,
+            identifier: context.identifier,
+        })),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to resolve position: {}",
+            e
+        ))),
+    }
+}
+
+fn anonymous_callback_info(callback: parser::AnonymousCallback) -> AnonymousCallbackInfo {
+    AnonymousCallbackInfo {
+        name: callback.name,
+        class_name: callback.class_name,
+        member_name: callback.member_name,
+        callee: callback.callee,
+        index: callback.index,
+        line: callback.span.line,
+        column: callback.span.column,
+        end_line: callback.span.end_line,
+    }
+}
+
+/// Lists the arrow and function expressions passed to calls in a class
+///
+/// Each gets a synthetic name such as `UserService.load$.subscribe#2`,
+/// which stays the same until the member declaring it changes.
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `class_name` - The name of the class
+///
+/// # Returns
+/// The callbacks in source order; empty if the class is not declared
+#[napi]
+pub fn list_anonymous_callbacks(
+    file_content: String,
+    class_name: String,
+) -> Result<Vec<AnonymousCallbackInfo>> {
+    let parser = TypeScriptParser::new();
+
+    match parser.find_anonymous_callbacks(&file_content, Some(&class_name)) {
+        Ok(callbacks) => Ok(callbacks.into_iter().map(anonymous_callback_info).collect()),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to parse callbacks: {}",
+            e
+        ))),
+    }
+}
+
+/// Names the anonymous callback at a position of a profiled call stack
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `line` - 1-based line of the frame
+/// * `column` - 1-based column of the frame
+///
+/// # Returns
+/// The innermost callback containing the position, or `null` if it is not
+/// inside a callback of a class member
+#[napi]
+pub fn name_anonymous_callback(
+    file_content: String,
+    line: u32,
+    column: u32,
+) -> Result<Option<AnonymousCallbackInfo>> {
+    let parser = TypeScriptParser::new();
+
+    parser
+        .find_anonymous_callback_at(&file_content, line, column)
+        .map(|callback| callback.map(anonymous_callback_info))
+        .map_err(|e| Error::from_reason(format!("Failed to parse callbacks: {}", e)))
+}
+
+/// Lists the methods of a class for choosing what to instrument
+///
+/// Includes accessors, arrow-function properties and the constructor.
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `class_name` - The name of the class
+///
+/// # Returns
+/// The class's methods in source order; empty if the class is not declared
+#[napi]
+pub fn list_class_methods(
+    file_content: String,
+    class_name: String,
+) -> Result<Vec<ClassMethodInfo>> {
+    let parser = TypeScriptParser::new();
+
+    match parser.list_class_methods(&file_content, &class_name) {
+        Ok(methods) => Ok(methods
+            .unwrap_or_default()
+            .into_iter()
+            .map(|method| ClassMethodInfo {
+                name: method.name,
+                kind: method.kind.as_str().to_string(),
+                line: method.span.line,
+                end_line: method.span.end_line,
+                is_static: method.is_static,
+                visibility: method.visibility.to_string(),
+                is_async: method.is_async,
+            })
+            .collect()),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to list class methods: {}",
+            e
+        ))),
+    }
+}
+
+/// Lists the inputs and outputs of a component
+///
+/// Covers `@Input()` / `@Output()` properties and setters as well as
+/// signal-based `input()`, `output()` and `model()`.
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `class_name` - The name of the component class
+///
+/// # Returns
+/// The bindings in source order; empty if the class is not declared
+#[napi]
+pub fn list_component_bindings(
+    file_content: String,
+    class_name: String,
+) -> Result<Vec<ComponentBindingInfo>> {
+    let parser = TypeScriptParser::new();
+
+    match parser.find_component_bindings(&file_content, &class_name) {
+        Ok(bindings) => Ok(bindings
+            .unwrap_or_default()
+            .into_iter()
+            .map(|binding| ComponentBindingInfo {
+                name: binding.name,
+                alias: binding.alias,
+                direction: binding.direction.as_str().to_string(),
+                line: binding.line,
+                type_text: binding.type_text,
+                required: binding.required,
+            })
+            .collect()),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to list component bindings: {}",
+            e
+        ))),
+    }
+}
+
+/// Detects the Angular lifecycle hooks a class implements
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `class_name` - The name of the component, directive or service class
+///
+/// # Returns
+/// The implemented hooks in the order Angular calls them; empty if the class
+/// is not declared
+#[napi]
+pub fn detect_lifecycle_hooks(
+    file_content: String,
+    class_name: String,
+) -> Result<Vec<LifecycleHookInfo>> {
+    let parser = TypeScriptParser::new();
+
+    match parser.find_lifecycle_hooks(&file_content, &class_name) {
+        Ok(hooks) => Ok(hooks
+            .unwrap_or_default()
+            .into_iter()
+            .map(|hook| LifecycleHookInfo {
+                name: hook.name.to_string(),
+                line: hook.span.line,
+                end_line: hook.span.end_line,
+                declares_interface: hook.declares_interface,
+            })
+            .collect()),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to detect lifecycle hooks: {}",
+            e
+        ))),
+    }
+}
+
+/// Lists the calls a method makes, for comparison with its runtime children
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `class_name` - The name of the class
+/// * `method_name` - The name of the method
+///
+/// # Returns
+/// The calls in source order, including those made from callbacks; empty if
+/// the method is not declared
+#[napi]
+pub fn extract_method_calls(
+    file_content: String,
+    class_name: String,
+    method_name: String,
+) -> Result<Vec<MethodCallInfo>> {
+    let parser = TypeScriptParser::new();
+
+    match parser.find_method_calls(&file_content, &class_name, &method_name) {
+        Ok(calls) => Ok(calls
+            .unwrap_or_default()
+            .into_iter()
+            .map(|call| MethodCallInfo {
+                callee: call.callee,
+                name: call.name,
+                line: call.line,
+                column: call.column,
+            })
+            .collect()),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to extract method calls: {}",
+            e
+        ))),
+    }
+}
+
+/// Lists the RxJS `pipe(...)` operator chains of a method
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `class_name` - The name of the class
+/// * `method_name` - The name of the method
+///
+/// # Returns
+/// The chains in source order, including those built inside operators;
+/// empty if the method is not declared
+#[napi]
+pub fn extract_pipe_chains(
+    file_content: String,
+    class_name: String,
+    method_name: String,
+) -> Result<Vec<PipeChainInfo>> {
+    let parser = TypeScriptParser::new();
+
+    match parser.find_pipe_chains(&file_content, &class_name, &method_name) {
+        Ok(chains) => Ok(chains
+            .unwrap_or_default()
+            .into_iter()
+            .map(|chain| PipeChainInfo {
+                source: chain.source,
+                operators: chain
+                    .operators
+                    .into_iter()
+                    .map(|operator| PipeOperatorInfo {
+                        name: operator.name,
+                        arity: operator.arity,
+                        line: operator.line,
+                    })
+                    .collect(),
+                line: chain.line,
+                column: chain.column,
+            })
+            .collect()),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to extract pipe chains: {}",
+            e
+        ))),
+    }
+}
+
+/// Measures the cyclomatic complexity and size of a method
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `class_name` - The name of the class
+/// * `method_name` - The name of the method
+///
+/// # Returns
+/// The method's metrics, or `null` if the method is not declared
+#[napi]
+pub fn measure_method(
+    file_content: String,
+    class_name: String,
+    method_name: String,
+) -> Result<Option<MethodMetrics>> {
+    let parser = TypeScriptParser::new();
+
+    match parser.measure_method(&file_content, &class_name, &method_name) {
+        Ok(metrics) => Ok(metrics.map(|metrics| MethodMetrics {
+            cyclomatic_complexity: metrics.cyclomatic_complexity,
+            statement_count: metrics.statement_count,
+            max_nesting_depth: metrics.max_nesting_depth,
+            line_count: metrics.line_count,
+        })),
+        Err(e) => Err(Error::from_reason(format!(
+            "Failed to measure method: {}",
+            e
+        ))),
+    }
+}
+
+/// Maps a position in a generated JavaScript file back to its TypeScript source
+///
+/// # Arguments
+/// * `map_path` - The `.map` file of the generated file
+/// * `line` - The generated line (1-based), as in stack frames
+/// * `column` - The generated column (1-based)
+/// * `workspace_path` - The root workspace path that bundler URLs are relative to
+///
+/// # Returns
+/// The original position, or `null` if the map has no mapping there
+#[napi]
+pub fn map_to_source(
+    map_path: String,
+    line: u32,
+    column: u32,
+    workspace_path: String,
+) -> Result<Option<SourcePosition>> {
+    match sourcemap::resolve_position(
+        std::path::Path::new(&map_path),
+        line,
+        column,
+        std::path::Path::new(&workspace_path),
+    ) {
+        Ok(position) => Ok(position.map(|position| SourcePosition {
+            file_path: position.file_path.to_string_lossy().to_string(),
+            source: position.source,
+            line: position.line,
+            column: position.column,
+            name: position.name,
+        })),
+        Err(e) => Err(Error::from_reason(format!("Failed to map position: {}", e))),
+    }
+}
+
+/// Finds the template bindings that call a component method
+///
+/// Covers event bindings (`(click)="save()"`), property and two-way
+/// bindings, structural directives and `{{ }}` interpolations.
+///
+/// # Arguments
+/// * `template_path` - The template file, absolute or relative to the workspace
+/// * `method_name` - The name of the component method or property
+/// * `workspace_path` - The root workspace path
+///
+/// # Returns
+/// The bindings referencing the method, in template order
+#[napi]
+pub fn find_template_bindings(
+    template_path: String,
+    method_name: String,
+    workspace_path: String,
+) -> Result<Vec<TemplateBindingInfo>> {
+    let template = std::path::Path::new(&workspace_path).join(&template_path);
+    let content = std::fs::read_to_string(&template)
+        .map_err(|e| Error::from_reason(format!("Failed to read template: {}", e)))?;
+    let file_path = template.to_string_lossy().to_string();
+
+    Ok(template::find_method_bindings(&content, &method_name)
+        .into_iter()
+        .map(|binding| TemplateBindingInfo {
+            file_path: file_path.clone(),
+            kind: binding.kind.as_str().to_string(),
+            name: binding.name,
+            expression: binding.expression,
+            line: binding.line,
+            column: binding.column,
+        })
+        .collect())
+}
+
+/// Reports change-detection anti-patterns in an Angular template
+///
+/// Flags function and getter calls in interpolations, property bindings
+/// and structural directives, which re-run on every change detection pass,
+/// and `*ngFor` loops without `trackBy` or over long hard-coded lists.
+///
+/// # Arguments
+/// * `template_content` - The content of the HTML template
+///
+/// # Returns
+/// The warnings in template order
+#[napi]
+pub fn analyze_template_bindings(template_content: String) -> Vec<TemplateWarningInfo> {
+    template::analyze_bindings(&template_content)
+        .into_iter()
+        .map(|warning| TemplateWarningInfo {
+            kind: warning.kind.as_str().to_string(),
+            identifier: warning.identifier,
+            expression: warning.expression,
+            line: warning.line,
+            column: warning.column,
+        })
+        .collect()
+}
+
+/// Rewrites class methods to report their execution time
+///
+/// Bodies are wrapped in `performance.mark` / `performance.measure` calls
+/// named `xray:<Class>.<method>`, or the methods get the decorator named in
+/// the options. Every insertion is tagged with an `@xray` comment.
+///
+/// # Arguments
+/// * `file_content` - The content of the TypeScript file
+/// * `class_name` - The name of the class declaring the methods
+/// * `method_names` - The methods to instrument
+/// * `options` - Use a decorator instead of performance marks
+///
+/// # Returns
+/// The instrumented source and the edits that restore the original
+#[napi]
+pub fn instrument_class_methods(
+    file_content: String,
+    class_name: String,
+    method_names: Vec<String>,
+    options: Option<InstrumentOptions>,
+) -> Result<InstrumentResult> {
+    let instrumentation = match options.and_then(|options| {
+        options
+            .decorator
+            .map(|name| (name, options.decorator_import))
+    }) {
+        Some((name, import_from)) => instrument::Instrumentation::Decorator { name, import_from },
+        None => instrument::Instrumentation::PerformanceMark,
+    };
+
+    let result =
+        instrument::instrument_methods(&file_content, &class_name, &method_names, &instrumentation)
+            .map_err(|e| Error::from_reason(format!("Failed to instrument class: {}", e)))?;
+
+    let position = |offset| instrument::line_column(&result.source, offset);
+    let reverse_patch = result
+        .reverse_patch
+        .iter()
+        .map(|edit| {
+            let (start_line, start_column) = position(edit.start);
+            let (end_line, end_column) = position(edit.end);
+            SourceEdit {
+                start_line,
+                start_column,
+                end_line,
+                end_column,
+                text: edit.text.clone(),
+            }
+        })
+        .collect();
+
+    Ok(InstrumentResult {
+        reverse_patch,
+        source: result.source,
+        instrumented: result.instrumented,
+        skipped: result.skipped,
+    })
+}
+
+/// Removes the instrumentation added by `instrumentClassMethods`
+///
+/// # Arguments
+/// * `file_content` - The content of the instrumented TypeScript file
+///
+/// # Returns
+/// The source without X-Ray wrappers, decorators and imports
+#[napi]
+pub fn strip_instrumentation(file_content: String) -> String {
+    instrument::strip_instrumentation(&file_content)
+}
+
+#[napi(object)]
+pub struct FlameGraphOptions {
+    /// `chronological` (default) orders children by start time,
+    /// `left-heavy` puts the widest children first
+    pub child_order: Option<String>,
+    /// Merge the calls of the same `Class.method` under the same parent into
+    /// one node counting them, as classic aggregated flame graphs do
+    pub aggregate: Option<bool>,
+    /// Only include the calls, clipped, after this time (ms, same clock as
+    /// the call times) to zoom into one interaction of a long recording
+    pub start_time: Option<f64>,
+    /// Only include the calls, clipped, before this time (ms)
+    pub end_time: Option<f64>,
+    /// Collapse the children below this percentage of the total duration
+    /// into one `(other: N calls)` node, to keep huge graphs renderable
+    pub min_percentage: Option<f64>,
+    /// Collapse the children shorter than this many milliseconds likewise
+    pub min_duration_ms: Option<f64>,
+    /// Mark the nodes whose `Class.method` name contains this text with
+    /// `matched` and report their time as `matchedTime`
+    pub search: Option<String>,
+    /// Match `search` as a regular expression
+    pub search_regex: Option<bool>,
+    /// Leave out the nodes below this depth (roots are at 0); the deepest
+    /// nodes kept report `truncatedChildren` and `truncatedTime`
+    pub max_depth: Option<u32>,
+    /// Wrap all roots in one `(session)` root whose value is the total
+    /// duration
+    pub virtual_root: Option<bool>,
+    /// Node values: `duration` (default) in milliseconds, `count` of calls
+    /// or `allocation` in bytes, from `allocatedBytes`
+    pub weight: Option<String>,
+    /// Give each node a `layout` with its `x0`/`x1` extent (fractions of
+    /// the total) and `startAngle`/`endAngle` (radians) for icicle and
+    /// sunburst charts
+    pub layout: Option<bool>,
+    /// Make `percentage` relative to the parent node rather than the total
+    /// duration, as expected when zooming into a subtree
+    pub percent_of_parent: Option<bool>,
+    /// Hash node ids from the path of `Class.method` names from the root
+    /// and the rank among same-named siblings, so nodes can be matched
+    /// across recordings; call ids then move to `callId`
+    pub stable_ids: Option<bool>,
+    /// Keep only this many of the largest children of each node and merge
+    /// the rest into an `(other: N calls)` node, bounding the output of
+    /// huge recordings
+    pub max_children: Option<u32>,
+    /// Only keep the calls whose `Class.method` name starts with, or whose
+    /// file path contains, one of these patterns (e.g. `src/app/`); the
+    /// children of the calls left out take their place
+    pub include_patterns: Option<Vec<String>>,
+    /// Leave out the calls matching one of these patterns likewise, e.g.
+    /// `node_modules/` or `Zone`
+    pub exclude_patterns: Option<Vec<String>>,
+    /// Remove the self time of the calls left out from their ancestors,
+    /// instead of counting it as the self time of their parent
+    pub drop_elided_time: Option<bool>,
+}
+
+/// Parses an optional named setting, rejecting values it does not know
+fn parse_setting<T: Default>(
+    value: Option<&str>,
+    name: &str,
+    parse: fn(&str) -> Option<T>,
+) -> Result<T> {
+    match value {
+        Some(value) => parse(value)
+            .ok_or_else(|| Error::new(Status::InvalidArg, format!("Unknown {}: {}", name, value))),
+        None => Ok(T::default()),
+    }
+}
+
+impl TryFrom<FlameGraphOptions> for FlameGraphConfig {
+    type Error = Error;
+
+    fn try_from(options: FlameGraphOptions) -> Result<Self> {
+        Ok(FlameGraphConfig {
+            child_order: parse_setting(
+                options.child_order.as_deref(),
+                "child order",
+                ChildOrder::parse,
+            )?,
+            aggregate: options.aggregate.unwrap_or(false),
+            start_time: options.start_time,
+            end_time: options.end_time,
+            min_percentage: options.min_percentage,
+            min_duration_ms: options.min_duration_ms,
+            search: options.search,
+            search_regex: options.search_regex.unwrap_or(false),
+            max_depth: options.max_depth,
+            virtual_root: options.virtual_root.unwrap_or(false),
+            weight: parse_setting(options.weight.as_deref(), "weight", Weight::parse)?,
+            layout: options.layout.unwrap_or(false),
+            percent_of_parent: options.percent_of_parent.unwrap_or(false),
+            stable_ids: options.stable_ids.unwrap_or(false),
+            max_children: options.max_children,
+            include_patterns: options.include_patterns.unwrap_or_default(),
+            exclude_patterns: options.exclude_patterns.unwrap_or_default(),
+            drop_elided_time: options.drop_elided_time.unwrap_or(false),
+        })
+    }
+}
+
+// Wrapper functions to properly export NAPI functions from modules
+#[napi]
+pub fn build_flame_graph_data(
+    call_stack: Either<String, Buffer>,
+    options: Option<FlameGraphOptions>,
+) -> Result<String> {
+    let config = options
+        .map(FlameGraphConfig::try_from)
+        .transpose()?
+        .unwrap_or_default();
+    match call_stack {
+        Either::A(call_stack_json) => flame_graph::build_flame_graph_data(call_stack_json, &config),
+        // Binary call records, see `flame_graph::decode_call_records`
+        Either::B(records) => flame_graph::build_flame_graph_data_from_records(&records, &config),
+    }
+}
+
+/// Builds one aggregated flame graph from several recordings of the same
+/// flow, nodes counting the recordings they occur in as `runCount`
+#[napi]
+pub fn merge_call_stacks(
+    recordings_json: String,
+    options: Option<FlameGraphOptions>,
+) -> Result<String> {
+    let config = options
+        .map(FlameGraphConfig::try_from)
+        .transpose()?
+        .unwrap_or_default();
+    flame_graph::merge_call_stacks(recordings_json, &config)
+}
+
+/// Flame graph input streamed in chunks of binary call records
+///
+/// Long recordings can be sent as they are captured instead of as one
+/// giant string; the calls are kept in native memory until `finish`.
+#[napi]
+pub struct FlameGraphBuilder {
+    inner: Option<CallRecordCollector>,
+}
+
+#[napi]
+impl FlameGraphBuilder {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self {
+            inner: Some(CallRecordCollector::default()),
+        }
+    }
+
+    /// Adds a self-contained batch of binary call records
+    ///
+    /// # Returns
+    /// The number of calls in the batch
+    #[napi]
+    pub fn add_chunk(&mut self, records: Buffer) -> Result<u32> {
+        let collector = self.collector()?;
+        collector
+            .add_records(&records)
+            .map(|count| count as u32)
+            .map_err(|e| Error::from_reason(format!("Invalid call records: {}", e)))
+    }
+
+    /// Number of calls added so far
+    #[napi(getter)]
+    pub fn call_count(&self) -> u32 {
+        self.inner
+            .as_ref()
+            .map_or(0, |collector| collector.len() as u32)
+    }
+
+    /// Builds the flame graph from every chunk and releases the calls
+    ///
+    /// # Returns
+    /// JSON string containing flame graph data structure
+    #[napi]
+    pub fn finish(&mut self, options: Option<FlameGraphOptions>) -> Result<String> {
+        let collector = self.inner.take().ok_or_else(builder_finished)?;
+        let config = options
+            .map(FlameGraphConfig::try_from)
+            .transpose()?
+            .unwrap_or_default();
+        collector.build(&config)
+    }
+
+    fn collector(&mut self) -> Result<&mut CallRecordCollector> {
+        self.inner.as_mut().ok_or_else(builder_finished)
+    }
+}
+
+fn builder_finished() -> Error {
+    Error::from_reason("Flame graph builder already finished")
+}
+
+impl Default for FlameGraphBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[napi]
+pub fn export_folded_stacks(call_stack_json: String) -> Result<String> {
+    flame_graph::export_folded_stacks(call_stack_json)
+}
+
+#[napi]
+pub fn export_speedscope(call_stack_json: String) -> Result<String> {
+    flame_graph::export_speedscope(call_stack_json)
+}
+
+#[napi]
+pub fn export_trace_events(call_stack_json: String) -> Result<String> {
+    flame_graph::export_trace_events(call_stack_json)
+}
+
+#[napi]
+pub fn top_self_time(call_stack_json: String, limit: u32) -> Result<String> {
+    flame_graph::top_self_time(call_stack_json, limit)
+}
+
+#[napi(object)]
+pub struct FlameChartOptions {
+    /// Only include the calls, clipped, after this time (ms)
+    pub start_time: Option<f64>,
+    /// Only include the calls, clipped, before this time (ms)
+    pub end_time: Option<f64>,
+}
+
+/// Lays calls out on a timeline at their recorded times, overlapping root
+/// calls in separate lanes
+#[napi]
+pub fn build_flame_chart_data(
+    call_stack_json: String,
+    options: Option<FlameChartOptions>,
+) -> Result<String> {
+    let (start_time, end_time) = options.map_or((None, None), |options| {
+        (options.start_time, options.end_time)
+    });
+    flame_graph::build_flame_chart_data(call_stack_json, start_time, end_time)
+}
+
+#[napi(object)]
+pub struct SvgOptions {
+    /// Image width in pixels, 1200 by default
+    pub width: Option<u32>,
+    /// Frame height in pixels, 16 by default
+    pub frame_height: Option<u32>,
+    /// Heading drawn above the graph
+    pub title: Option<String>,
+}
+
+impl From<SvgOptions> for SvgConfig {
+    fn from(options: SvgOptions) -> Self {
+        let defaults = SvgConfig::default();
+        SvgConfig {
+            width: options.width.unwrap_or(defaults.width),
+            frame_height: options.frame_height.unwrap_or(defaults.frame_height),
+            title: options.title,
+        }
+    }
+}
+
+/// Renders call stacks as a standalone SVG flame graph, for reports built
+/// without a browser
+#[napi]
+pub fn render_flame_graph_svg(
+    call_stack_json: String,
+    options: Option<FlameGraphOptions>,
+    svg_options: Option<SvgOptions>,
+) -> Result<String> {
+    let config = options
+        .map(FlameGraphConfig::try_from)
+        .transpose()?
+        .unwrap_or_default();
+    let svg = svg_options.map(SvgConfig::from).unwrap_or_default();
+    flame_graph::render_flame_graph_svg(call_stack_json, &config, &svg)
 }
 
 #[napi]
@@ -0,0 +1,217 @@
+use crate::class_index::INDEX_DIR;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+const CACHE_FILE: &str = "matches.json";
+/// Bumped when the answers change meaning; older caches are discarded
+const CACHE_VERSION: u32 = 2;
+
+/// Cached "does this file declare class X" answers for a single file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedFile {
+    /// Modification time in nanoseconds since the Unix epoch
+    modified: u64,
+    len: u64,
+    /// FNV-1a hash of the content the answers were computed from
+    hash: u64,
+    answers: BTreeMap<String, bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedCache {
+    version: u32,
+    files: BTreeMap<String, CachedFile>,
+}
+
+/// Path → content hash → class match answers
+///
+/// Answers are reused without reading the file while its mtime and size are
+/// unchanged. When only the mtime moved (e.g. a checkout that rewrote the
+/// same bytes), the content hash lets the answers survive the re-read.
+#[derive(Debug, Default)]
+pub struct MatchCache {
+    files: HashMap<String, CachedFile>,
+    /// Workspaces whose persisted cache has been merged in
+    loaded: HashSet<PathBuf>,
+    dirty: bool,
+}
+
+impl MatchCache {
+    /// Get a cached answer, if the file is unchanged since it was recorded
+    pub fn lookup(&self, path: &str, modified: u64, len: u64, key: &str) -> Option<bool> {
+        self.files
+            .get(path)
+            .filter(|entry| entry.modified == modified && entry.len == len)
+            .and_then(|entry| entry.answers.get(key).copied())
+    }
+
+    /// Record an answer computed from content with the given hash
+    ///
+    /// Answers for other classes are kept if the content hash is unchanged.
+    pub fn record(
+        &mut self,
+        path: &str,
+        modified: u64,
+        len: u64,
+        hash: u64,
+        key: &str,
+        answer: bool,
+    ) {
+        let entry = self
+            .files
+            .entry(path.to_string())
+            .or_insert_with(|| CachedFile {
+                modified,
+                len,
+                hash,
+                answers: BTreeMap::new(),
+            });
+
+        if entry.hash != hash {
+            entry.answers.clear();
+            entry.hash = hash;
+        }
+        entry.modified = modified;
+        entry.len = len;
+        entry.answers.insert(key.to_string(), answer);
+        self.dirty = true;
+    }
+
+    /// Merge the cache persisted under `.xray/index`, once per workspace
+    ///
+    /// Entries already in memory win, since they are at least as fresh.
+    pub fn load_persisted(&mut self, workspace_path: &Path) {
+        if !self.loaded.insert(workspace_path.to_path_buf()) {
+            return;
+        }
+
+        let persisted = fs::read_to_string(cache_path(workspace_path))
+            .ok()
+            .and_then(|content| serde_json::from_str::<PersistedCache>(&content).ok())
+            .filter(|cache| cache.version == CACHE_VERSION);
+
+        if let Some(persisted) = persisted {
+            for (path, entry) in persisted.files {
+                self.files.entry(path).or_insert(entry);
+            }
+        }
+    }
+
+    /// Persist the entries under a workspace if anything changed
+    pub fn save_persisted(&mut self, workspace_path: &Path) -> io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let files = self
+            .files
+            .iter()
+            .filter(|(path, _)| Path::new(path.as_str()).starts_with(workspace_path))
+            .map(|(path, entry)| (path.clone(), entry.clone()))
+            .collect();
+        let persisted = PersistedCache {
+            version: CACHE_VERSION,
+            files,
+        };
+
+        let path = cache_path(workspace_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let json = serde_json::to_string(&persisted)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, json)?;
+        self.dirty = false;
+
+        Ok(())
+    }
+}
+
+/// Process-wide cache shared by all locators
+pub fn shared() -> MutexGuard<'static, MatchCache> {
+    static CACHE: OnceLock<Mutex<MatchCache>> = OnceLock::new();
+
+    // A panic while holding the lock leaves the cache usable
+    CACHE
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Initial state of [`fnv1a`]
+pub const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// 64-bit FNV-1a hash of file content
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    fnv1a(FNV_OFFSET, bytes)
+}
+
+/// Continue a 64-bit FNV-1a hash over more bytes
+///
+/// Stable across runs and platforms, unlike the standard hasher.
+pub fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+fn cache_path(workspace_path: &Path) -> PathBuf {
+    workspace_path.join(INDEX_DIR).join(CACHE_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_workspace;
+
+    #[test]
+    fn test_record_and_lookup() {
+        let mut cache = MatchCache::default();
+        let hash = content_hash(b"export class A {}");
+
+        cache.record("/ws/a.ts", 1, 17, hash, "A", true);
+        cache.record("/ws/a.ts", 1, 17, hash, "B", false);
+        assert_eq!(cache.lookup("/ws/a.ts", 1, 17, "A"), Some(true));
+        assert_eq!(cache.lookup("/ws/a.ts", 1, 17, "B"), Some(false));
+
+        // A touched file misses until re-read...
+        assert_eq!(cache.lookup("/ws/a.ts", 2, 17, "A"), None);
+
+        // ...and keeps its other answers if the content is the same
+        cache.record("/ws/a.ts", 2, 17, hash, "A", true);
+        assert_eq!(cache.lookup("/ws/a.ts", 2, 17, "B"), Some(false));
+
+        // Changed content drops stale answers
+        cache.record(
+            "/ws/a.ts",
+            3,
+            17,
+            content_hash(b"export class C {}"),
+            "C",
+            true,
+        );
+        assert_eq!(cache.lookup("/ws/a.ts", 3, 17, "A"), None);
+    }
+
+    #[test]
+    fn test_persist_roundtrip() {
+        let workspace = temp_workspace("matches");
+        let path = workspace.join("a.ts").to_string_lossy().to_string();
+
+        let mut cache = MatchCache::default();
+        cache.record(&path, 1, 2, 3, "A", true);
+        cache.save_persisted(&workspace).unwrap();
+
+        let mut reloaded = MatchCache::default();
+        reloaded.load_persisted(&workspace);
+        assert_eq!(reloaded.lookup(&path, 1, 2, "A"), Some(true));
+    }
+}
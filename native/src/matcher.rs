@@ -0,0 +1,109 @@
+use crate::class_index::is_ident_byte;
+use aho_corasick::AhoCorasick;
+use regex::Regex;
+
+/// Compiled matcher for `class <Name>` declarations of many classes at once
+///
+/// Every file is scanned in a single pass regardless of the number of
+/// targets. Like the single-class check, both ends of a match must sit on
+/// identifier boundaries, so `Foo` does not match `class FooBar`.
+pub struct ClassMatcher {
+    automaton: Option<AhoCorasick>,
+    targets: usize,
+}
+
+impl ClassMatcher {
+    pub fn new<S: AsRef<str>>(class_names: &[S]) -> Self {
+        let patterns: Vec<String> = class_names
+            .iter()
+            .map(|name| format!("class {}", name.as_ref()))
+            .collect();
+
+        Self {
+            // Only fails for absurdly large pattern sets; nothing matches then
+            automaton: AhoCorasick::new(&patterns).ok(),
+            targets: patterns.len(),
+        }
+    }
+
+    /// Flags, by target index, which classes the content may declare
+    pub fn matched(&self, content: &str) -> Vec<bool> {
+        let mut matched = vec![false; self.targets];
+
+        if let Some(automaton) = &self.automaton {
+            let bytes = content.as_bytes();
+            // Overlapping search so `class Foo` and `class FooBar` both report
+            for found in automaton.find_overlapping_iter(content) {
+                if on_ident_boundaries(bytes, found.start(), found.end()) {
+                    matched[found.pattern().as_usize()] = true;
+                }
+            }
+        }
+
+        matched
+    }
+}
+
+/// Check that `bytes[start..end]` is neither preceded nor followed by an
+/// identifier byte
+pub fn on_ident_boundaries(bytes: &[u8], start: usize, end: usize) -> bool {
+    let before = start.checked_sub(1).map(|i| bytes[i]);
+    let after = bytes.get(end).copied();
+    !before.is_some_and(is_ident_byte) && !after.is_some_and(is_ident_byte)
+}
+
+/// Compiled matcher for a `<keyword> <name>` declaration
+///
+/// Both ends must sit on identifier boundaries, so `interface Foo` does not
+/// match `interface FooBar`.
+pub struct DeclarationMatcher {
+    regex: Regex,
+}
+
+impl DeclarationMatcher {
+    pub fn new(keywords: &[&str], name: &str) -> Self {
+        let keywords: Vec<String> = keywords.iter().map(|k| regex::escape(k)).collect();
+        let pattern = format!(
+            r"(?:^|[^\p{{Alphabetic}}\p{{N}}_$])(?:{})\s+{}(?:[^\p{{Alphabetic}}\p{{N}}_$]|$)",
+            keywords.join("|"),
+            regex::escape(name)
+        );
+
+        Self {
+            // Every piece is escaped, so the pattern is always valid
+            regex: Regex::new(&pattern).expect("escaped declaration pattern"),
+        }
+    }
+
+    pub fn is_match(&self, content: &str) -> bool {
+        self.regex.is_match(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_class_matcher() {
+        let matcher = ClassMatcher::new(&["Foo", "FooBar", "Baz"]);
+        assert_eq!(
+            matcher.matched("export class FooBar {}"),
+            vec![false, true, false]
+        );
+        assert_eq!(
+            matcher.matched("class Foo {}\nclass FooBar {}"),
+            vec![true, true, false]
+        );
+        assert_eq!(matcher.matched("const Baz = 1;"), vec![false; 3]);
+    }
+
+    #[test]
+    fn test_declaration_matcher() {
+        let matcher = DeclarationMatcher::new(&["interface"], "User");
+        assert!(matcher.is_match("export interface User {}"));
+        assert!(matcher.is_match("interface\n  User<T> {}"));
+        assert!(!matcher.is_match("interface UserDto {}"));
+        assert!(!matcher.is_match("myinterface User {}"));
+    }
+}
@@ -0,0 +1,274 @@
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+use swc_ecma_ast::*;
+
+/// Key of the object standing in for a value that cannot be computed
+/// statically, e.g. `{"$expression": "ChangeDetectionStrategy.OnPush"}`
+pub const EXPRESSION_KEY: &str = "$expression";
+
+/// Nesting of constant references followed before giving up, which also
+/// breaks reference cycles
+const MAX_DEPTH: usize = 16;
+
+/// Evaluates decorator arguments to JSON, following the `const`
+/// declarations at the top level of the module
+pub struct StaticEvaluator<'a, F> {
+    constants: HashMap<&'a str, &'a Expr>,
+    /// Renders the source of an expression that cannot be evaluated
+    snippet: F,
+}
+
+impl<'a, F: Fn(&Expr) -> Option<String>> StaticEvaluator<'a, F> {
+    pub fn new(module: &'a Module, snippet: F) -> Self {
+        let constants = module
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) => Some(&**var),
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                    decl: Decl::Var(var),
+                    ..
+                })) => Some(&**var),
+                _ => None,
+            })
+            .filter(|var| var.kind == VarDeclKind::Const)
+            .flat_map(|var| &var.decls)
+            .filter_map(|decl| Some((decl.name.as_ident()?.sym.as_str(), decl.init.as_deref()?)))
+            .collect();
+
+        Self { constants, snippet }
+    }
+
+    /// Evaluate an expression to JSON
+    ///
+    /// Literals, arrays, objects (with spreads, shorthands and computed
+    /// keys), template literals, string concatenation and references to
+    /// constants are evaluated; anything else becomes an [`EXPRESSION_KEY`]
+    /// object holding its source.
+    pub fn evaluate(&self, expr: &Expr) -> Value {
+        self.value(expr, 0).unwrap_or_else(|| self.unresolved(expr))
+    }
+
+    fn value(&self, expr: &Expr, depth: usize) -> Option<Value> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        match expr {
+            Expr::Lit(Lit::Str(str_lit)) => {
+                Some(Value::String(str_lit.value.as_str()?.to_string()))
+            }
+            Expr::Lit(Lit::Num(num)) => number(num.value),
+            Expr::Lit(Lit::Bool(bool_lit)) => Some(Value::Bool(bool_lit.value)),
+            Expr::Lit(Lit::Null(_)) => Some(Value::Null),
+            Expr::Unary(UnaryExpr {
+                op: UnaryOp::Minus,
+                arg,
+                ..
+            }) => number(-self.value(arg, depth + 1)?.as_f64()?),
+            Expr::Tpl(tpl) => {
+                let mut text = String::new();
+                for (i, quasi) in tpl.quasis.iter().enumerate() {
+                    text.push_str(quasi.cooked.as_ref()?.as_str()?);
+                    if let Some(expr) = tpl.exprs.get(i) {
+                        text.push_str(&scalar_text(&self.value(expr, depth + 1)?)?);
+                    }
+                }
+                Some(Value::String(text))
+            }
+            Expr::Bin(BinExpr {
+                op: BinaryOp::Add,
+                left,
+                right,
+                ..
+            }) => {
+                let (left, right) = (self.value(left, depth + 1)?, self.value(right, depth + 1)?);
+                match (&left, &right) {
+                    (Value::Number(a), Value::Number(b)) => number(a.as_f64()? + b.as_f64()?),
+                    (Value::String(_), _) | (_, Value::String(_)) => Some(Value::String(
+                        scalar_text(&left)? + scalar_text(&right)?.as_str(),
+                    )),
+                    _ => None,
+                }
+            }
+            Expr::Array(array) => {
+                let mut values = Vec::new();
+                for elem in array.elems.iter().flatten() {
+                    match (&elem.spread, self.value(&elem.expr, depth + 1)) {
+                        (Some(_), Some(Value::Array(spread))) => values.extend(spread),
+                        (Some(_), _) => values.push(self.unresolved(&elem.expr)),
+                        (None, value) => {
+                            values.push(value.unwrap_or_else(|| self.unresolved(&elem.expr)))
+                        }
+                    }
+                }
+                Some(Value::Array(values))
+            }
+            Expr::Object(object) => {
+                let mut values = Map::new();
+                for prop in &object.props {
+                    match prop {
+                        PropOrSpread::Spread(spread) => {
+                            if let Some(Value::Object(spread)) = self.value(&spread.expr, depth + 1)
+                            {
+                                values.extend(spread);
+                            }
+                        }
+                        PropOrSpread::Prop(prop) => {
+                            let (key, value) = match &**prop {
+                                Prop::KeyValue(key_value) => {
+                                    // Computed keys that cannot be evaluated are dropped
+                                    let Some(key) = self.key(&key_value.key, depth) else {
+                                        continue;
+                                    };
+                                    (key, &*key_value.value)
+                                }
+                                Prop::Shorthand(ident) => {
+                                    let value = self
+                                        .constants
+                                        .get(ident.sym.as_str())
+                                        .and_then(|value| self.value(value, depth + 1))
+                                        .unwrap_or_else(|| {
+                                            self.unresolved(&Expr::Ident(ident.clone()))
+                                        });
+                                    values.insert(ident.sym.to_string(), value);
+                                    continue;
+                                }
+                                // Methods and accessors have no static value
+                                _ => continue,
+                            };
+                            let value = self
+                                .value(value, depth + 1)
+                                .unwrap_or_else(|| self.unresolved(value));
+                            values.insert(key, value);
+                        }
+                    }
+                }
+                Some(Value::Object(values))
+            }
+            Expr::Ident(ident) => self.value(self.constants.get(ident.sym.as_str())?, depth + 1),
+            Expr::Member(member) => {
+                let object = self.value(&member.obj, depth + 1)?;
+                let key = match &member.prop {
+                    MemberProp::Ident(prop) => prop.sym.to_string(),
+                    MemberProp::Computed(computed) => {
+                        scalar_text(&self.value(&computed.expr, depth + 1)?)?
+                    }
+                    MemberProp::PrivateName(_) => return None,
+                };
+                match object {
+                    Value::Object(mut object) => object.remove(&key),
+                    Value::Array(array) => array.into_iter().nth(key.parse().ok()?),
+                    _ => None,
+                }
+            }
+            Expr::Paren(paren) => self.value(&paren.expr, depth),
+            Expr::TsAs(as_expr) => self.value(&as_expr.expr, depth),
+            Expr::TsConstAssertion(assertion) => self.value(&assertion.expr, depth),
+            Expr::TsSatisfies(satisfies) => self.value(&satisfies.expr, depth),
+            _ => None,
+        }
+    }
+
+    /// Static text of an object key; `None` for computed keys that cannot be
+    /// evaluated
+    fn key(&self, key: &PropName, depth: usize) -> Option<String> {
+        match key {
+            PropName::Ident(ident) => Some(ident.sym.to_string()),
+            PropName::Str(str_lit) => str_lit.value.as_str().map(str::to_string),
+            PropName::Num(num) => Some(num.value.to_string()),
+            PropName::Computed(computed) => scalar_text(&self.value(&computed.expr, depth + 1)?),
+            PropName::BigInt(big_int) => Some(big_int.value.to_string()),
+        }
+    }
+
+    fn unresolved(&self, expr: &Expr) -> Value {
+        let mut object = Map::new();
+        object.insert(
+            EXPRESSION_KEY.to_string(),
+            (self.snippet)(expr).map_or(Value::Null, Value::String),
+        );
+        Value::Object(object)
+    }
+}
+
+/// A JSON number, integral when possible
+fn number(value: f64) -> Option<Value> {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        return Some(Value::Number((value as i64).into()));
+    }
+    Number::from_f64(value).map(Value::Number)
+}
+
+/// Text of a string, number or boolean, as JavaScript would concatenate it
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::TypeScriptParser;
+    use serde_json::json;
+
+    #[test]
+    fn test_find_decorator_metadata() {
+        let parser = TypeScriptParser::new();
+        let content = r#"
+const PREFIX = 'app';
+const HOST_KEY = 'class';
+export const SHARED = { standalone: true, providers: [UserService] } as const;
+const SIZES = [1, 2];
+
+@Component({
+    ...SHARED,
+    selector: `${PREFIX}-user`,
+    templateUrl: './' + PREFIX + '.html',
+    host: { [HOST_KEY]: 'card', ['data-' + 'id']: `${-1}` },
+    sizes: [...SIZES, 3, 1.5],
+    changeDetection: ChangeDetectionStrategy.OnPush,
+    prefix: PREFIX,
+    [dynamicKey()]: true,
+})
+export class UserComponent {}
+
+@Injectable()
+export class UserService {}
+"#;
+
+        let metadata = parser
+            .find_decorator_metadata(content, "UserComponent", "Component")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            metadata,
+            json!({
+                "standalone": true,
+                "providers": [{ "$expression": "UserService" }],
+                "selector": "app-user",
+                "templateUrl": "./app.html",
+                "host": { "class": "card", "data-id": "-1" },
+                "sizes": [1, 2, 3, 1.5],
+                "changeDetection": { "$expression": "ChangeDetectionStrategy.OnPush" },
+                "prefix": "app",
+            })
+        );
+
+        assert_eq!(
+            parser
+                .find_decorator_metadata(content, "UserService", "Injectable")
+                .unwrap(),
+            Some(json!(null))
+        );
+        assert_eq!(
+            parser
+                .find_decorator_metadata(content, "UserService", "Component")
+                .unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use ignore::WalkBuilder;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Angular Package Format bundle directories, newest first
+const FESM_DIRS: &[&str] = &["fesm2022", "fesm2020", "fesm2015"];
+
+/// A package installed under `node_modules`
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledPackage {
+    pub root: PathBuf,
+    /// Entry file named by `typings` / `types` in `package.json`
+    pub typings: Option<PathBuf>,
+}
+
+impl InstalledPackage {
+    /// Type declarations shipped with the package
+    ///
+    /// Covers every `.d.ts` next to or below the typings entry, since Angular
+    /// libraries often split declarations across files.
+    pub fn declaration_files(&self) -> Vec<PathBuf> {
+        let Some(typings) = &self.typings else {
+            return Vec::new();
+        };
+        let directory = typings.parent().unwrap_or(&self.root);
+
+        let mut files: Vec<PathBuf> = WalkBuilder::new(directory)
+            .standard_filters(false)
+            .filter_entry(|e| e.file_name() != "node_modules")
+            .build()
+            .filter_map(Result::ok)
+            .map(|entry| entry.into_path())
+            .filter(|path| path.to_string_lossy().ends_with(".d.ts"))
+            .collect();
+
+        // The entry file first; single-file typings declare everything there
+        files.sort_by_key(|path| path != typings);
+        files
+    }
+
+    /// Flat ES module bundles (`fesm2022/*.mjs`)
+    pub fn bundle_files(&self) -> Vec<PathBuf> {
+        let Some(directory) = FESM_DIRS
+            .iter()
+            .map(|dir| self.root.join(dir))
+            .find(|dir| dir.is_dir())
+        else {
+            return Vec::new();
+        };
+
+        let mut files: Vec<PathBuf> = fs::read_dir(directory)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "mjs"))
+            .collect();
+        files.sort();
+        files
+    }
+}
+
+/// List the top-level packages of a workspace's `node_modules`, including
+/// scoped packages (`@scope/name`), in name order
+pub fn installed_packages(workspace_path: &Path) -> Vec<InstalledPackage> {
+    let mut roots = Vec::new();
+
+    for entry in sorted_dirs(&workspace_path.join("node_modules")) {
+        let name = entry.file_name().map(|n| n.to_string_lossy().to_string());
+        match name.as_deref() {
+            Some(name) if name.starts_with('@') => roots.extend(sorted_dirs(&entry)),
+            Some(name) if name.starts_with('.') => {}
+            _ => roots.push(entry),
+        }
+    }
+
+    roots
+        .into_iter()
+        .map(|root| {
+            let typings = fs::read_to_string(root.join("package.json"))
+                .ok()
+                .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+                .and_then(|manifest| {
+                    manifest
+                        .get("typings")
+                        .or_else(|| manifest.get("types"))
+                        .and_then(|value| value.as_str())
+                        .map(|entry| root.join(entry))
+                });
+            InstalledPackage { root, typings }
+        })
+        .collect()
+}
+
+fn sorted_dirs(directory: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_workspace;
+
+    #[test]
+    fn test_installed_packages() {
+        let workspace = temp_workspace("packages");
+        let material = workspace.join("node_modules/@angular/material");
+        fs::create_dir_all(material.join("button")).unwrap();
+        fs::create_dir_all(material.join("fesm2022")).unwrap();
+        fs::write(
+            material.join("package.json"),
+            r#"{ "name": "@angular/material", "typings": "./index.d.ts" }"#,
+        )
+        .unwrap();
+        fs::write(material.join("index.d.ts"), "export * from './button';").unwrap();
+        fs::write(
+            material.join("button/index.d.ts"),
+            "export declare class MatButton {}",
+        )
+        .unwrap();
+        fs::write(material.join("fesm2022/button.mjs"), "class MatButton {}").unwrap();
+        fs::create_dir_all(workspace.join("node_modules/.cache")).unwrap();
+
+        let packages = installed_packages(&workspace);
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].root, material);
+
+        let declarations = packages[0].declaration_files();
+        assert_eq!(declarations.len(), 2);
+        assert_eq!(declarations[0], material.join("index.d.ts"));
+        assert_eq!(
+            packages[0].bundle_files(),
+            vec![material.join("fesm2022/button.mjs")]
+        );
+    }
+}
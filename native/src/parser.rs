@@ -1,7 +1,426 @@
+use swc_common::comments::{Comments, SingleThreadedComments};
 use swc_common::sync::Lrc;
-use swc_common::{FileName, SourceMap};
+use swc_common::{FileName, SourceMap, SourceMapper, Spanned};
 use swc_ecma_ast::*;
-use swc_ecma_parser::{Parser, StringInput, Syntax, TsSyntax};
+use swc_ecma_codegen::text_writer::{omit_trailing_semi, JsWriter};
+use swc_ecma_codegen::{self as codegen, Emitter, Node};
+use swc_ecma_parser::{EsSyntax, Parser, StringInput, Syntax, TsSyntax};
+
+use crate::match_cache::content_hash;
+use crate::metadata::StaticEvaluator;
+use crate::walk::{walk_body, FunctionBody, Visitor};
+use std::collections::HashMap;
+
+/// Position of a declaration within a file
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeclarationSpan {
+    /// 1-based line of the declaration start
+    pub line: u32,
+    /// 1-based column of the declaration start
+    pub column: u32,
+    /// Byte offset of the declaration start
+    pub start: u32,
+    /// Byte offset just past the declaration end
+    pub end: u32,
+    /// 1-based line of the declaration end
+    pub end_line: u32,
+    /// 1-based column just past the declaration end
+    pub end_column: u32,
+}
+
+impl DeclarationSpan {
+    /// Check whether a 1-based position falls inside the declaration
+    pub fn contains(&self, line: u32, column: u32) -> bool {
+        (self.line, self.column) <= (line, column)
+            && (line, column) < (self.end_line, self.end_column)
+    }
+}
+
+/// What a position of a file belongs to, from the class down to the
+/// identifier under it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionContext {
+    /// Innermost class containing the position
+    pub class_name: String,
+    /// Member containing the position, if any
+    pub member_name: Option<String>,
+    /// Kind of the member; `None` for plain properties
+    pub member_kind: Option<MemberKind>,
+    /// Synthetic name of the innermost anonymous callback containing the
+    /// position, e.g. `UserService.load$.subscribe#2`
+    pub callback: Option<String>,
+    /// Identifier under the position, if any
+    pub identifier: Option<String>,
+}
+
+/// A re-export of another module, as found in barrel files
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReExport {
+    /// `export * from './foo'`
+    All { source: String },
+    /// `export { Foo, Bar as Baz } from './foo'`, as (exported, original) names
+    Named {
+        source: String,
+        names: Vec<(String, String)>,
+    },
+}
+
+/// An import declaration and the names it brings in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub source: String,
+    /// Imported names; default imports use their local name
+    pub names: Vec<String>,
+}
+
+/// The base class and interfaces of a class declaration
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ClassHeritage {
+    /// The `extends` expression without type arguments, e.g. `BaseComponent`
+    /// or `ns.Base`
+    pub super_class: Option<String>,
+    /// The `implements` clauses without type arguments, in source order
+    pub interfaces: Vec<String>,
+}
+
+/// An exported class and the shape that matters for instrumentation
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassSummary {
+    pub name: String,
+    /// Decorator names without `@`, in source order
+    pub decorators: Vec<String>,
+    /// Methods declared in the class body, excluding the constructor and
+    /// accessors
+    pub method_count: usize,
+}
+
+/// Kind of a class member found by name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemberKind {
+    Method,
+    Getter,
+    Setter,
+    Constructor,
+}
+
+impl MemberKind {
+    /// Parse the kind name used across the FFI boundary
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "method" => Some(Self::Method),
+            "getter" => Some(Self::Getter),
+            "setter" => Some(Self::Setter),
+            "constructor" => Some(Self::Constructor),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Method => "method",
+            Self::Getter => "getter",
+            Self::Setter => "setter",
+            Self::Constructor => "constructor",
+        }
+    }
+
+    fn of(kind: MethodKind) -> Self {
+        match kind {
+            MethodKind::Method => Self::Method,
+            MethodKind::Getter => Self::Getter,
+            MethodKind::Setter => Self::Setter,
+        }
+    }
+}
+
+/// A method-like member of a class, for choosing what to instrument
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodSummary {
+    pub name: String,
+    pub kind: MemberKind,
+    pub span: DeclarationSpan,
+    pub is_static: bool,
+    /// `public`, `protected` or `private`; `#name` members are `private`
+    pub visibility: &'static str,
+    pub is_async: bool,
+}
+
+/// Direction of a component binding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingDirection {
+    Input,
+    Output,
+    /// Two-way `model()` signal, both an input and an output
+    Model,
+}
+
+impl BindingDirection {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Input => "input",
+            Self::Output => "output",
+            Self::Model => "model",
+        }
+    }
+}
+
+/// An `@Input()` / `@Output()` property or signal-based `input()` /
+/// `output()` / `model()` of a component
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentBinding {
+    /// Class property name
+    pub name: String,
+    /// Public name, when aliased
+    pub alias: Option<String>,
+    pub direction: BindingDirection,
+    /// 1-based line of the property
+    pub line: u32,
+    /// Declared type as written: the annotation, or the type argument of
+    /// `EventEmitter<T>` / `input<T>()`
+    pub type_text: Option<String>,
+    pub required: bool,
+}
+
+/// A symbol listed in the `imports` array of a standalone component
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentImport {
+    /// Symbol as written, e.g. `MatButtonModule` or `Shared.CardModule`
+    pub symbol: String,
+    /// Module the symbol is imported from, e.g. `@angular/material/button`
+    pub source: Option<String>,
+    /// The symbol is a list of imports itself: spread into the array
+    /// (`...SHARED_IMPORTS`) or used as the whole array
+    pub spread: bool,
+    /// 1-based line of the symbol
+    pub line: u32,
+    /// 1-based column of the symbol
+    pub column: u32,
+}
+
+/// A component and the change detection strategy of its decorator
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentChangeDetection {
+    pub class_name: String,
+    /// Strategy name as written, e.g. `OnPush` for
+    /// `ChangeDetectionStrategy.OnPush`; `None` when not set (`Default`)
+    pub strategy: Option<String>,
+    pub span: DeclarationSpan,
+}
+
+/// A write to component state, e.g. `this.items.push(item)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateMutation {
+    /// The class member written to, e.g. `items`
+    pub member: String,
+    /// 1-based line of the write
+    pub line: u32,
+}
+
+/// A `subscribe()` call whose subscription is never released
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnmanagedSubscription {
+    pub class_name: String,
+    /// Method or property containing the call
+    pub member_name: String,
+    /// The subscribed observable with call arguments elided, e.g.
+    /// `this.store.select().pipe()`
+    pub source: String,
+    /// 1-based line of the call
+    pub line: u32,
+    /// 1-based column of the call
+    pub column: u32,
+}
+
+/// Operators that end a subscription by themselves
+const BOUNDING_OPERATORS: &[&str] = &[
+    "takeUntil",
+    "takeUntilDestroyed",
+    "untilDestroyed",
+    "take",
+    "first",
+];
+
+/// Methods of arrays, maps and sets that change them in place
+const MUTATING_METHODS: &[&str] = &[
+    "push",
+    "pop",
+    "shift",
+    "unshift",
+    "splice",
+    "sort",
+    "reverse",
+    "fill",
+    "copyWithin",
+    "set",
+    "add",
+    "delete",
+    "clear",
+];
+
+/// A method found by name, with the location of its overload signatures
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberLocation {
+    pub span: DeclarationSpan,
+    pub kind: MemberKind,
+    /// 1-based lines of the overload signatures preceding the implementation
+    pub overload_lines: Vec<u32>,
+    /// Decorators applied to the member, in source order
+    pub decorators: Vec<MemberDecorator>,
+    /// Text of the leading `/** */` comment, without the comment markers
+    pub doc_comment: Option<String>,
+}
+
+/// A decorator applied to a class member, e.g. `@Debounce(300)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberDecorator {
+    /// Decorator name without arguments, e.g. `Debounce`
+    pub name: String,
+    /// Source text of the decorator, including `@` and arguments
+    pub text: String,
+    /// 1-based line of the decorator
+    pub line: u32,
+}
+
+/// Byte offsets of a method and its body, for rewriting its source
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodBodySpan {
+    pub kind: MemberKind,
+    /// Start of the member, including its decorators
+    pub member_start: u32,
+    /// Offset of the body's `{`, or of the expression of a concise arrow
+    pub body_start: u32,
+    /// Offset just past the body's `}` or expression
+    pub body_end: u32,
+    /// The body is the expression of a concise arrow (`load = () => x`)
+    pub expression_body: bool,
+}
+
+/// A syntax error found while parsing in tolerant mode
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseDiagnostic {
+    pub message: String,
+    /// 1-based line of the error
+    pub line: u32,
+    /// 1-based column of the error
+    pub column: u32,
+}
+
+/// Comment opting a method out of profiling, e.g. `// @xray:ignore polled`
+const IGNORE_MARKER: &str = "@xray:ignore";
+
+/// A method annotated with an [`IGNORE_MARKER`] comment
+#[derive(Debug, Clone, PartialEq)]
+pub struct IgnoredMethod {
+    pub class_name: String,
+    pub method_name: String,
+    /// 1-based line of the method
+    pub line: u32,
+    /// Text following the marker, e.g. `polled every frame`
+    pub reason: Option<String>,
+}
+
+/// Repairs attempted by [`TypeScriptParser::parse_module_tolerant`] before
+/// giving up on a file
+const MAX_RECOVERY_ATTEMPTS: usize = 8;
+
+/// A call made from a method body, e.g. `this.userService.load()`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodCall {
+    /// Callee path with call arguments elided, e.g. `this.http.get().pipe`
+    pub callee: String,
+    /// Name of the called function or method, e.g. `pipe`
+    pub name: String,
+    /// 1-based line of the call
+    pub line: u32,
+    /// 1-based column of the call
+    pub column: u32,
+}
+
+/// An arrow or function expression passed as an argument inside a class
+/// member, e.g. the callback of `this.load$.subscribe(user => ...)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnonymousCallback {
+    /// Stable synthetic name, e.g. `UserService.load$.subscribe#2`
+    pub name: String,
+    pub class_name: String,
+    /// Method or property whose body contains the callback
+    pub member_name: String,
+    /// Name of the function receiving the callback, e.g. `subscribe`
+    pub callee: String,
+    /// 1-based rank among the callbacks passed to `callee` in the member
+    pub index: u32,
+    pub span: DeclarationSpan,
+}
+
+/// An RxJS `pipe(...)` call, e.g. `this.http.get(url).pipe(map(...))`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipeChain {
+    /// The piped observable with call arguments elided, e.g. `this.http.get()`
+    pub source: String,
+    pub operators: Vec<PipeOperator>,
+    /// 1-based line of the `pipe` call
+    pub line: u32,
+    /// 1-based column of the `pipe` call
+    pub column: u32,
+}
+
+/// An operator passed to `pipe`, e.g. `debounceTime(300)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipeOperator {
+    /// Operator name, e.g. `debounceTime`; custom operators passed without a
+    /// call (`pipe(untilDestroyed)`) use their path
+    pub name: String,
+    /// Number of arguments given to the operator
+    pub arity: u32,
+    /// 1-based line of the operator
+    pub line: u32,
+}
+
+/// Size and complexity of a method body
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MethodMetrics {
+    /// 1 plus the number of branch points: conditionals, loops, `case`
+    /// clauses, `catch` clauses and short-circuit operators
+    pub cyclomatic_complexity: u32,
+    /// Statements in the body, not counting blocks and empty statements
+    pub statement_count: u32,
+    /// Deepest nesting of control-flow statements
+    pub max_nesting_depth: u32,
+    /// Lines spanned by the method, including its signature
+    pub line_count: u32,
+}
+
+/// Raw match of [`TypeScriptParser::find_method_in_class`]
+struct MethodMatch<'a> {
+    span: swc_common::Span,
+    kind: MemberKind,
+    overloads: Vec<swc_common::Span>,
+    decorators: &'a [Decorator],
+    /// `None` for an overload signature
+    body: Option<FunctionBody<'a>>,
+}
+
+/// Angular lifecycle hook methods and their interfaces, in call order
+const LIFECYCLE_HOOKS: &[(&str, &str)] = &[
+    ("ngOnChanges", "OnChanges"),
+    ("ngOnInit", "OnInit"),
+    ("ngDoCheck", "DoCheck"),
+    ("ngAfterContentInit", "AfterContentInit"),
+    ("ngAfterContentChecked", "AfterContentChecked"),
+    ("ngAfterViewInit", "AfterViewInit"),
+    ("ngAfterViewChecked", "AfterViewChecked"),
+    ("ngOnDestroy", "OnDestroy"),
+];
+
+/// An Angular lifecycle hook implemented by a class
+#[derive(Debug, Clone, PartialEq)]
+pub struct LifecycleHook {
+    /// Hook method name, e.g. `ngOnInit`
+    pub name: &'static str,
+    pub span: DeclarationSpan,
+    /// The class also lists the hook interface in `implements`
+    pub declares_interface: bool,
+}
 
 pub struct TypeScriptParser {
     source_map: Lrc<SourceMap>,
@@ -14,150 +433,3925 @@ impl TypeScriptParser {
         }
     }
 
-    /// Find the line number where a method is defined in TypeScript code
-    pub fn find_method_line(
+    /// Find the span and kind of a method, getter, setter or constructor by name
+    ///
+    /// With `kind`, only members of that kind match, so `get value()` and
+    /// `set value(v)` can be told apart; otherwise the first member wins.
+    /// With `class_name`, only that class is searched, for files declaring
+    /// several classes. Overloaded methods resolve to the implementation.
+    pub fn find_member(
         &self,
         file_content: &str,
-        method_name: &str,
-    ) -> Result<Option<u32>, String> {
+        member_name: &str,
+        kind: Option<MemberKind>,
+        class_name: Option<&str>,
+    ) -> Result<Option<MemberLocation>, String> {
+        let module = self.parse_module(file_content)?;
+
+        // Search for the method in the AST
+        Ok(self.member_location(&module, member_name, kind, class_name))
+    }
+
+    /// Like [`Self::find_member`], but tolerates syntax errors
+    ///
+    /// The member is searched in the partial AST recovered by
+    /// [`Self::parse_module_tolerant`]; the syntax errors are returned
+    /// alongside instead of failing the lookup.
+    pub fn find_member_tolerant(
+        &self,
+        file_content: &str,
+        member_name: &str,
+        kind: Option<MemberKind>,
+        class_name: Option<&str>,
+    ) -> (Option<MemberLocation>, Vec<ParseDiagnostic>) {
+        let (module, diagnostics) = self.parse_module_tolerant(file_content);
+        let member =
+            module.and_then(|module| self.member_location(&module, member_name, kind, class_name));
+
+        (member, diagnostics)
+    }
+
+    /// Find several methods by name in a single parse
+    ///
+    /// # Returns
+    /// One entry per name, in the order given
+    pub fn find_members(
+        &self,
+        file_content: &str,
+        member_names: &[String],
+    ) -> Result<Vec<Option<MemberLocation>>, String> {
+        let module = self.parse_module(file_content)?;
+
+        Ok(member_names
+            .iter()
+            .map(|name| self.member_location(&module, name, None, None))
+            .collect())
+    }
+
+    /// Find the byte offsets needed to rewrite the bodies of several methods
+    /// of a class, in a single parse
+    ///
+    /// # Returns
+    /// One entry per name, in the order given; `None` for members that are
+    /// not declared or have no body
+    pub fn find_method_bodies(
+        &self,
+        file_content: &str,
+        class_name: &str,
+        method_names: &[String],
+    ) -> Result<Vec<Option<MethodBodySpan>>, String> {
+        let module = self.parse_module(file_content)?;
+
+        Ok(method_names
+            .iter()
+            .map(|name| {
+                let found = self.find_method_in_module(&module, name, None, Some(class_name))?;
+                let (body_span, expression_body) = match found.body? {
+                    FunctionBody::Block(block) => (block.span, false),
+                    FunctionBody::Expr(expr) => (expr.span(), true),
+                };
+                let member_start = found
+                    .decorators
+                    .iter()
+                    .map(|decorator| decorator.span.lo)
+                    .fold(found.span.lo, |start, lo| start.min(lo));
+                let offset = |pos| self.source_map.lookup_byte_offset(pos).pos.0;
+
+                Some(MethodBodySpan {
+                    kind: found.kind,
+                    member_start: offset(member_start),
+                    body_start: offset(body_span.lo),
+                    body_end: offset(body_span.hi),
+                    expression_body,
+                })
+            })
+            .collect())
+    }
+
+    fn member_location(
+        &self,
+        module: &Module,
+        member_name: &str,
+        kind: Option<MemberKind>,
+        class_name: Option<&str>,
+    ) -> Option<MemberLocation> {
+        self.find_method_in_module(module, member_name, kind, class_name)
+            .map(|found| MemberLocation {
+                doc_comment: self.doc_comment(&found),
+                span: self.declaration_span(found.span),
+                kind: found.kind,
+                overload_lines: found
+                    .overloads
+                    .into_iter()
+                    .map(|span| self.declaration_span(span).line)
+                    .collect(),
+                decorators: found
+                    .decorators
+                    .iter()
+                    .filter_map(|decorator| self.member_decorator(decorator))
+                    .collect(),
+            })
+    }
+
+    /// Text of the `/** */` comment right before a member, its decorators
+    /// or its first overload signature
+    fn doc_comment(&self, found: &MethodMatch) -> Option<String> {
+        let start = found
+            .overloads
+            .iter()
+            .chain(found.decorators.iter().map(|decorator| &decorator.span))
+            .map(|span| span.lo)
+            .fold(found.span.lo, |start, lo| start.min(lo));
+
+        let location = self.source_map.lookup_byte_offset(start);
+        let before = location.sf.src.get(..location.pos.0 as usize)?.trim_end();
+        let body = before.strip_suffix("*/")?;
+        let text = &body[body.rfind("/**")? + 3..];
+        if text.contains("*/") {
+            // The closest comment is a plain `/* */` one
+            return None;
+        }
+
+        let lines: Vec<&str> = text
+            .lines()
+            .map(|line| {
+                let line = line.trim();
+                let line = line.strip_prefix('*').unwrap_or(line);
+                line.strip_prefix(' ').unwrap_or(line).trim_end()
+            })
+            .collect();
+        Some(lines.join("\n").trim().to_string())
+    }
+
+    fn member_decorator(&self, decorator: &Decorator) -> Option<MemberDecorator> {
+        Some(MemberDecorator {
+            name: decorator_name(decorator)?,
+            text: self.snippet(decorator.span)?,
+            line: self.declaration_span(decorator.span).line,
+        })
+    }
+
+    /// Parse TypeScript code into a module AST
+    ///
+    /// TSX is tried first. Code it rejects is retried as plain TypeScript
+    /// (`<T>value` assertions are not valid TSX) and then as JavaScript, so
+    /// `.js` codebases and transpiled sources still resolve. The TSX error
+    /// is reported if every attempt fails.
+    pub(crate) fn parse_module(&self, file_content: &str) -> Result<Module, String> {
+        self.parse_source(file_content, None)
+            .map(|(module, _)| module)
+            .map_err(|e| format!("Parse error: {:?}", e))
+    }
+
+    /// Parse code into a module AST, with the errors the parser recovered from
+    ///
+    /// Comments are collected into `comments` when given.
+    fn parse_source(
+        &self,
+        file_content: &str,
+        comments: Option<&SingleThreadedComments>,
+    ) -> Result<(Module, Vec<swc_ecma_parser::error::Error>), swc_ecma_parser::error::Error> {
         // Create a source file
         let source_file = self
             .source_map
             .new_source_file(Lrc::new(FileName::Anon), file_content.to_string());
 
-        // Configure TypeScript parser
-        let syntax = Syntax::Typescript(TsSyntax {
-            tsx: true,
-            decorators: true,
-            ..Default::default()
-        });
-
-        // Create parser
-        let input = StringInput::from(&*source_file);
-        let mut parser = Parser::new(syntax, input, None);
+        let syntaxes = [
+            Syntax::Typescript(TsSyntax {
+                tsx: true,
+                decorators: true,
+                ..Default::default()
+            }),
+            Syntax::Typescript(TsSyntax {
+                decorators: true,
+                ..Default::default()
+            }),
+            Syntax::Es(EsSyntax {
+                jsx: true,
+                decorators: true,
+                decorators_before_export: true,
+                ..Default::default()
+            }),
+        ];
 
-        // Parse the file
-        let module = parser
-            .parse_module()
-            .map_err(|e| format!("Parse error: {:?}", e))?;
+        let mut first_error = None;
+        for syntax in syntaxes {
+            // Every attempt reads the same source file, so spans stay valid
+            let input = StringInput::from(&*source_file);
+            let mut parser = Parser::new(syntax, input, comments.map(|c| c as &dyn Comments));
 
-        // Search for the method in the AST
-        let line = self.find_method_in_module(&module, method_name);
+            match parser.parse_module() {
+                Ok(module) => return Ok((module, parser.take_errors())),
+                Err(e) => {
+                    if let Some(comments) = comments {
+                        // Drop what the failed attempt collected
+                        let (mut leading, mut trailing) = comments.borrow_all_mut();
+                        leading.clear();
+                        trailing.clear();
+                    }
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
 
-        Ok(line)
+        Err(first_error.expect("at least one syntax is tried"))
     }
 
-    /// Search for a method in the module's AST
-    fn find_method_in_module(&self, module: &Module, method_name: &str) -> Option<u32> {
-        for item in &module.body {
-            if let ModuleItem::Stmt(stmt) = item {
-                if let Some(line) = self.find_method_in_stmt(stmt, method_name) {
-                    return Some(line);
+    /// Parse code that may contain syntax errors, e.g. a file mid-edit
+    ///
+    /// When parsing fails, the line being edited is blanked out and the
+    /// parse retried, up to [`MAX_RECOVERY_ATTEMPTS`] times: the nearest
+    /// line at or above the error with unbalanced parentheses or brackets,
+    /// else the error line itself. Errors at the end of the file get a
+    /// missing closing brace appended instead. Line numbers
+    /// are preserved, so locations in the partial AST stay valid.
+    ///
+    /// # Returns
+    /// The partial module, if any attempt succeeded, and every syntax error
+    /// encountered along the way
+    fn parse_module_tolerant(&self, file_content: &str) -> (Option<Module>, Vec<ParseDiagnostic>) {
+        let mut source = file_content.to_string();
+        let mut diagnostics = Vec::new();
+
+        for _ in 0..=MAX_RECOVERY_ATTEMPTS {
+            match self.parse_source(&source, None) {
+                Ok((module, errors)) => {
+                    diagnostics.extend(errors.iter().map(|e| self.diagnostic(e)));
+                    return (Some(module), diagnostics);
                 }
-            } else if let ModuleItem::ModuleDecl(decl) = item {
-                if let Some(line) = self.find_method_in_module_decl(decl, method_name) {
-                    return Some(line);
+                Err(e) => {
+                    let at_end = self.source_map.lookup_byte_offset(e.span().lo).pos.0 as usize
+                        >= source.trim_end().len();
+                    let diagnostic = self.diagnostic(&e);
+                    let line = edited_line(&source, diagnostic.line);
+                    diagnostics.push(diagnostic);
+
+                    if at_end {
+                        source.push_str("\n}");
+                    } else if !blank_line(&mut source, line) {
+                        break;
+                    }
                 }
             }
         }
-        None
+
+        (None, diagnostics)
     }
 
-    /// Search for a method in a statement
-    fn find_method_in_stmt(&self, stmt: &Stmt, method_name: &str) -> Option<u32> {
-        match stmt {
-            Stmt::Decl(Decl::Class(class_decl)) => {
-                self.find_method_in_class(&class_decl.class, method_name)
+    fn diagnostic(&self, error: &swc_ecma_parser::error::Error) -> ParseDiagnostic {
+        let span = self.declaration_span(error.span());
+        ParseDiagnostic {
+            message: error.kind().msg().to_string(),
+            line: span.line,
+            column: span.column,
+        }
+    }
+
+    /// Check whether the code contains a real declaration of the class
+    ///
+    /// Unlike textual matching, names inside comments or string literals
+    /// are not counted.
+    pub fn has_class_declaration(
+        &self,
+        file_content: &str,
+        class_name: &str,
+    ) -> Result<bool, String> {
+        let module = self.parse_module(file_content)?;
+
+        let found = top_level_classes(&module)
+            .iter()
+            .any(|(name, _)| *name == Some(class_name));
+
+        Ok(found)
+    }
+
+    /// Find the position of a class declaration and what it extends and
+    /// implements
+    pub fn describe_class(
+        &self,
+        file_content: &str,
+        class_name: &str,
+    ) -> Result<Option<(DeclarationSpan, ClassHeritage)>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let declaration = top_level_classes(&module)
+            .into_iter()
+            .find(|(name, _)| *name == Some(class_name))
+            .map(|(_, class)| {
+                let heritage = ClassHeritage {
+                    super_class: class
+                        .super_class
+                        .as_ref()
+                        .and_then(|super_class| self.snippet(super_class.span())),
+                    interfaces: class
+                        .implements
+                        .iter()
+                        .filter_map(|implemented| self.snippet(implemented.expr.span()))
+                        .collect(),
+                };
+                (self.declaration_span(class.span), heritage)
+            });
+
+        Ok(declaration)
+    }
+
+    /// Find the declaration of a property inside a named class
+    ///
+    /// Covers fields (`total = computed(...)`, `form = new FormGroup(...)`),
+    /// `#private` fields and constructor parameter properties
+    /// (`constructor(private http: HttpClient)`).
+    pub fn find_property(
+        &self,
+        file_content: &str,
+        class_name: &str,
+        property_name: &str,
+    ) -> Result<Option<DeclarationSpan>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let span = all_classes(&module)
+            .into_iter()
+            .filter(|(name, _)| *name == Some(class_name))
+            .find_map(|(_, class)| self.find_property_in_class(class, property_name))
+            .map(|span| self.declaration_span(span));
+
+        Ok(span)
+    }
+
+    fn find_property_in_class(
+        &self,
+        class: &Class,
+        property_name: &str,
+    ) -> Option<swc_common::Span> {
+        class.body.iter().find_map(|member| match member {
+            ClassMember::ClassProp(prop) if self.matches_method_name(&prop.key, property_name) => {
+                Some(prop.span)
+            }
+            ClassMember::PrivateProp(prop) if prop.key.name.as_str() == property_name => {
+                Some(prop.span)
+            }
+            ClassMember::Constructor(constructor) => {
+                constructor.params.iter().find_map(|param| match param {
+                    ParamOrTsParamProp::TsParamProp(prop) => {
+                        let ident = match &prop.param {
+                            TsParamPropParam::Ident(ident) => Some(&ident.id),
+                            TsParamPropParam::Assign(assign) => {
+                                assign.left.as_ident().map(|b| &b.id)
+                            }
+                        }?;
+                        (ident.sym.as_str() == property_name).then_some(prop.span)
+                    }
+                    _ => None,
+                })
             }
             _ => None,
-        }
+        })
+    }
+
+    /// Find the position of a method (or constructor) inside a named class
+    pub fn find_method_declaration(
+        &self,
+        file_content: &str,
+        class_name: &str,
+        method_name: &str,
+    ) -> Result<Option<DeclarationSpan>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let span = self
+            .find_method_in_module(&module, method_name, None, Some(class_name))
+            .map(|found| self.declaration_span(found.span));
+
+        Ok(span)
+    }
+
+    /// Find the class whose `@Component` decorator declares the selector
+    ///
+    /// Selector lists (`'app-foo, [appFoo]'`) match if any entry equals the
+    /// requested selector.
+    pub fn find_component_by_selector(
+        &self,
+        file_content: &str,
+        selector: &str,
+    ) -> Result<Option<String>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let class_name = top_level_classes(&module)
+            .into_iter()
+            .find(|(_, class)| {
+                decorator_call(class, "Component")
+                    .and_then(|call| call_object_arg(call, 0))
+                    .and_then(|config| object_string_prop(config, "selector"))
+                    .is_some_and(|value| value.split(',').any(|s| s.trim() == selector))
+            })
+            .and_then(|(name, _)| name.map(str::to_string));
+
+        Ok(class_name)
+    }
+
+    /// Find the names of top-level classes carrying a decorator call such as
+    /// `@Injectable()`
+    pub fn find_decorated_classes(
+        &self,
+        file_content: &str,
+        decorator_name: &str,
+    ) -> Result<Vec<String>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let class_names = top_level_classes(&module)
+            .into_iter()
+            .filter(|(_, class)| decorator_call(class, decorator_name).is_some())
+            .filter_map(|(name, _)| name.map(str::to_string))
+            .collect();
+
+        Ok(class_names)
     }
 
-    /// Search for a method in a module declaration
-    fn find_method_in_module_decl(&self, decl: &ModuleDecl, method_name: &str) -> Option<u32> {
-        match decl {
-            ModuleDecl::ExportDecl(export_decl) => match &export_decl.decl {
-                Decl::Class(class_decl) => {
-                    self.find_method_in_class(&class_decl.class, method_name)
+    /// Collect the `export ... from` re-exports of a module
+    ///
+    /// Namespace re-exports (`export * as ns from`) are skipped, since they
+    /// do not expose the class under its own name.
+    pub fn find_reexports(&self, file_content: &str) -> Result<Vec<ReExport>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let reexports = module
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                ModuleItem::ModuleDecl(ModuleDecl::ExportAll(export_all)) => Some(ReExport::All {
+                    source: export_all.src.value.as_str()?.to_string(),
+                }),
+                ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(named)) => {
+                    let source = named.src.as_ref()?.value.as_str()?.to_string();
+                    let names = named
+                        .specifiers
+                        .iter()
+                        .filter_map(|specifier| match specifier {
+                            ExportSpecifier::Named(named) => {
+                                let orig = export_name(&named.orig)?;
+                                let exported = match &named.exported {
+                                    Some(exported) => export_name(exported)?,
+                                    None => orig.clone(),
+                                };
+                                Some((exported, orig))
+                            }
+                            _ => None,
+                        })
+                        .collect();
+                    Some(ReExport::Named { source, names })
                 }
                 _ => None,
-            },
-            ModuleDecl::ExportDefaultDecl(export_default) => match &export_default.decl {
-                DefaultDecl::Class(class_expr) => {
-                    self.find_method_in_class(&class_expr.class, method_name)
-                }
+            })
+            .collect();
+
+        Ok(reexports)
+    }
+
+    /// Collect the import declarations of a module
+    ///
+    /// Namespace imports (`import * as ns`) contribute no names.
+    pub fn find_imports(&self, file_content: &str) -> Result<Vec<Import>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let imports = module
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => Some(Import {
+                    source: import.src.value.as_str()?.to_string(),
+                    names: import
+                        .specifiers
+                        .iter()
+                        .filter_map(|specifier| match specifier {
+                            ImportSpecifier::Named(named) => match &named.imported {
+                                Some(imported) => export_name(imported),
+                                None => Some(named.local.sym.to_string()),
+                            },
+                            ImportSpecifier::Default(default) => {
+                                Some(default.local.sym.to_string())
+                            }
+                            ImportSpecifier::Namespace(_) => None,
+                        })
+                        .collect(),
+                }),
                 _ => None,
-            },
-            _ => None,
-        }
+            })
+            .collect();
+
+        Ok(imports)
     }
 
-    /// Search for a method in a class
-    fn find_method_in_class(&self, class: &Class, method_name: &str) -> Option<u32> {
-        for member in &class.body {
-            match member {
-                ClassMember::Method(method) => {
-                    if self.matches_method_name(&method.key, method_name) {
-                        // Get the line number from the span
-                        let loc = self.source_map.lookup_line(method.span.lo);
-                        if let Ok(loc) = loc {
-                            // Line numbers are 0-indexed, so add 1 for human-readable line numbers
-                            return Some(loc.line as u32 + 1);
-                        }
-                    }
-                }
-                ClassMember::PrivateMethod(method) => {
-                    if method.key.name.as_str() == method_name {
-                        let loc = self.source_map.lookup_line(method.span.lo);
-                        if let Ok(loc) = loc {
-                            return Some(loc.line as u32 + 1);
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-        None
+    /// List the symbols a standalone component imports through the
+    /// `imports` array of its `@Component` decorator, in array order
+    ///
+    /// `forwardRef(() => Symbol)` is unwrapped. Each symbol is matched with
+    /// the import declaration bringing it into the file, if any. Returns
+    /// `None` if the class is not declared in the file or is not a
+    /// component.
+    pub fn find_component_imports(
+        &self,
+        file_content: &str,
+        class_name: &str,
+    ) -> Result<Option<Vec<ComponentImport>>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let Some(config) = top_level_classes(&module)
+            .into_iter()
+            .find(|(name, _)| *name == Some(class_name))
+            .and_then(|(_, class)| decorator_call(class, "Component"))
+        else {
+            return Ok(None);
+        };
+        let Some(imports) =
+            call_object_arg(config, 0).and_then(|config| object_prop(config, "imports"))
+        else {
+            return Ok(Some(Vec::new()));
+        };
+
+        let symbols: Vec<(&Expr, bool)> = match imports {
+            Expr::Array(array) => array
+                .elems
+                .iter()
+                .flatten()
+                .map(|elem| (&*elem.expr, elem.spread.is_some()))
+                .collect(),
+            imports => vec![(imports, true)],
+        };
+
+        let sources: Vec<(&str, &str)> = module
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => Some(import),
+                _ => None,
+            })
+            .flat_map(|import| {
+                import.specifiers.iter().filter_map(|specifier| {
+                    let local = match specifier {
+                        ImportSpecifier::Named(named) => &named.local,
+                        ImportSpecifier::Default(default) => &default.local,
+                        ImportSpecifier::Namespace(namespace) => &namespace.local,
+                    };
+                    Some((local.sym.as_str(), import.src.value.as_str()?))
+                })
+            })
+            .collect();
+
+        Ok(Some(
+            symbols
+                .into_iter()
+                .filter_map(|(expr, spread)| {
+                    let expr = forward_ref_target(expr).unwrap_or(expr);
+                    let symbol = callee_path(expr).filter(|path| !path.contains("()"))?;
+                    let root = symbol.split('.').next().unwrap_or(&symbol);
+                    let source = sources
+                        .iter()
+                        .find(|(local, _)| *local == root)
+                        .map(|(_, source)| source.to_string());
+                    let position = self.declaration_span(expr.span());
+
+                    Some(ComponentImport {
+                        symbol,
+                        source,
+                        spread,
+                        line: position.line,
+                        column: position.column,
+                    })
+                })
+                .collect(),
+        ))
     }
 
-    /// Check if a property name matches the method name
-    fn matches_method_name(&self, prop_name: &PropName, method_name: &str) -> bool {
-        match prop_name {
-            PropName::Ident(ident) => ident.sym.as_str() == method_name,
-            PropName::Str(str_lit) => str_lit.value.as_str() == Some(method_name),
-            _ => false,
-        }
+    /// List the top-level components of a module with their change
+    /// detection strategy
+    pub fn find_change_detection(&self, module: &Module) -> Vec<ComponentChangeDetection> {
+        top_level_classes(module)
+            .into_iter()
+            .filter_map(|(name, class)| {
+                let call = decorator_call(class, "Component")?;
+                let strategy = call_object_arg(call, 0)
+                    .and_then(|config| object_prop(config, "changeDetection"))
+                    .and_then(callee_path)
+                    .map(|path| callee_name(&path).to_string());
+
+                Some(ComponentChangeDetection {
+                    class_name: name?.to_string(),
+                    strategy,
+                    span: self.declaration_span(class.span),
+                })
+            })
+            .collect()
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Evaluate the first argument of a class decorator to JSON, e.g. the
+    /// configuration object of `@Component({...})`
+    ///
+    /// Spreads, template literals and computed keys are resolved through
+    /// the file's top-level constants; values that are not static become
+    /// `{"$expression": "<source>"}` objects. Returns `None` if the class
+    /// is not declared or does not call the decorator, and `null` JSON if
+    /// the decorator is called without arguments.
+    pub fn find_decorator_metadata(
+        &self,
+        file_content: &str,
+        class_name: &str,
+        decorator_name: &str,
+    ) -> Result<Option<serde_json::Value>, String> {
+        let module = self.parse_module(file_content)?;
 
-    #[test]
-    fn test_find_method_line() {
-        let parser = TypeScriptParser::new();
+        let Some(call) = top_level_classes(&module)
+            .into_iter()
+            .find(|(name, _)| *name == Some(class_name))
+            .and_then(|(_, class)| decorator_call(class, decorator_name))
+        else {
+            return Ok(None);
+        };
 
-        let code = r#"
-export class MyComponent {
-  constructor() {}
+        let evaluator = StaticEvaluator::new(&module, |expr| self.snippet(expr.span()));
+        Ok(Some(
+            call.args
+                .first()
+                .map_or(serde_json::Value::Null, |arg| evaluator.evaluate(&arg.expr)),
+        ))
+    }
 
-  ngOnInit() {
-    console.log('init');
-  }
+    /// List the components of a module with their `templateUrl`
+    ///
+    /// Returns class name and template URL pairs, as written in the decorator.
+    pub fn find_component_templates(
+        &self,
+        file_content: &str,
+    ) -> Result<Vec<(String, String)>, String> {
+        let module = self.parse_module(file_content)?;
 
-  myMethod() {
-    return 42;
-  }
-}
-"#;
+        let templates = top_level_classes(&module)
+            .into_iter()
+            .filter_map(|(name, class)| {
+                let template_url = decorator_call(class, "Component")
+                    .and_then(|call| call_object_arg(call, 0))
+                    .and_then(|config| object_string_prop(config, "templateUrl"))?;
+                Some((name?.to_string(), template_url))
+            })
+            .collect();
 
-        let result = parser.find_method_line(code, "myMethod");
-        assert!(result.is_ok());
-        assert!(result.unwrap().is_some());
+        Ok(templates)
+    }
 
-        let result = parser.find_method_line(code, "ngOnInit");
-        assert!(result.is_ok());
-        assert!(result.unwrap().is_some());
+    /// Find the names of top-level classes that directly extend a base class
+    ///
+    /// Matches `extends Base`, `extends Base<T>` and namespaced
+    /// `extends ns.Base`.
+    pub fn find_subclasses(
+        &self,
+        file_content: &str,
+        base_class_name: &str,
+    ) -> Result<Vec<String>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let class_names = top_level_classes(&module)
+            .into_iter()
+            .filter(|(_, class)| match class.super_class.as_deref() {
+                Some(Expr::Ident(ident)) => ident.sym.as_str() == base_class_name,
+                Some(Expr::Member(member)) => match &member.prop {
+                    MemberProp::Ident(prop) => prop.sym.as_str() == base_class_name,
+                    _ => false,
+                },
+                _ => false,
+            })
+            .filter_map(|(name, _)| name.map(str::to_string))
+            .collect();
+
+        Ok(class_names)
+    }
+
+    /// Summarize the exported top-level classes of a module
+    ///
+    /// A class counts as exported when declared with `export` /
+    /// `export default` or listed in a local `export { ... }`.
+    pub fn find_exported_classes(&self, file_content: &str) -> Result<Vec<ClassSummary>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let locally_exported: Vec<String> = module
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(named)) if named.src.is_none() => {
+                    Some(named)
+                }
+                _ => None,
+            })
+            .flat_map(|named| &named.specifiers)
+            .filter_map(|specifier| match specifier {
+                ExportSpecifier::Named(named) => export_name(&named.orig),
+                _ => None,
+            })
+            .collect();
+
+        let summaries = module
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                    decl: Decl::Class(class_decl),
+                    ..
+                })) => Some((class_decl.ident.sym.to_string(), &*class_decl.class)),
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+                    decl: DefaultDecl::Class(class_expr),
+                    ..
+                })) => Some((
+                    class_expr
+                        .ident
+                        .as_ref()
+                        .map(|ident| ident.sym.to_string())
+                        .unwrap_or_else(|| "default".to_string()),
+                    &*class_expr.class,
+                )),
+                ModuleItem::Stmt(Stmt::Decl(Decl::Class(class_decl)))
+                    if locally_exported
+                        .iter()
+                        .any(|name| name == class_decl.ident.sym.as_str()) =>
+                {
+                    Some((class_decl.ident.sym.to_string(), &*class_decl.class))
+                }
+                _ => None,
+            })
+            .map(|(name, class)| ClassSummary {
+                name,
+                decorators: class.decorators.iter().filter_map(decorator_name).collect(),
+                method_count: class
+                    .body
+                    .iter()
+                    .filter(|member| match member {
+                        ClassMember::Method(method) => method.kind == MethodKind::Method,
+                        ClassMember::PrivateMethod(method) => method.kind == MethodKind::Method,
+                        _ => false,
+                    })
+                    .count(),
+            })
+            .collect();
+
+        Ok(summaries)
+    }
+
+    /// List the methods, accessors, arrow-function properties and constructor
+    /// of a top-level class, in source order
+    ///
+    /// Returns `None` if the class is not declared in the file. Members with
+    /// computed names are skipped.
+    pub fn list_class_methods(
+        &self,
+        file_content: &str,
+        class_name: &str,
+    ) -> Result<Option<Vec<MethodSummary>>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let Some((_, class)) = top_level_classes(&module)
+            .into_iter()
+            .find(|(name, _)| *name == Some(class_name))
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(self.class_methods(class)))
+    }
+
+    /// List the calls made by a method, in source order
+    ///
+    /// Calls inside callbacks (`subscribe(user => this.render(user))`) are
+    /// included; `new` expressions are not. Returns `None` if the class or
+    /// method is not declared in the file.
+    pub fn find_method_calls(
+        &self,
+        file_content: &str,
+        class_name: &str,
+        method_name: &str,
+    ) -> Result<Option<Vec<MethodCall>>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let Some(found) = self.find_method_in_module(&module, method_name, None, Some(class_name))
+        else {
+            return Ok(None);
+        };
+
+        let mut collector = CallCollector::default();
+        if let Some(body) = found.body {
+            walk_body(body, &mut collector);
+        }
+
+        let mut calls: Vec<_> = collector
+            .calls
+            .into_iter()
+            .map(|(span, callee)| {
+                let position = self.declaration_span(span);
+                let callee = callee.or_else(|| self.snippet(span)).unwrap_or_default();
+                MethodCall {
+                    name: callee_name(&callee).to_string(),
+                    callee,
+                    line: position.line,
+                    column: position.column,
+                }
+            })
+            .collect();
+        calls.sort_by_key(|call| (call.line, call.column));
+
+        Ok(Some(calls))
+    }
+
+    /// List the RxJS `pipe(...)` operator chains of a method, in source order
+    ///
+    /// Chains built inside operators (`switchMap(id => this.load(id).pipe(...))`)
+    /// are listed separately. Returns `None` if the class or method is not
+    /// declared in the file.
+    pub fn find_pipe_chains(
+        &self,
+        file_content: &str,
+        class_name: &str,
+        method_name: &str,
+    ) -> Result<Option<Vec<PipeChain>>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let Some(found) = self.find_method_in_module(&module, method_name, None, Some(class_name))
+        else {
+            return Ok(None);
+        };
+
+        let mut collector = PipeCollector::default();
+        if let Some(body) = found.body {
+            walk_body(body, &mut collector);
+        }
+
+        let mut chains: Vec<_> = collector
+            .pipes
+            .into_iter()
+            .map(|(span, source, args)| {
+                let position = self.declaration_span(span);
+                let operators = args
+                    .iter()
+                    .filter_map(|arg| {
+                        let (name, arity) = match &*arg.expr {
+                            Expr::Call(CallExpr {
+                                callee: Callee::Expr(callee),
+                                args,
+                                ..
+                            }) => (callee_name(&callee_path(callee)?).to_string(), args.len()),
+                            operator => (callee_path(operator)?, 0),
+                        };
+                        Some(PipeOperator {
+                            name,
+                            arity: arity as u32,
+                            line: self.declaration_span(arg.expr.span()).line,
+                        })
+                    })
+                    .collect();
+
+                PipeChain {
+                    source: callee_path(source)
+                        .or_else(|| self.snippet(source.span()))
+                        .unwrap_or_default(),
+                    operators,
+                    line: position.line,
+                    column: position.column,
+                }
+            })
+            .collect();
+        chains.sort_by_key(|chain| (chain.line, chain.column));
+
+        Ok(Some(chains))
+    }
+
+    /// List the writes a method makes to the state of its class
+    ///
+    /// Covers assignments and updates of `this.member` (or anything reached
+    /// through it, like `this.user.name = ...`) and in-place array, map and
+    /// set methods such as `this.items.push(...)`, including those made from
+    /// callbacks. Returns `None` if the class or method is not declared in
+    /// the module.
+    pub fn find_state_mutations(
+        &self,
+        module: &Module,
+        class_name: &str,
+        method_name: &str,
+    ) -> Option<Vec<StateMutation>> {
+        let found = self.find_method_in_module(module, method_name, None, Some(class_name))?;
+
+        let mut collector = MutationCollector::default();
+        if let Some(body) = found.body {
+            walk_body(body, &mut collector);
+        }
+
+        Some(
+            collector
+                .mutations
+                .into_iter()
+                .map(|(member, span)| StateMutation {
+                    member,
+                    line: self.declaration_span(span).line,
+                })
+                .collect(),
+        )
+    }
+
+    /// Measure the complexity and size of a method
+    ///
+    /// Callbacks count toward the enclosing method; overload signatures
+    /// measure as an empty body. Returns `None` if the class or method
+    /// is not declared in the file.
+    pub fn measure_method(
+        &self,
+        file_content: &str,
+        class_name: &str,
+        method_name: &str,
+    ) -> Result<Option<MethodMetrics>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let Some(found) = self.find_method_in_module(&module, method_name, None, Some(class_name))
+        else {
+            return Ok(None);
+        };
+
+        let mut metrics = MethodMetrics {
+            cyclomatic_complexity: 1,
+            ..Default::default()
+        };
+        if let Some(body) = found.body {
+            walk_body(body, &mut metrics);
+        }
+        let span = self.declaration_span(found.span);
+        metrics.line_count = span.end_line - span.line + 1;
+
+        Ok(Some(metrics))
+    }
+
+    /// Hash the shape of a method body, ignoring formatting
+    ///
+    /// Whitespace, comments, positions and the spelling of literals (`'a'`
+    /// vs `"a"`, `1.0` vs `1`) do not affect the hash; any change to the
+    /// statements, expressions or names does. Returns `None` if the class or
+    /// method is not declared in the file, or declares no body.
+    ///
+    /// # Returns
+    /// 16 hex digits of a 64-bit FNV-1a hash
+    pub fn method_structural_hash(
+        &self,
+        file_content: &str,
+        class_name: &str,
+        method_name: &str,
+    ) -> Result<Option<String>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let body = self
+            .find_method_in_module(&module, method_name, None, Some(class_name))
+            .and_then(|found| found.body);
+
+        body.map(|body| structural_hash(&self.source_map, body))
+            .transpose()
+            .map(|hash| hash.map(|hash| format!("{:016x}", hash)))
+    }
+
+    /// List the methods opted out of profiling with a `// @xray:ignore`
+    /// comment, in source order
+    ///
+    /// The marker may be a line or block comment directly above the method or
+    /// its decorators, optionally followed by a reason. Arrow-function
+    /// properties count as methods.
+    ///
+    /// # Arguments
+    /// * `file_content` - The content of the TypeScript file
+    /// * `class_name` - Only report the methods of this class
+    pub fn find_ignored_methods(
+        &self,
+        file_content: &str,
+        class_name: Option<&str>,
+    ) -> Result<Vec<IgnoredMethod>, String> {
+        let comments = SingleThreadedComments::default();
+        let (module, _) = self
+            .parse_source(file_content, Some(&comments))
+            .map_err(|e| format!("Parse error: {:?}", e))?;
+
+        let mut ignored: Vec<IgnoredMethod> = Vec::new();
+        for (name, class) in all_classes(&module) {
+            let Some(name) = name.filter(|name| class_name.is_none_or(|wanted| wanted == *name))
+            else {
+                continue;
+            };
+
+            for member in &class.body {
+                let Some((method_name, span, decorators)) = method_member(member) else {
+                    continue;
+                };
+                let reason = decorators
+                    .iter()
+                    .map(|decorator| decorator.span.lo)
+                    .chain([span.lo])
+                    .filter_map(|pos| comments.get_leading(pos))
+                    .flatten()
+                    .find_map(|comment| ignore_reason(&comment.text));
+                let Some(reason) = reason else {
+                    continue;
+                };
+                // An overloaded method is reported once
+                if ignored
+                    .iter()
+                    .any(|method| method.class_name == name && method.method_name == method_name)
+                {
+                    continue;
+                }
+
+                ignored.push(IgnoredMethod {
+                    class_name: name.to_string(),
+                    method_name,
+                    line: self.declaration_span(span).line,
+                    reason,
+                });
+            }
+        }
+
+        Ok(ignored)
+    }
+
+    /// Find the `subscribe()` calls of the classes of a file whose
+    /// subscription can leak, in source order
+    ///
+    /// A call is reported when its result is discarded (the call is a
+    /// statement of its own, so the subscription is neither stored, added to
+    /// a parent subscription nor returned) and the observable is not piped
+    /// through an operator ending it, such as `takeUntil(this.destroy$)` or
+    /// `takeUntilDestroyed()`.
+    pub fn find_unmanaged_subscriptions(
+        &self,
+        file_content: &str,
+    ) -> Result<Vec<UnmanagedSubscription>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let mut subscriptions = Vec::new();
+        for (name, class) in all_classes(&module) {
+            let Some(name) = name else {
+                continue;
+            };
+
+            for (member_name, body) in member_bodies(class) {
+                let mut collector = SubscriptionCollector::default();
+                walk_body(body, &mut collector);
+
+                for (span, source) in collector.unmanaged {
+                    let position = self.declaration_span(span);
+                    subscriptions.push(UnmanagedSubscription {
+                        class_name: name.to_string(),
+                        member_name: member_name.clone(),
+                        source: callee_path(source)
+                            .or_else(|| self.snippet(source.span()))
+                            .unwrap_or_default(),
+                        line: position.line,
+                        column: position.column,
+                    });
+                }
+            }
+        }
+
+        Ok(subscriptions)
+    }
+
+    /// List the anonymous callbacks of the members of a class, in source order
+    ///
+    /// Callbacks are named `<Class>.<member>.<callee>#<n>`, counting the
+    /// callbacks passed to the same callee in the member, so names only
+    /// change when that member is edited. Property initializers such as
+    /// `users$ = this.store.select(...).pipe(map(...))` are included.
+    ///
+    /// # Arguments
+    /// * `file_content` - The content of the TypeScript file
+    /// * `class_name` - Only list the callbacks of this class
+    pub fn find_anonymous_callbacks(
+        &self,
+        file_content: &str,
+        class_name: Option<&str>,
+    ) -> Result<Vec<AnonymousCallback>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let mut callbacks = Vec::new();
+        for (name, class) in all_classes(&module) {
+            let Some(name) = name.filter(|name| class_name.is_none_or(|wanted| wanted == *name))
+            else {
+                continue;
+            };
+
+            for (member_name, body) in member_bodies(class) {
+                let mut collector = CallbackCollector::default();
+                walk_body(body, &mut collector);
+
+                let mut counts: HashMap<String, u32> = HashMap::new();
+                for (callee, span) in collector.callbacks {
+                    let index = counts.entry(callee.clone()).or_default();
+                    *index += 1;
+                    callbacks.push(AnonymousCallback {
+                        name: format!("{}.{}.{}#{}", name, member_name, callee, index),
+                        class_name: name.to_string(),
+                        member_name: member_name.clone(),
+                        callee,
+                        index: *index,
+                        span: self.declaration_span(span),
+                    });
+                }
+            }
+        }
+
+        Ok(callbacks)
+    }
+
+    /// Name the innermost anonymous callback containing a position, such as
+    /// a frame of a profiled call stack
+    ///
+    /// # Arguments
+    /// * `line` - 1-based line
+    /// * `column` - 1-based column
+    ///
+    /// # Returns
+    /// The callback, or `None` if the position is not inside a callback of
+    /// a class member
+    pub fn find_anonymous_callback_at(
+        &self,
+        file_content: &str,
+        line: u32,
+        column: u32,
+    ) -> Result<Option<AnonymousCallback>, String> {
+        Ok(self
+            .find_anonymous_callbacks(file_content, None)?
+            .into_iter()
+            .filter(|callback| callback.span.contains(line, column))
+            .max_by_key(|callback| callback.span.start))
+    }
+
+    /// Resolve a position of a file to the class and member containing it
+    ///
+    /// Meant for frames whose only information is a source position: the
+    /// class and member name form the `Class.member` key of the method.
+    ///
+    /// # Arguments
+    /// * `line` - 1-based line
+    /// * `column` - 1-based column, in characters
+    ///
+    /// # Returns
+    /// The context, or `None` if the position is outside every named class
+    pub fn find_identifier_at(
+        &self,
+        file_content: &str,
+        line: u32,
+        column: u32,
+    ) -> Result<Option<PositionContext>, String> {
+        let module = self.parse_module(file_content)?;
+
+        let Some((class_name, class)) = all_classes(&module)
+            .into_iter()
+            .filter_map(|(name, class)| {
+                let span = self.declaration_span(class.span);
+                span.contains(line, column)
+                    .then_some((name?, class, span.start))
+            })
+            .max_by_key(|(_, _, start)| *start)
+            .map(|(name, class, _)| (name, class))
+        else {
+            return Ok(None);
+        };
+
+        let member = self
+            .class_methods(class)
+            .into_iter()
+            .find(|method| method.span.contains(line, column))
+            .map(|method| (method.name, Some(method.kind)))
+            .or_else(|| {
+                class.body.iter().find_map(|member| {
+                    let (name, span) = match member {
+                        ClassMember::ClassProp(prop) => (prop_name(&prop.key)?, prop.span),
+                        ClassMember::PrivateProp(prop) => (prop.key.name.to_string(), prop.span),
+                        _ => return None,
+                    };
+                    self.declaration_span(span)
+                        .contains(line, column)
+                        .then_some((name, None))
+                })
+            });
+        let (member_name, member_kind) = member.unzip();
+
+        let callback = match &member_name {
+            Some(_) => self
+                .find_anonymous_callback_at(file_content, line, column)?
+                .map(|callback| callback.name),
+            None => None,
+        };
+
+        Ok(Some(PositionContext {
+            class_name: class_name.to_string(),
+            member_name,
+            member_kind: member_kind.flatten(),
+            callback,
+            identifier: identifier_at(file_content, line, column),
+        }))
+    }
+
+    /// Report the Angular lifecycle hooks a top-level class implements, in
+    /// the order Angular calls them
+    ///
+    /// Returns `None` if the class is not declared in the file.
+    pub fn find_lifecycle_hooks(
+        &self,
+        file_content: &str,
+        class_name: &str,
+    ) -> Result<Option<Vec<LifecycleHook>>, String> {
+        let module = self.parse_module(file_content)?;
+
+        Ok(self.find_lifecycle_hooks_in_module(&module, class_name))
+    }
+
+    /// `find_lifecycle_hooks` over an already parsed module
+    pub(crate) fn find_lifecycle_hooks_in_module(
+        &self,
+        module: &Module,
+        class_name: &str,
+    ) -> Option<Vec<LifecycleHook>> {
+        let (_, class) = top_level_classes(module)
+            .into_iter()
+            .find(|(name, _)| *name == Some(class_name))?;
+
+        let methods = self.class_methods(class);
+        let hooks = LIFECYCLE_HOOKS
+            .iter()
+            .filter_map(|&(hook, interface)| {
+                let method = methods
+                    .iter()
+                    .find(|m| m.name == hook && m.kind == MemberKind::Method && !m.is_static)?;
+                let declares_interface =
+                    class
+                        .implements
+                        .iter()
+                        .any(|implemented| match &*implemented.expr {
+                            Expr::Ident(ident) => ident.sym.as_str() == interface,
+                            Expr::Member(member) => matches!(
+                                &member.prop,
+                                MemberProp::Ident(prop) if prop.sym.as_str() == interface
+                            ),
+                            _ => false,
+                        });
+
+                Some(LifecycleHook {
+                    name: hook,
+                    span: method.span.clone(),
+                    declares_interface,
+                })
+            })
+            .collect();
+
+        Some(hooks)
+    }
+
+    /// Summarize the method-like members of a class
+    fn class_methods(&self, class: &Class) -> Vec<MethodSummary> {
+        class
+            .body
+            .iter()
+            .filter_map(|member| {
+                let (name, span, kind, is_static, visibility, is_async) = match member {
+                    ClassMember::Method(method) => (
+                        prop_name(&method.key)?,
+                        method.span,
+                        MemberKind::of(method.kind),
+                        method.is_static,
+                        visibility(method.accessibility),
+                        method.function.is_async,
+                    ),
+                    ClassMember::PrivateMethod(method) => (
+                        method.key.name.to_string(),
+                        method.span,
+                        MemberKind::of(method.kind),
+                        method.is_static,
+                        "private",
+                        method.function.is_async,
+                    ),
+                    ClassMember::ClassProp(prop) if is_function_value(prop.value.as_deref()) => (
+                        prop_name(&prop.key)?,
+                        prop.span,
+                        MemberKind::Method,
+                        prop.is_static,
+                        visibility(prop.accessibility),
+                        is_async_function_value(prop.value.as_deref()),
+                    ),
+                    ClassMember::PrivateProp(prop) if is_function_value(prop.value.as_deref()) => (
+                        prop.key.name.to_string(),
+                        prop.span,
+                        MemberKind::Method,
+                        prop.is_static,
+                        "private",
+                        is_async_function_value(prop.value.as_deref()),
+                    ),
+                    ClassMember::Constructor(constructor) => (
+                        "constructor".to_string(),
+                        constructor.span,
+                        MemberKind::Constructor,
+                        false,
+                        visibility(constructor.accessibility),
+                        false,
+                    ),
+                    _ => return None,
+                };
+
+                Some(MethodSummary {
+                    name,
+                    kind,
+                    span: self.declaration_span(span),
+                    is_static,
+                    visibility,
+                    is_async,
+                })
+            })
+            .collect()
+    }
+
+    /// List the inputs and outputs of a top-level class, in source order
+    ///
+    /// Covers decorated properties and setters (`@Input() set value(v)`)
+    /// as well as signal-based `input()`, `input.required()`, `output()` and
+    /// `model()`. Returns `None` if the class is not declared in the file.
+    pub fn find_component_bindings(
+        &self,
+        file_content: &str,
+        class_name: &str,
+    ) -> Result<Option<Vec<ComponentBinding>>, String> {
+        let module = self.parse_module(file_content)?;
+
+        Ok(self.find_component_bindings_in_module(&module, class_name))
+    }
+
+    /// `find_component_bindings` over an already parsed module
+    pub(crate) fn find_component_bindings_in_module(
+        &self,
+        module: &Module,
+        class_name: &str,
+    ) -> Option<Vec<ComponentBinding>> {
+        let (_, class) = top_level_classes(module)
+            .into_iter()
+            .find(|(name, _)| *name == Some(class_name))?;
+
+        let bindings = class
+            .body
+            .iter()
+            .filter_map(|member| match member {
+                ClassMember::ClassProp(prop) => {
+                    let name = prop_name(&prop.key)?;
+                    let annotation = prop
+                        .type_ann
+                        .as_ref()
+                        .and_then(|ann| self.snippet(ann.type_ann.span()));
+                    self.decorated_binding(&prop.decorators, &name, prop.span, annotation)
+                        .or_else(|| self.signal_binding(prop.value.as_deref(), &name, prop.span))
+                        .map(|mut binding| {
+                            // `new EventEmitter<T>()` carries the payload type
+                            if binding.type_text.is_none() {
+                                binding.type_text = match prop.value.as_deref() {
+                                    Some(Expr::New(new_expr)) => new_expr
+                                        .type_args
+                                        .as_ref()
+                                        .and_then(|args| args.params.first())
+                                        .and_then(|param| self.snippet(param.span())),
+                                    _ => None,
+                                };
+                            }
+                            binding
+                        })
+                }
+                ClassMember::Method(method) if method.kind == MethodKind::Setter => {
+                    let name = prop_name(&method.key)?;
+                    let annotation = method
+                        .function
+                        .params
+                        .first()
+                        .and_then(|param| match &param.pat {
+                            Pat::Ident(ident) => ident.type_ann.as_ref(),
+                            _ => None,
+                        })
+                        .and_then(|ann| self.snippet(ann.type_ann.span()));
+                    self.decorated_binding(
+                        &method.function.decorators,
+                        &name,
+                        method.span,
+                        annotation,
+                    )
+                }
+                _ => None,
+            })
+            .collect();
+
+        Some(bindings)
+    }
+
+    /// Build a binding from an `@Input(...)` / `@Output(...)` decorator
+    fn decorated_binding(
+        &self,
+        decorators: &[Decorator],
+        name: &str,
+        span: swc_common::Span,
+        type_text: Option<String>,
+    ) -> Option<ComponentBinding> {
+        let (direction, call) = [
+            ("Input", BindingDirection::Input),
+            ("Output", BindingDirection::Output),
+        ]
+        .into_iter()
+        .find_map(|(decorator, direction)| {
+            find_decorator_call(decorators, decorator).map(|call| (direction, call))
+        })?;
+
+        // `@Input('alias')` or `@Input({ alias, required })`
+        let (alias, required) = match call.args.first().map(|arg| &*arg.expr) {
+            Some(Expr::Lit(Lit::Str(alias))) => (alias.value.as_str().map(str::to_string), false),
+            Some(Expr::Object(options)) => (
+                object_string_prop(options, "alias"),
+                object_bool_prop(options, "required"),
+            ),
+            _ => (None, false),
+        };
+
+        Some(ComponentBinding {
+            name: name.to_string(),
+            alias,
+            direction,
+            line: self.declaration_span(span).line,
+            type_text,
+            required,
+        })
+    }
+
+    /// Build a binding from a signal initializer such as `input<T>()`
+    fn signal_binding(
+        &self,
+        value: Option<&Expr>,
+        name: &str,
+        span: swc_common::Span,
+    ) -> Option<ComponentBinding> {
+        let Some(Expr::Call(call)) = value else {
+            return None;
+        };
+        let Callee::Expr(callee) = &call.callee else {
+            return None;
+        };
+
+        let (function, required) = match &**callee {
+            Expr::Ident(ident) => (ident.sym.as_str(), false),
+            Expr::Member(member) => match (&*member.obj, &member.prop) {
+                (Expr::Ident(obj), MemberProp::Ident(prop)) if prop.sym.as_str() == "required" => {
+                    (obj.sym.as_str(), true)
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let direction = match function {
+            "input" => BindingDirection::Input,
+            "output" => BindingDirection::Output,
+            "model" => BindingDirection::Model,
+            _ => return None,
+        };
+
+        let alias = call.args.iter().find_map(|arg| match &*arg.expr {
+            Expr::Object(options) => object_string_prop(options, "alias"),
+            _ => None,
+        });
+        let type_text = call
+            .type_args
+            .as_ref()
+            .and_then(|args| args.params.first())
+            .and_then(|param| self.snippet(param.span()));
+
+        Some(ComponentBinding {
+            name: name.to_string(),
+            alias,
+            direction,
+            line: self.declaration_span(span).line,
+            type_text,
+            required,
+        })
+    }
+
+    /// Source text of a span
+    fn snippet(&self, span: swc_common::Span) -> Option<String> {
+        self.source_map.span_to_snippet(span).ok()
+    }
+
+    /// Convert an swc span into a file-relative declaration position
+    fn declaration_span(&self, span: swc_common::Span) -> DeclarationSpan {
+        let loc = self.source_map.lookup_char_pos(span.lo);
+        let end_loc = self.source_map.lookup_char_pos(span.hi);
+        let start = self.source_map.lookup_byte_offset(span.lo).pos.0;
+        let end = self.source_map.lookup_byte_offset(span.hi).pos.0;
+
+        DeclarationSpan {
+            line: loc.line as u32,
+            column: loc.col.0 as u32 + 1,
+            start,
+            end,
+            end_line: end_loc.line as u32,
+            end_column: end_loc.col.0 as u32 + 1,
+        }
+    }
+
+    /// Search for a method in the module's AST
+    fn find_method_in_module<'a>(
+        &self,
+        module: &'a Module,
+        method_name: &str,
+        kind: Option<MemberKind>,
+        class_name: Option<&str>,
+    ) -> Option<MethodMatch<'a>> {
+        all_classes(module)
+            .into_iter()
+            .filter(|(name, _)| class_name.is_none() || *name == class_name)
+            .find_map(|(_, class)| self.find_method_in_class(class, method_name, kind))
+    }
+
+    /// Search for a method, getter or setter in a class
+    ///
+    /// Overload signatures (declarations without a body) are skipped in
+    /// favor of the implementation; a class with signatures only, such as
+    /// an ambient declaration, resolves to the first one.
+    fn find_method_in_class<'a>(
+        &self,
+        class: &'a Class,
+        method_name: &str,
+        kind: Option<MemberKind>,
+    ) -> Option<MethodMatch<'a>> {
+        let mut overloads = Vec::new();
+        let mut overload_kind = None;
+        let mut overload_decorators: &[Decorator] = &[];
+
+        for member in &class.body {
+            let (span, member_kind, body, decorators) = match member {
+                ClassMember::Method(method)
+                    if self.matches_method_name(&method.key, method_name) =>
+                {
+                    (
+                        method.span,
+                        MemberKind::of(method.kind),
+                        method.function.body.as_ref().map(FunctionBody::Block),
+                        &method.function.decorators[..],
+                    )
+                }
+                ClassMember::PrivateMethod(method) if method.key.name.as_str() == method_name => (
+                    method.span,
+                    MemberKind::of(method.kind),
+                    method.function.body.as_ref().map(FunctionBody::Block),
+                    &method.function.decorators[..],
+                ),
+                // `onClick = () => {...}` behaves like a method
+                ClassMember::ClassProp(prop)
+                    if self.matches_method_name(&prop.key, method_name)
+                        && is_function_value(prop.value.as_deref()) =>
+                {
+                    (
+                        prop.span,
+                        MemberKind::Method,
+                        prop.value.as_deref().and_then(FunctionBody::of_value),
+                        &prop.decorators[..],
+                    )
+                }
+                ClassMember::PrivateProp(prop)
+                    if prop.key.name.as_str() == method_name
+                        && is_function_value(prop.value.as_deref()) =>
+                {
+                    (
+                        prop.span,
+                        MemberKind::Method,
+                        prop.value.as_deref().and_then(FunctionBody::of_value),
+                        &prop.decorators[..],
+                    )
+                }
+                ClassMember::Constructor(constructor) if method_name == "constructor" => (
+                    constructor.span,
+                    MemberKind::Constructor,
+                    constructor.body.as_ref().map(FunctionBody::Block),
+                    &[][..],
+                ),
+                _ => continue,
+            };
+            if kind.is_some_and(|kind| kind != member_kind) {
+                continue;
+            }
+
+            if body.is_none() {
+                if overloads.is_empty() {
+                    overload_decorators = decorators;
+                }
+                overloads.push(span);
+                overload_kind.get_or_insert(member_kind);
+                continue;
+            }
+
+            return Some(MethodMatch {
+                span,
+                kind: member_kind,
+                overloads,
+                decorators,
+                body,
+            });
+        }
+
+        Some(MethodMatch {
+            span: *overloads.first()?,
+            kind: overload_kind?,
+            overloads,
+            decorators: overload_decorators,
+            body: None,
+        })
+    }
+
+    /// Check if a property name matches the method name
+    fn matches_method_name(&self, prop_name: &PropName, method_name: &str) -> bool {
+        match prop_name {
+            PropName::Ident(ident) => ident.sym.as_str() == method_name,
+            PropName::Str(str_lit) => str_lit.value.as_str() == Some(method_name),
+            _ => false,
+        }
+    }
+}
+
+/// Guess the 1-based line being edited from the line of a syntax error
+///
+/// An unfinished call or array (`this.store.dispatch(`) is usually only
+/// reported where the parser gives up, at a later line.
+fn edited_line(source: &str, error_line: u32) -> u32 {
+    let lines: Vec<&str> = source.lines().take(error_line as usize).collect();
+    lines
+        .iter()
+        .rposition(|line| {
+            let balance = line.chars().fold(0i32, |balance, c| match c {
+                '(' | '[' => balance + 1,
+                ')' | ']' => balance - 1,
+                _ => balance,
+            });
+            balance != 0
+        })
+        .map_or(error_line, |index| index as u32 + 1)
+}
+
+/// Replace the characters of a 1-based line with spaces, keeping the line
+///
+/// Returns `false` if the line does not exist or is already blank.
+fn blank_line(source: &mut String, line: u32) -> bool {
+    let Some(start) = source
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1) as usize)
+        .map(str::len)
+        .try_fold(0, |offset, len| Some(offset + len))
+    else {
+        return false;
+    };
+    let Some(rest) = source.get(start..) else {
+        return false;
+    };
+
+    let end = start + rest.find('\n').unwrap_or(rest.len());
+    if source[start..end].trim().is_empty() {
+        return false;
+    }
+
+    let blank = " ".repeat(source[start..end].chars().count());
+    source.replace_range(start..end, &blank);
+    true
+}
+
+/// Call expressions of a method body, with their callee path when it can be
+/// rendered
+#[derive(Default)]
+struct CallCollector {
+    calls: Vec<(swc_common::Span, Option<String>)>,
+}
+
+impl<'a> Visitor<'a> for CallCollector {
+    fn visit_expr(&mut self, expr: &'a Expr, _depth: usize) {
+        match expr {
+            Expr::Call(call) => {
+                if let Callee::Expr(callee) = &call.callee {
+                    self.calls.push((call.span, callee_path(callee)));
+                }
+            }
+            Expr::OptChain(opt_chain) => {
+                if let OptChainBase::Call(call) = &*opt_chain.base {
+                    self.calls.push((opt_chain.span, callee_path(&call.callee)));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `pipe` calls of a method body: the call span, the piped expression and
+/// the operators
+#[derive(Default)]
+struct PipeCollector<'a> {
+    pipes: Vec<(swc_common::Span, &'a Expr, &'a [ExprOrSpread])>,
+}
+
+impl<'a> Visitor<'a> for PipeCollector<'a> {
+    fn visit_expr(&mut self, expr: &'a Expr, _depth: usize) {
+        let (span, callee, args) = match expr {
+            Expr::Call(CallExpr {
+                span,
+                callee: Callee::Expr(callee),
+                args,
+                ..
+            }) => (*span, &**callee, &args[..]),
+            Expr::OptChain(opt_chain) => match &*opt_chain.base {
+                OptChainBase::Call(call) => (opt_chain.span, &*call.callee, &call.args[..]),
+                OptChainBase::Member(_) => return,
+            },
+            _ => return,
+        };
+
+        let member = match callee {
+            Expr::Member(member) => member,
+            Expr::OptChain(opt_chain) => match &*opt_chain.base {
+                OptChainBase::Member(member) => member,
+                OptChainBase::Call(_) => return,
+            },
+            _ => return,
+        };
+        if matches!(&member.prop, MemberProp::Ident(prop) if prop.sym == "pipe") {
+            self.pipes.push((span, &member.obj, args));
+        }
+    }
+}
+
+/// `subscribe()` calls used as statements on observables that are not
+/// bounded by an operator, with the subscribed expression
+#[derive(Default)]
+struct SubscriptionCollector<'a> {
+    unmanaged: Vec<(swc_common::Span, &'a Expr)>,
+}
+
+impl<'a> Visitor<'a> for SubscriptionCollector<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt, _depth: usize) {
+        let Stmt::Expr(expr_stmt) = stmt else {
+            return;
+        };
+        let Some((call, source)) = method_call(&expr_stmt.expr, "subscribe") else {
+            return;
+        };
+
+        let mut observable = source;
+        while let Some((pipe, piped)) = method_call(observable, "pipe") {
+            let bounded = pipe.args.iter().any(|arg| match &*arg.expr {
+                Expr::Call(CallExpr {
+                    callee: Callee::Expr(callee),
+                    ..
+                }) => callee_path(callee)
+                    .is_some_and(|path| BOUNDING_OPERATORS.contains(&callee_name(&path))),
+                _ => false,
+            });
+            if bounded {
+                return;
+            }
+            observable = piped;
+        }
+
+        self.unmanaged.push((call.span, source));
+    }
+}
+
+/// Match `object.name(...)` calls, returning the call and `object`
+fn method_call<'a>(expr: &'a Expr, name: &str) -> Option<(&'a CallExpr, &'a Expr)> {
+    let Expr::Call(call) = expr else {
+        return None;
+    };
+    let Callee::Expr(callee) = &call.callee else {
+        return None;
+    };
+    match &**callee {
+        Expr::Member(member) if matches!(&member.prop, MemberProp::Ident(prop) if prop.sym == name) => {
+            Some((call, &member.obj))
+        }
+        _ => None,
+    }
+}
+
+/// Writes to `this` state, with the member written to
+#[derive(Default)]
+struct MutationCollector {
+    mutations: Vec<(String, swc_common::Span)>,
+}
+
+impl<'a> Visitor<'a> for MutationCollector {
+    fn visit_expr(&mut self, expr: &'a Expr, _depth: usize) {
+        let member = match expr {
+            Expr::Assign(assign) => match &assign.left {
+                AssignTarget::Simple(SimpleAssignTarget::Member(member)) => this_member(member),
+                _ => None,
+            },
+            Expr::Update(update) => match &*update.arg {
+                Expr::Member(member) => this_member(member),
+                _ => None,
+            },
+            Expr::Call(CallExpr {
+                callee: Callee::Expr(callee),
+                ..
+            }) => match &**callee {
+                Expr::Member(member)
+                    if matches!(
+                        &member.prop,
+                        MemberProp::Ident(prop) if MUTATING_METHODS.contains(&prop.sym.as_str())
+                    ) =>
+                {
+                    match &*member.obj {
+                        Expr::Member(object) => this_member(object),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+
+        if let Some(member) = member {
+            self.mutations.push((member, expr.span()));
+        }
+    }
+}
+
+/// The class member a member expression starts from, e.g. `user` for
+/// `this.user.address.city`
+fn this_member(member: &MemberExpr) -> Option<String> {
+    match &*member.obj {
+        Expr::This(_) => match &member.prop {
+            MemberProp::Ident(prop) => Some(prop.sym.to_string()),
+            MemberProp::PrivateName(prop) => Some(format!("#{}", prop.name)),
+            MemberProp::Computed(_) => None,
+        },
+        Expr::Member(object) => this_member(object),
+        Expr::TsNonNull(non_null) => match &*non_null.expr {
+            Expr::Member(object) => this_member(object),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Arrow and function expressions passed to calls, with the name of the
+/// callee receiving them
+#[derive(Default)]
+struct CallbackCollector {
+    callbacks: Vec<(String, swc_common::Span)>,
+}
+
+impl<'a> Visitor<'a> for CallbackCollector {
+    fn visit_expr(&mut self, expr: &'a Expr, _depth: usize) {
+        let (callee, args) = match expr {
+            Expr::Call(CallExpr {
+                callee: Callee::Expr(callee),
+                args,
+                ..
+            }) => (callee_path(callee), &args[..]),
+            Expr::New(new) => (
+                callee_path(&new.callee),
+                new.args.as_deref().unwrap_or_default(),
+            ),
+            Expr::OptChain(opt_chain) => match &*opt_chain.base {
+                OptChainBase::Call(call) => (callee_path(&call.callee), &call.args[..]),
+                OptChainBase::Member(_) => return,
+            },
+            _ => return,
+        };
+
+        let callee = callee.as_deref().map_or("anonymous", callee_name);
+        for arg in args {
+            if is_function_value(Some(&arg.expr)) {
+                self.callbacks.push((callee.to_string(), arg.expr.span()));
+            }
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for MethodMetrics {
+    fn visit_stmt(&mut self, stmt: &'a Stmt, depth: usize) {
+        self.max_nesting_depth = self.max_nesting_depth.max(depth as u32);
+
+        match stmt {
+            Stmt::Block(_) | Stmt::Empty(_) => return,
+            Stmt::If(_)
+            | Stmt::While(_)
+            | Stmt::DoWhile(_)
+            | Stmt::For(_)
+            | Stmt::ForIn(_)
+            | Stmt::ForOf(_) => self.cyclomatic_complexity += 1,
+            Stmt::Switch(switch) => {
+                let cases = switch.cases.iter().filter(|case| case.test.is_some());
+                self.cyclomatic_complexity += cases.count() as u32;
+            }
+            Stmt::Try(try_stmt) if try_stmt.handler.is_some() => {
+                self.cyclomatic_complexity += 1;
+            }
+            _ => {}
+        }
+        self.statement_count += 1;
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr, _depth: usize) {
+        let branches = match expr {
+            Expr::Cond(_) => true,
+            Expr::Bin(bin) => matches!(
+                bin.op,
+                BinaryOp::LogicalAnd | BinaryOp::LogicalOr | BinaryOp::NullishCoalescing
+            ),
+            Expr::Assign(assign) => matches!(
+                assign.op,
+                AssignOp::AndAssign | AssignOp::OrAssign | AssignOp::NullishAssign
+            ),
+            _ => false,
+        };
+        if branches {
+            self.cyclomatic_complexity += 1;
+        }
+    }
+}
+
+/// Hash the minified code of a body
+///
+/// Printing the AST again without comments, in minify mode, normalizes
+/// whitespace, quotes and the spelling of numbers while keeping every
+/// statement, operator, name and literal value.
+fn structural_hash(source_map: &Lrc<SourceMap>, body: FunctionBody) -> Result<u64, String> {
+    let mut code = Vec::new();
+    {
+        let writer = JsWriter::new(source_map.clone(), "\n", &mut code, None);
+        let mut emitter = Emitter {
+            cfg: codegen::Config::default().with_minify(true),
+            cm: source_map.clone(),
+            comments: None,
+            wr: omit_trailing_semi(writer),
+        };
+        match body {
+            FunctionBody::Block(block) => block.emit_with(&mut emitter),
+            FunctionBody::Expr(expr) => expr.emit_with(&mut emitter),
+        }
+        .map_err(|e| format!("Failed to print method body: {}", e))?;
+    }
+    Ok(content_hash(&code))
+}
+
+/// Render a callee as a dotted path, e.g. `this.http.get().pipe`
+///
+/// Returns `None` for computed members and other expressions that have no
+/// readable path.
+fn callee_path(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Ident(ident) => Some(ident.sym.to_string()),
+        Expr::This(_) => Some("this".to_string()),
+        Expr::Member(member) => member_path(member),
+        Expr::SuperProp(super_prop) => match &super_prop.prop {
+            SuperProp::Ident(prop) => Some(format!("super.{}", prop.sym)),
+            SuperProp::Computed(_) => None,
+        },
+        Expr::Call(call) => match &call.callee {
+            Callee::Expr(callee) => Some(format!("{}()", callee_path(callee)?)),
+            _ => None,
+        },
+        Expr::OptChain(opt_chain) => match &*opt_chain.base {
+            OptChainBase::Member(member) => member_path(member),
+            OptChainBase::Call(call) => Some(format!("{}()", callee_path(&call.callee)?)),
+        },
+        Expr::Paren(paren) => callee_path(&paren.expr),
+        Expr::TsNonNull(non_null) => callee_path(&non_null.expr),
+        Expr::TsAs(as_expr) => callee_path(&as_expr.expr),
+        _ => None,
+    }
+}
+
+fn member_path(member: &MemberExpr) -> Option<String> {
+    let prop = match &member.prop {
+        MemberProp::Ident(prop) => prop.sym.to_string(),
+        MemberProp::PrivateName(prop) => format!("#{}", prop.name),
+        MemberProp::Computed(_) => return None,
+    };
+    Some(format!("{}.{}", callee_path(&member.obj)?, prop))
+}
+
+/// Last segment of a callee path, e.g. `pipe` for `this.http.get().pipe`
+fn callee_name(callee: &str) -> &str {
+    let callee = callee.trim_end_matches("()");
+    callee.rsplit('.').next().unwrap_or(callee)
+}
+
+/// Collect the classes declared at the top level of a module, with their names
+///
+/// Covers plain, exported and default-exported declarations; anonymous
+/// default exports have no name.
+fn top_level_classes(module: &Module) -> Vec<(Option<&str>, &Class)> {
+    module
+        .body
+        .iter()
+        .filter_map(|item| match item {
+            ModuleItem::Stmt(Stmt::Decl(Decl::Class(class_decl))) => {
+                Some((Some(class_decl.ident.sym.as_str()), &*class_decl.class))
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export_decl)) => {
+                match &export_decl.decl {
+                    Decl::Class(class_decl) => {
+                        Some((Some(class_decl.ident.sym.as_str()), &*class_decl.class))
+                    }
+                    _ => None,
+                }
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(export_default)) => {
+                match &export_default.decl {
+                    DefaultDecl::Class(class_expr) => Some((
+                        class_expr.ident.as_ref().map(|ident| ident.sym.as_str()),
+                        &*class_expr.class,
+                    )),
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The identifier touching a 1-based position of the source, if any
+fn identifier_at(source: &str, line: u32, column: u32) -> Option<String> {
+    let text = source.lines().nth(line.checked_sub(1)? as usize)?;
+    let chars: Vec<char> = text.chars().collect();
+    let is_ident = |c: &char| c.is_alphanumeric() || *c == '_' || *c == '$';
+
+    let at = (column as usize).checked_sub(1)?.min(chars.len());
+    // A position just past an identifier still touches it
+    let at = match chars.get(at) {
+        Some(c) if is_ident(c) => at,
+        _ if at > 0 && is_ident(&chars[at - 1]) => at - 1,
+        _ => return None,
+    };
+
+    let start = chars[..at]
+        .iter()
+        .rposition(|c| !is_ident(c))
+        .map_or(0, |i| i + 1);
+    let end = chars[at..]
+        .iter()
+        .position(|c| !is_ident(c))
+        .map_or(chars.len(), |i| at + i);
+    let identifier: String = chars[start..end].iter().collect();
+    (!identifier.starts_with(|c: char| c.is_ascii_digit())).then_some(identifier)
+}
+
+/// Name, span and decorators of a method-like class member
+fn method_member(member: &ClassMember) -> Option<(String, swc_common::Span, &[Decorator])> {
+    match member {
+        ClassMember::Method(method) => Some((
+            prop_name(&method.key)?,
+            method.span,
+            &method.function.decorators[..],
+        )),
+        ClassMember::PrivateMethod(method) => Some((
+            method.key.name.to_string(),
+            method.span,
+            &method.function.decorators[..],
+        )),
+        ClassMember::ClassProp(prop) if is_function_value(prop.value.as_deref()) => {
+            Some((prop_name(&prop.key)?, prop.span, &prop.decorators[..]))
+        }
+        ClassMember::PrivateProp(prop) if is_function_value(prop.value.as_deref()) => {
+            Some((prop.key.name.to_string(), prop.span, &prop.decorators[..]))
+        }
+        ClassMember::Constructor(constructor) => {
+            Some(("constructor".to_string(), constructor.span, &[][..]))
+        }
+        _ => None,
+    }
+}
+
+/// Check a comment for the [`IGNORE_MARKER`] and return the reason given
+/// after it, if any
+///
+/// Each line is checked, so the marker can be a tag of a doc comment.
+fn ignore_reason(comment: &str) -> Option<Option<String>> {
+    comment.lines().find_map(|line| {
+        let line = line.trim().trim_start_matches('*').trim();
+        let rest = line.strip_prefix(IGNORE_MARKER)?;
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            // e.g. `@xray:ignored`
+            return None;
+        }
+        let reason = rest.trim();
+        Some((!reason.is_empty()).then(|| reason.to_string()))
+    })
+}
+
+/// Named members of a class with code to walk: method and constructor
+/// bodies and property initializers
+fn member_bodies(class: &Class) -> Vec<(String, FunctionBody<'_>)> {
+    class
+        .body
+        .iter()
+        .filter_map(|member| match member {
+            ClassMember::Method(method) => Some((
+                prop_name(&method.key)?,
+                FunctionBody::Block(method.function.body.as_ref()?),
+            )),
+            ClassMember::PrivateMethod(method) => Some((
+                method.key.name.to_string(),
+                FunctionBody::Block(method.function.body.as_ref()?),
+            )),
+            ClassMember::ClassProp(prop) => Some((
+                prop_name(&prop.key)?,
+                FunctionBody::Expr(prop.value.as_deref()?),
+            )),
+            ClassMember::PrivateProp(prop) => Some((
+                prop.key.name.to_string(),
+                FunctionBody::Expr(prop.value.as_deref()?),
+            )),
+            ClassMember::Constructor(constructor) => Some((
+                "constructor".to_string(),
+                FunctionBody::Block(constructor.body.as_ref()?),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Check whether a class property is initialized with an arrow or function
+/// expression
+fn is_function_value(value: Option<&Expr>) -> bool {
+    match value {
+        Some(Expr::Arrow(_)) | Some(Expr::Fn(_)) => true,
+        Some(Expr::Paren(paren)) => is_function_value(Some(&paren.expr)),
+        _ => false,
+    }
+}
+
+/// Check whether a class property is initialized with an async arrow or
+/// function expression
+fn is_async_function_value(value: Option<&Expr>) -> bool {
+    match value {
+        Some(Expr::Arrow(arrow)) => arrow.is_async,
+        Some(Expr::Fn(fn_expr)) => fn_expr.function.is_async,
+        Some(Expr::Paren(paren)) => is_async_function_value(Some(&paren.expr)),
+        _ => false,
+    }
+}
+
+/// Get a non-computed member name (identifier, string or number)
+fn prop_name(prop_name: &PropName) -> Option<String> {
+    match prop_name {
+        PropName::Ident(ident) => Some(ident.sym.to_string()),
+        PropName::Str(str_lit) => str_lit.value.as_str().map(str::to_string),
+        PropName::Num(num) => Some(num.value.to_string()),
+        _ => None,
+    }
+}
+
+/// TypeScript visibility of a member; unannotated members are public
+fn visibility(accessibility: Option<Accessibility>) -> &'static str {
+    match accessibility {
+        Some(Accessibility::Private) => "private",
+        Some(Accessibility::Protected) => "protected",
+        Some(Accessibility::Public) | None => "public",
+    }
+}
+
+/// Collect every class of a module in source order, including class
+/// expressions (`const Foo = class {...}`) and classes returned from mixin
+/// factories
+///
+/// Class expressions are named after the variable they are assigned to.
+/// Classes inside `namespace` / `module` blocks use their own name.
+fn all_classes(module: &Module) -> Vec<(Option<&str>, &Class)> {
+    let mut classes = Vec::new();
+    classes_in_items(&module.body, &mut classes);
+    classes
+}
+
+fn classes_in_items<'a>(items: &'a [ModuleItem], classes: &mut Vec<(Option<&'a str>, &'a Class)>) {
+    for item in items {
+        match item {
+            ModuleItem::Stmt(stmt) => classes_in_stmt(stmt, classes),
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export_decl)) => {
+                classes_in_decl(&export_decl.decl, classes)
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(export_default)) => {
+                match &export_default.decl {
+                    DefaultDecl::Class(class_expr) => {
+                        classes.push((
+                            class_expr.ident.as_ref().map(|ident| ident.sym.as_str()),
+                            &*class_expr.class,
+                        ));
+                        classes_in_class(&class_expr.class, classes);
+                    }
+                    DefaultDecl::Fn(fn_expr) => classes_in_function(&fn_expr.function, classes),
+                    _ => {}
+                }
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(export_default)) => {
+                classes_in_expr(&export_default.expr, None, classes)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Descend into `namespace Foo { ... }`, including dotted `namespace A.B`
+fn classes_in_namespace<'a>(
+    body: &'a TsNamespaceBody,
+    classes: &mut Vec<(Option<&'a str>, &'a Class)>,
+) {
+    match body {
+        TsNamespaceBody::TsModuleBlock(block) => classes_in_items(&block.body, classes),
+        TsNamespaceBody::TsNamespaceDecl(nested) => classes_in_namespace(&nested.body, classes),
+    }
+}
+
+fn classes_in_decl<'a>(decl: &'a Decl, classes: &mut Vec<(Option<&'a str>, &'a Class)>) {
+    match decl {
+        Decl::Class(class_decl) => {
+            classes.push((Some(class_decl.ident.sym.as_str()), &*class_decl.class));
+            classes_in_class(&class_decl.class, classes);
+        }
+        Decl::Var(var_decl) => {
+            for declarator in &var_decl.decls {
+                let name = match &declarator.name {
+                    Pat::Ident(ident) => Some(ident.id.sym.as_str()),
+                    _ => None,
+                };
+                if let Some(init) = &declarator.init {
+                    classes_in_expr(init, name, classes);
+                }
+            }
+        }
+        Decl::Fn(fn_decl) => classes_in_function(&fn_decl.function, classes),
+        Decl::TsModule(module_decl) => {
+            if let Some(body) = &module_decl.body {
+                classes_in_namespace(body, classes);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn classes_in_stmt<'a>(stmt: &'a Stmt, classes: &mut Vec<(Option<&'a str>, &'a Class)>) {
+    match stmt {
+        Stmt::Decl(decl) => classes_in_decl(decl, classes),
+        Stmt::Return(ReturnStmt { arg: Some(arg), .. }) => classes_in_expr(arg, None, classes),
+        Stmt::Expr(expr_stmt) => classes_in_expr(&expr_stmt.expr, None, classes),
+        Stmt::Block(block) => {
+            for stmt in &block.stmts {
+                classes_in_stmt(stmt, classes);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collect class expressions; `name` is the variable the expression is
+/// assigned to, if any
+fn classes_in_expr<'a>(
+    expr: &'a Expr,
+    name: Option<&'a str>,
+    classes: &mut Vec<(Option<&'a str>, &'a Class)>,
+) {
+    match expr {
+        Expr::Class(class_expr) => {
+            let name = name.or(class_expr.ident.as_ref().map(|ident| ident.sym.as_str()));
+            classes.push((name, &*class_expr.class));
+            classes_in_class(&class_expr.class, classes);
+        }
+        Expr::Paren(paren) => classes_in_expr(&paren.expr, name, classes),
+        Expr::Assign(assign) => {
+            let target = match &assign.left {
+                AssignTarget::Simple(SimpleAssignTarget::Ident(ident)) => {
+                    Some(ident.id.sym.as_str())
+                }
+                _ => None,
+            };
+            classes_in_expr(&assign.right, target, classes);
+        }
+        // Mixin factories: `(Base) => class extends Base {...}`
+        Expr::Arrow(arrow) => match &*arrow.body {
+            BlockStmtOrExpr::BlockStmt(block) => {
+                for stmt in &block.stmts {
+                    classes_in_stmt(stmt, classes);
+                }
+            }
+            BlockStmtOrExpr::Expr(body) => classes_in_expr(body, None, classes),
+        },
+        Expr::Fn(fn_expr) => classes_in_function(&fn_expr.function, classes),
+        // Mixin applications: `Mixin(class {...})`
+        Expr::Call(call) => {
+            for arg in &call.args {
+                classes_in_expr(&arg.expr, None, classes);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn classes_in_function<'a>(
+    function: &'a Function,
+    classes: &mut Vec<(Option<&'a str>, &'a Class)>,
+) {
+    if let Some(body) = &function.body {
+        for stmt in &body.stmts {
+            classes_in_stmt(stmt, classes);
+        }
+    }
+}
+
+/// Mixins can extend a class expression: `class extends Mixin(class {...})`
+fn classes_in_class<'a>(class: &'a Class, classes: &mut Vec<(Option<&'a str>, &'a Class)>) {
+    if let Some(super_class) = &class.super_class {
+        classes_in_expr(super_class, None, classes);
+    }
+}
+
+/// Get the name of an export specifier (identifier or string)
+fn export_name(name: &ModuleExportName) -> Option<String> {
+    match name {
+        ModuleExportName::Ident(ident) => Some(ident.sym.to_string()),
+        ModuleExportName::Str(str_lit) => str_lit.value.as_str().map(str::to_string),
+    }
+}
+
+/// Find a decorator call such as `@Component(...)` on a class by name
+fn decorator_call<'a>(class: &'a Class, decorator_name: &str) -> Option<&'a CallExpr> {
+    find_decorator_call(&class.decorators, decorator_name)
+}
+
+/// Find a decorator call by name among the decorators of any declaration
+fn find_decorator_call<'a>(
+    decorators: &'a [Decorator],
+    decorator_name: &str,
+) -> Option<&'a CallExpr> {
+    decorators
+        .iter()
+        .find_map(|decorator| match &*decorator.expr {
+            Expr::Call(call) => match &call.callee {
+                Callee::Expr(callee) => match &**callee {
+                    Expr::Ident(ident) if ident.sym.as_str() == decorator_name => Some(call),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
+/// Get the name of a decorator, called (`@Input()`) or not (`@Input`)
+///
+/// Namespaced decorators (`@core.Component()`) use their last segment.
+fn decorator_name(decorator: &Decorator) -> Option<String> {
+    let expr = match &*decorator.expr {
+        Expr::Call(call) => match &call.callee {
+            Callee::Expr(callee) => &**callee,
+            _ => return None,
+        },
+        expr => expr,
+    };
+
+    match expr {
+        Expr::Ident(ident) => Some(ident.sym.to_string()),
+        Expr::Member(member) => match &member.prop {
+            MemberProp::Ident(prop) => Some(prop.sym.to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Get an object literal passed as the n-th argument of a call
+fn call_object_arg(call: &CallExpr, index: usize) -> Option<&ObjectLit> {
+    match call.args.get(index).map(|arg| &*arg.expr) {
+        Some(Expr::Object(object)) => Some(object),
+        _ => None,
+    }
+}
+
+/// Read the value of a `key: value` property
+fn object_prop<'a>(object: &'a ObjectLit, key: &str) -> Option<&'a Expr> {
+    object.props.iter().find_map(|prop| match prop {
+        PropOrSpread::Prop(prop) => match &**prop {
+            Prop::KeyValue(key_value) if prop_name_is(&key_value.key, key) => {
+                Some(&*key_value.value)
+            }
+            _ => None,
+        },
+        PropOrSpread::Spread(_) => None,
+    })
+}
+
+/// The symbol returned by `forwardRef(() => Symbol)`
+fn forward_ref_target(expr: &Expr) -> Option<&Expr> {
+    let Expr::Call(call) = expr else {
+        return None;
+    };
+    match &call.callee {
+        Callee::Expr(callee) if matches!(&**callee, Expr::Ident(ident) if ident.sym == "forwardRef") =>
+            {}
+        _ => return None,
+    }
+    match call.args.first().map(|arg| &*arg.expr)? {
+        Expr::Arrow(arrow) => match &*arrow.body {
+            BlockStmtOrExpr::Expr(body) => Some(body),
+            BlockStmtOrExpr::BlockStmt(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Read a string-valued property (string or substitution-free template literal)
+fn object_string_prop(object: &ObjectLit, key: &str) -> Option<String> {
+    object.props.iter().find_map(|prop| {
+        let PropOrSpread::Prop(prop) = prop else {
+            return None;
+        };
+        let Prop::KeyValue(key_value) = &**prop else {
+            return None;
+        };
+        if !prop_name_is(&key_value.key, key) {
+            return None;
+        }
+
+        match &*key_value.value {
+            Expr::Lit(Lit::Str(str_lit)) => str_lit.value.as_str().map(str::to_string),
+            Expr::Tpl(tpl) if tpl.exprs.is_empty() => tpl
+                .quasis
+                .first()
+                .and_then(|quasi| quasi.cooked.as_ref())
+                .and_then(|cooked| cooked.as_str())
+                .map(str::to_string),
+            _ => None,
+        }
+    })
+}
+
+/// Read a boolean-valued property, `false` when absent or not a literal
+fn object_bool_prop(object: &ObjectLit, key: &str) -> bool {
+    object.props.iter().any(|prop| {
+        let PropOrSpread::Prop(prop) = prop else {
+            return false;
+        };
+        let Prop::KeyValue(key_value) = &**prop else {
+            return false;
+        };
+
+        prop_name_is(&key_value.key, key)
+            && matches!(&*key_value.value, Expr::Lit(Lit::Bool(value)) if value.value)
+    })
+}
+
+/// Check whether an object property key is the given identifier or string
+fn prop_name_is(prop_name: &PropName, name: &str) -> bool {
+    match prop_name {
+        PropName::Ident(ident) => ident.sym.as_str() == name,
+        PropName::Str(str_lit) => str_lit.value.as_str() == Some(name),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_method() {
+        let parser = TypeScriptParser::new();
+
+        let code = r#"
+export class MyComponent {
+  constructor() {}
+
+  ngOnInit() {
+    console.log('init');
+  }
+
+  myMethod() {
+    return 42;
+  }
+}
+"#;
+
+        let result = parser.find_member(code, "myMethod", None, None);
+        assert!(result.is_ok());
+        assert!(result.unwrap().is_some());
+
+        let result = parser.find_member(code, "ngOnInit", None, None);
+        assert!(result.is_ok());
+        assert!(result.unwrap().is_some());
+    }
+
+    #[test]
+    fn test_find_component_by_selector() {
+        let parser = TypeScriptParser::new();
+
+        let code = r#"
+@Injectable()
+export class DataService {}
+
+@Component({
+  selector: 'app-user-list, [appUserList]',
+  templateUrl: './user-list.component.html',
+})
+export class UserListComponent {}
+"#;
+
+        let result = parser.find_component_by_selector(code, "app-user-list");
+        assert_eq!(result.unwrap().as_deref(), Some("UserListComponent"));
+
+        let result = parser.find_component_by_selector(code, "[appUserList]");
+        assert_eq!(result.unwrap().as_deref(), Some("UserListComponent"));
+
+        let result = parser.find_component_by_selector(code, "app-user");
+        assert!(result.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_find_class_declaration() {
+        let parser = TypeScriptParser::new();
+
+        // Parse another file first so byte offsets must be file-relative
+        parser
+            .find_member("class Other {}", "x", None, None)
+            .unwrap();
+
+        let code = "import { X } from 'x';\n\nexport class UserService {\n  load() {}\n}\n";
+        let (span, _) = parser.describe_class(code, "UserService").unwrap().unwrap();
+
+        assert_eq!(span.line, 3);
+        assert_eq!(span.column, 8);
+        assert_eq!(&code[span.start as usize..span.start as usize + 5], "class");
+        assert!(code[..span.end as usize].ends_with('}'));
+    }
+
+    #[test]
+    fn test_find_decorated_classes() {
+        let parser = TypeScriptParser::new();
+
+        let code = r#"
+@Injectable({ providedIn: 'root' })
+export class DataService {}
+
+@Pipe({ name: 'short' })
+export class ShortPipe {}
+
+// @Injectable() in a comment does not count
+export class Plain {}
+"#;
+
+        let result = parser.find_decorated_classes(code, "Injectable").unwrap();
+        assert_eq!(result, vec!["DataService"]);
+
+        let result = parser.find_decorated_classes(code, "Directive").unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_find_reexports() {
+        let parser = TypeScriptParser::new();
+
+        let code = r#"
+export * from './user.service';
+export { AuthGuard as Guard, Role } from './auth';
+export * as models from './models';
+export const VERSION = 1;
+"#;
+
+        let result = parser.find_reexports(code).unwrap();
+        assert_eq!(
+            result,
+            vec![
+                ReExport::All {
+                    source: "./user.service".to_string()
+                },
+                ReExport::Named {
+                    source: "./auth".to_string(),
+                    names: vec![
+                        ("Guard".to_string(), "AuthGuard".to_string()),
+                        ("Role".to_string(), "Role".to_string()),
+                    ],
+                },
+                ReExport::Named {
+                    source: "./models".to_string(),
+                    names: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_imports() {
+        let parser = TypeScriptParser::new();
+
+        let code = r#"
+import { TestBed } from '@angular/core/testing';
+import { UserService as Service } from './user.service';
+import Default from './default';
+import * as helpers from './helpers';
+"#;
+
+        let names: Vec<(String, Vec<String>)> = parser
+            .find_imports(code)
+            .unwrap()
+            .into_iter()
+            .map(|import| (import.source, import.names))
+            .collect();
+
+        assert_eq!(
+            names[1],
+            (
+                "./user.service".to_string(),
+                vec!["UserService".to_string()]
+            )
+        );
+        assert_eq!(names[2].1, vec!["Default"]);
+        assert!(names[3].1.is_empty());
+    }
+
+    #[test]
+    fn test_find_method_declaration() {
+        let parser = TypeScriptParser::new();
+
+        let code = "class Other {\n  load() {}\n}\n\nexport class UserService {\n  constructor() {}\n\n  load() {}\n}\n";
+
+        let span = parser
+            .find_method_declaration(code, "UserService", "load")
+            .unwrap()
+            .unwrap();
+        assert_eq!((span.line, span.column), (8, 3));
+
+        let span = parser
+            .find_method_declaration(code, "UserService", "constructor")
+            .unwrap()
+            .unwrap();
+        assert_eq!(span.line, 6);
+
+        assert!(parser
+            .find_method_declaration(code, "Missing", "load")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_find_subclasses() {
+        let parser = TypeScriptParser::new();
+
+        let code = r#"
+export class UserStore extends BaseStore<User> {}
+export class CartStore extends stores.BaseStore {}
+export class Unrelated extends BaseStoreV2 {}
+class Plain {}
+"#;
+
+        let result = parser.find_subclasses(code, "BaseStore").unwrap();
+        assert_eq!(result, vec!["UserStore", "CartStore"]);
+    }
+
+    #[test]
+    fn test_find_component_templates() {
+        let parser = TypeScriptParser::new();
+
+        let code = r#"
+@Component({ selector: 'app-a', templateUrl: './a.component.html' })
+export class AComponent {}
+
+@Component({ selector: 'app-b', template: '<p>b</p>' })
+export class BComponent {}
+"#;
+
+        let result = parser.find_component_templates(code).unwrap();
+        assert_eq!(
+            result,
+            vec![("AComponent".to_string(), "./a.component.html".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_find_exported_classes() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+@Component({ selector: 'app-root' })
+export class AppComponent {
+    constructor() {}
+    ngOnInit() {}
+    get title() { return ''; }
+    #refresh() {}
+}
+
+class Helper {}
+class Shared { run() {} }
+export { Shared };
+"#;
+
+        let classes = parser.find_exported_classes(code).unwrap();
+        assert_eq!(
+            classes,
+            vec![
+                ClassSummary {
+                    name: "AppComponent".to_string(),
+                    decorators: vec!["Component".to_string()],
+                    method_count: 2,
+                },
+                ClassSummary {
+                    name: "Shared".to_string(),
+                    decorators: Vec::new(),
+                    method_count: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_member_accessors() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+export class Slider {
+    set value(v: number) {}
+    get value(): number { return 0; }
+    get #ratio() { return 1; }
+}
+"#;
+
+        assert_eq!(
+            parser
+                .find_member(code, "value", None, None)
+                .unwrap()
+                .map(|member| (member.span.line, member.kind)),
+            Some((3, MemberKind::Setter))
+        );
+        assert_eq!(
+            parser
+                .find_member(code, "value", Some(MemberKind::Getter), None)
+                .unwrap()
+                .map(|member| (member.span.line, member.kind)),
+            Some((4, MemberKind::Getter))
+        );
+        assert_eq!(
+            parser
+                .find_member(code, "ratio", Some(MemberKind::Getter), None)
+                .unwrap()
+                .map(|member| (member.span.line, member.kind)),
+            Some((5, MemberKind::Getter))
+        );
+        assert_eq!(
+            parser
+                .find_member(code, "value", Some(MemberKind::Method), None)
+                .unwrap()
+                .map(|member| (member.span.line, member.kind)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_arrow_function_property() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+export class ButtonComponent {
+    label = 'Save';
+    onClick = () => {
+        this.save();
+    };
+    #track = async function () {};
+}
+"#;
+
+        assert_eq!(
+            parser
+                .find_member(code, "onClick", None, None)
+                .unwrap()
+                .map(|member| (member.span.line, member.kind)),
+            Some((4, MemberKind::Method))
+        );
+        assert_eq!(
+            parser
+                .find_member(code, "track", None, None)
+                .unwrap()
+                .map(|member| (member.span.line, member.kind)),
+            Some((7, MemberKind::Method))
+        );
+        assert_eq!(
+            parser
+                .find_member(code, "label", None, None)
+                .unwrap()
+                .map(|member| (member.span.line, member.kind)),
+            None
+        );
+
+        let span = parser
+            .find_method_declaration(code, "ButtonComponent", "onClick")
+            .unwrap()
+            .unwrap();
+        assert_eq!(span.line, 4);
+    }
+
+    #[test]
+    fn test_find_constructor_line() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+@Injectable()
+export class OrderService {
+    private cache = new Map();
+
+    constructor(
+        private http: HttpClient,
+        private store: Store,
+    ) {}
+}
+"#;
+
+        assert_eq!(
+            parser
+                .find_member(code, "constructor", None, None)
+                .unwrap()
+                .map(|member| (member.span.line, member.kind)),
+            Some((6, MemberKind::Constructor))
+        );
+        assert_eq!(
+            parser
+                .find_member(code, "constructor", None, None)
+                .unwrap()
+                .map(|member| member.span.line),
+            Some(6)
+        );
+    }
+
+    #[test]
+    fn test_find_member_span() {
+        let parser = TypeScriptParser::new();
+        let code = "export class A {\n  async load() {\n    return 1;\n  }\n}\n";
+
+        let member = parser
+            .find_member(code, "load", None, None)
+            .unwrap()
+            .unwrap();
+        let (span, kind) = (member.span, member.kind);
+        assert_eq!(kind, MemberKind::Method);
+        assert_eq!((span.line, span.column), (2, 3));
+        assert_eq!((span.end_line, span.end_column), (4, 4));
+        assert_eq!(
+            &code[span.start as usize..span.end as usize],
+            "async load() {\n    return 1;\n  }"
+        );
+    }
+
+    #[test]
+    fn test_list_class_methods() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+export class CartComponent {
+    items: string[] = [];
+    constructor(private store: Store) {}
+    static create() {}
+    protected async load() {}
+    private onClick = async () => {};
+    get total() { return 0; }
+    #reset() {}
+}
+"#;
+
+        let methods = parser
+            .list_class_methods(code, "CartComponent")
+            .unwrap()
+            .unwrap();
+        let summary: Vec<(&str, MemberKind, bool, &str, bool)> = methods
+            .iter()
+            .map(|m| {
+                (
+                    m.name.as_str(),
+                    m.kind,
+                    m.is_static,
+                    m.visibility,
+                    m.is_async,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "constructor",
+                    MemberKind::Constructor,
+                    false,
+                    "public",
+                    false
+                ),
+                ("create", MemberKind::Method, true, "public", false),
+                ("load", MemberKind::Method, false, "protected", true),
+                ("onClick", MemberKind::Method, false, "private", true),
+                ("total", MemberKind::Getter, false, "public", false),
+                ("reset", MemberKind::Method, false, "private", false),
+            ]
+        );
+        assert_eq!((methods[2].span.line, methods[2].span.end_line), (6, 6));
+
+        assert_eq!(parser.list_class_methods(code, "Missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_find_component_bindings() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+@Component({ selector: 'app-user' })
+export class UserComponent {
+    @Input() user: User | null = null;
+    @Input({ alias: 'userId', required: true }) id!: string;
+    @Output('picked') selected = new EventEmitter<User>();
+    @Input() set theme(value: 'light' | 'dark') {}
+    name = input.required<string>();
+    changed = output<number>({ alias: 'change' });
+    value = model(0);
+    other = signal(1);
+}
+"#;
+
+        let bindings = parser
+            .find_component_bindings(code, "UserComponent")
+            .unwrap()
+            .unwrap();
+        let summary: Vec<_> = bindings
+            .iter()
+            .map(|b| {
+                (
+                    b.name.as_str(),
+                    b.alias.as_deref(),
+                    b.direction,
+                    b.type_text.as_deref(),
+                    b.required,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "user",
+                    None,
+                    BindingDirection::Input,
+                    Some("User | null"),
+                    false
+                ),
+                (
+                    "id",
+                    Some("userId"),
+                    BindingDirection::Input,
+                    Some("string"),
+                    true
+                ),
+                (
+                    "selected",
+                    Some("picked"),
+                    BindingDirection::Output,
+                    Some("User"),
+                    false
+                ),
+                (
+                    "theme",
+                    None,
+                    BindingDirection::Input,
+                    Some("'light' | 'dark'"),
+                    false
+                ),
+                ("name", None, BindingDirection::Input, Some("string"), true),
+                (
+                    "changed",
+                    Some("change"),
+                    BindingDirection::Output,
+                    Some("number"),
+                    false
+                ),
+                ("value", None, BindingDirection::Model, None, false),
+            ]
+        );
+        assert_eq!(bindings[0].line, 4);
+    }
+
+    #[test]
+    fn test_find_lifecycle_hooks() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+export class ChartComponent implements OnInit, core.OnDestroy {
+    ngOnDestroy() {}
+    ngOnInit() {}
+    ngAfterViewInit() {}
+    static ngDoCheck() {}
+    ngOnChanges = 1;
+}
+"#;
+
+        let hooks = parser
+            .find_lifecycle_hooks(code, "ChartComponent")
+            .unwrap()
+            .unwrap();
+        let summary: Vec<_> = hooks
+            .iter()
+            .map(|h| (h.name, h.span.line, h.declares_interface))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("ngOnInit", 4, true),
+                ("ngAfterViewInit", 5, false),
+                ("ngOnDestroy", 3, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_method_in_class_expressions() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+export const Timestamped = <T extends Constructor>(Base: T) =>
+    class extends Base {
+        stamp() {}
+    };
+
+function Disposable(Base) {
+    return class extends Base {
+        dispose() {}
+    };
+}
+
+const Store = class {
+    select() {}
+};
+"#;
+
+        assert_eq!(
+            parser
+                .find_member(code, "stamp", None, None)
+                .unwrap()
+                .map(|member| member.span.line),
+            Some(4)
+        );
+        assert_eq!(
+            parser
+                .find_member(code, "dispose", None, None)
+                .unwrap()
+                .map(|member| member.span.line),
+            Some(9)
+        );
+        assert_eq!(
+            parser
+                .find_member(code, "select", None, None)
+                .unwrap()
+                .map(|member| member.span.line),
+            Some(14)
+        );
+
+        let span = parser
+            .find_method_declaration(code, "Store", "select")
+            .unwrap()
+            .unwrap();
+        assert_eq!(span.line, 14);
+    }
+
+    #[test]
+    fn test_find_method_in_namespaces() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+namespace App.Legacy {
+    export namespace Widgets {
+        export class Grid {
+            render() {}
+        }
+    }
+}
+"#;
+
+        assert_eq!(
+            parser
+                .find_member(code, "render", None, None)
+                .unwrap()
+                .map(|member| member.span.line),
+            Some(5)
+        );
+        let span = parser
+            .find_method_declaration(code, "Grid", "render")
+            .unwrap()
+            .unwrap();
+        assert_eq!(span.line, 5);
+    }
+
+    #[test]
+    fn test_find_member_in_named_class() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+export class OrdersComponent {
+    save() {}
+}
+
+export class ConfirmDialogComponent {
+    save() {}
+}
+"#;
+
+        let member = |class_name| {
+            parser
+                .find_member(code, "save", None, class_name)
+                .unwrap()
+                .map(|member| member.span.line)
+        };
+        assert_eq!(member(None), Some(3));
+        assert_eq!(member(Some("ConfirmDialogComponent")), Some(7));
+        assert_eq!(member(Some("Missing")), None);
+    }
+
+    #[test]
+    fn test_find_overloaded_method() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+export class Formatter {
+    format(value: number): string;
+    format(value: Date): string;
+    format(value: number | Date): string {
+        return String(value);
+    }
+}
+
+export declare class Ambient {
+    parse(text: string): number;
+    parse(text: string, radix: number): number;
+}
+"#;
+
+        let member = parser
+            .find_member(code, "format", None, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(member.span.line, 5);
+        assert_eq!(member.overload_lines, vec![3, 4]);
+
+        let member = parser
+            .find_member(code, "parse", None, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(member.span.line, 11);
+        assert_eq!(member.overload_lines, vec![11, 12]);
+
+        let span = parser
+            .find_method_declaration(code, "Formatter", "format")
+            .unwrap()
+            .unwrap();
+        assert_eq!(span.line, 5);
+    }
+
+    #[test]
+    fn test_parse_fallbacks() {
+        let parser = TypeScriptParser::new();
+
+        // `<T>value` assertions are rejected by the TSX grammar
+        let assertion = "export class Cast {\n    run(x: unknown) { return <string>x; }\n}\n";
+        assert_eq!(
+            parser
+                .find_member(assertion, "run", None, None)
+                .unwrap()
+                .map(|member| member.span.line),
+            Some(2)
+        );
+
+        // A trailing `as` is rejected by both TypeScript grammars only
+        let javascript = "export class Legacy {\n    load() { var x = value as; }\n}\n";
+        assert_eq!(
+            parser
+                .find_member(javascript, "load", None, None)
+                .unwrap()
+                .map(|member| member.span.line),
+            Some(2)
+        );
+
+        assert!(parser
+            .find_member("export class {", "load", None, None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_find_members() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+export class Hot {
+    render() {}
+
+    get total() { return 1; }
+
+    update() {}
+}
+"#;
+
+        let names: Vec<String> = ["update", "missing", "render", "total"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let lines: Vec<_> = parser
+            .find_members(code, &names)
+            .unwrap()
+            .into_iter()
+            .map(|member| member.map(|member| member.span.line))
+            .collect();
+        assert_eq!(lines, vec![Some(7), None, Some(3), Some(5)]);
+    }
+
+    #[test]
+    fn test_member_decorators() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+export class Search {
+    @HostListener('window:resize', ['$event'])
+    @Debounce(300)
+    onResize(event: UIEvent) {}
+
+    @Profile() onSave = () => {};
+
+    plain() {}
+}
+"#;
+
+        let member = parser
+            .find_member(code, "onResize", None, None)
+            .unwrap()
+            .unwrap();
+        let decorators: Vec<_> = member
+            .decorators
+            .iter()
+            .map(|decorator| {
+                (
+                    decorator.name.as_str(),
+                    decorator.text.as_str(),
+                    decorator.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            decorators,
+            vec![
+                (
+                    "HostListener",
+                    "@HostListener('window:resize', ['$event'])",
+                    3
+                ),
+                ("Debounce", "@Debounce(300)", 4),
+            ]
+        );
+
+        let member = parser
+            .find_member(code, "onSave", None, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(member.decorators[0].name, "Profile");
+
+        let member = parser
+            .find_member(code, "plain", None, None)
+            .unwrap()
+            .unwrap();
+        assert!(member.decorators.is_empty());
+    }
+
+    #[test]
+    fn test_find_method_calls() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+export class UserListComponent {
+    load() {
+        this.loading = true;
+        this.http.get(url).pipe(
+            map(users => this.sort(users)),
+        ).subscribe(users => {
+            if (users.length) {
+                this.render(users);
+            }
+        });
+        super.load?.();
+    }
+
+    onClick = () => this.load();
+}
+"#;
+
+        let calls: Vec<_> = parser
+            .find_method_calls(code, "UserListComponent", "load")
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(|call| (call.callee, call.name, call.line))
+            .collect();
+        let expected = [
+            ("this.http.get().pipe().subscribe", "subscribe", 5),
+            ("this.http.get().pipe", "pipe", 5),
+            ("this.http.get", "get", 5),
+            ("map", "map", 6),
+            ("this.sort", "sort", 6),
+            ("this.render", "render", 9),
+            ("super.load", "load", 12),
+        ];
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|&(callee, name, line)| (callee.to_string(), name.to_string(), line))
+            .collect();
+        assert_eq!(calls, expected);
+
+        let calls = parser
+            .find_method_calls(code, "UserListComponent", "onClick")
+            .unwrap()
+            .unwrap();
+        assert_eq!(calls[0].callee, "this.load");
+
+        assert!(parser
+            .find_method_calls(code, "UserListComponent", "missing")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_measure_method() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+export class Report {
+    build(rows: Row[]) {
+        const result = [];
+        for (const row of rows) {
+            if (row.hidden) {
+                continue;
+            } else if (row.total > 0 && row.visible) {
+                result.push(row);
+            }
+        }
+        switch (this.mode) {
+            case 'a': return result;
+            case 'b': break;
+            default: break;
+        }
+        try {
+            this.save(result ?? []);
+        } catch (e) {}
+        return result.length ? result : null;
+    }
+
+    empty() {}
+}
+"#;
+
+        let metrics = parser
+            .measure_method(code, "Report", "build")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            metrics,
+            MethodMetrics {
+                cyclomatic_complexity: 10,
+                statement_count: 13,
+                max_nesting_depth: 2,
+                line_count: 19,
+            }
+        );
+
+        let metrics = parser
+            .measure_method(code, "Report", "empty")
+            .unwrap()
+            .unwrap();
+        assert_eq!(metrics.cyclomatic_complexity, 1);
+        assert_eq!(metrics.statement_count, 0);
+        assert_eq!(metrics.line_count, 1);
+
+        assert!(parser
+            .measure_method(code, "Report", "missing")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_find_member_tolerant() {
+        let parser = TypeScriptParser::new();
+
+        // Mid-edit: an unfinished statement inside another method
+        let code = r#"
+export class Editor {
+    save() {
+        this.store.dispatch(
+    }
+
+    load() {
+        return this.http.get(url);
+    }
+}
+"#;
+        assert!(parser.find_member(code, "load", None, None).is_err());
+
+        let (member, diagnostics) = parser.find_member_tolerant(code, "load", None, None);
+        assert_eq!(member.unwrap().span.line, 7);
+        assert_eq!(diagnostics[0].line, 5);
+
+        // Mid-edit: a method whose closing brace is not typed yet
+        let code =
+            "export class Editor {\n    save() {\n        this.flush();\n    }\n\n    load() {\n";
+        let (member, diagnostics) = parser.find_member_tolerant(code, "save", None, None);
+        assert_eq!(member.unwrap().span.line, 2);
+        assert!(!diagnostics.is_empty());
+
+        let (member, diagnostics) =
+            parser.find_member_tolerant("export class Ok {\n    run() {}\n}\n", "run", None, None);
+        assert_eq!(member.unwrap().span.line, 2);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_edited_line() {
+        let source = "run() {\n    this.save(\n}\n";
+        assert_eq!(edited_line(source, 3), 2);
+        assert_eq!(edited_line("a = ;\nb\n", 1), 1);
+    }
+
+    #[test]
+    fn test_blank_line() {
+        let mut source = "a\nbé c\nd".to_string();
+        assert!(blank_line(&mut source, 2));
+        assert_eq!(source, "a\n    \nd");
+        assert!(!blank_line(&mut source, 2));
+        assert!(!blank_line(&mut source, 9));
+    }
+
+    #[test]
+    fn test_describe_class() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+export class ListComponent extends base.BaseList<User> implements OnInit, OnDestroy {}
+export class Plain {}
+"#;
+
+        let (span, heritage) = parser
+            .describe_class(code, "ListComponent")
+            .unwrap()
+            .unwrap();
+        assert_eq!(span.line, 2);
+        assert_eq!(heritage.super_class.as_deref(), Some("base.BaseList"));
+        assert_eq!(heritage.interfaces, vec!["OnInit", "OnDestroy"]);
+
+        let (_, heritage) = parser.describe_class(code, "Plain").unwrap().unwrap();
+        assert_eq!(heritage, ClassHeritage::default());
+    }
+
+    #[test]
+    fn test_member_doc_comment() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+export class Cart {
+    /**
+     * Sum of all line items.
+     *
+     * @returns the total in cents
+     */
+    @Memoize()
+    total() {}
+
+    /** Single line */
+    clear() {}
+
+    /* not a doc comment */
+    save() {}
+
+    /** Formats a price */
+    format(value: number): string;
+    format(value: string): string;
+    format(value: unknown) {}
+
+    load() {}
+}
+"#;
+
+        let doc = |name| {
+            parser
+                .find_member(code, name, None, None)
+                .unwrap()
+                .unwrap()
+                .doc_comment
+        };
+        assert_eq!(
+            doc("total").as_deref(),
+            Some("Sum of all line items.\n\n@returns the total in cents")
+        );
+        assert_eq!(doc("clear").as_deref(), Some("Single line"));
+        assert_eq!(doc("save"), None);
+        assert_eq!(doc("format").as_deref(), Some("Formats a price"));
+        assert_eq!(doc("load"), None);
+    }
+
+    #[test]
+    fn test_find_property() {
+        let parser = TypeScriptParser::new();
+        let code = r#"
+export class CartComponent {
+    items = signal<Item[]>([]);
+    total = computed(() => this.items().reduce((sum, item) => sum + item.price, 0));
+    #cache = new Map();
+    readonly form = new FormGroup({});
+
+    constructor(private readonly http: HttpClient, public limit = 10) {}
+
+    refresh() {}
+}
+"#;
+
+        let line = |name| {
+            parser
+                .find_property(code, "CartComponent", name)
+                .unwrap()
+                .map(|span| span.line)
+        };
+        assert_eq!(line("items"), Some(3));
+        assert_eq!(line("total"), Some(4));
+        assert_eq!(line("cache"), Some(5));
+        assert_eq!(line("form"), Some(6));
+        assert_eq!(line("http"), Some(8));
+        assert_eq!(line("limit"), Some(8));
+        assert_eq!(line("refresh"), None);
+        assert_eq!(parser.find_property(code, "Other", "items").unwrap(), None);
+    }
+
+    #[test]
+    fn test_method_structural_hash() {
+        let parser = TypeScriptParser::new();
+        let hash = |code: &str| {
+            parser
+                .method_structural_hash(code, "Cart", "total")
+                .unwrap()
+                .unwrap()
+        };
+
+        let original = hash("class Cart {\n    total() { return this.sum('a', 1); }\n}");
+        let reformatted = hash(
+            "// header\nclass Cart {\n\n    total() {\n        // why\n        return this.sum(\"a\", 1.0);\n    }\n}",
+        );
+        let changed = hash("class Cart {\n    total() { return this.sum('b', 1); }\n}");
+
+        assert_eq!(original.len(), 16);
+        assert_eq!(original, reformatted);
+        assert_ne!(original, changed);
+        // Literal text that looks like AST positions is code like any other
+        assert_ne!(
+            hash("class Cart {\n    total() { return 'span: 1..2'; }\n}"),
+            hash("class Cart {\n    total() { return ''; }\n}")
+        );
+        assert!(parser
+            .method_structural_hash("class Cart {}", "Cart", "total")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_find_anonymous_callbacks() {
+        let parser = TypeScriptParser::new();
+        let content = r#"export class UserService {
+    users$ = this.store.select(selectUsers).pipe(map(users => users.length));
+
+    load$() {
+        this.http.get(url).subscribe(user => this.render(user));
+        this.route.params.subscribe(
+            params => this.load(params),
+            function (error) { console.error(error); }
+        );
+    }
+}
+
+class Other {
+    run() { setTimeout(() => this.run(), 10); }
+}
+"#;
+
+        let callbacks = parser
+            .find_anonymous_callbacks(content, Some("UserService"))
+            .unwrap();
+        let names: Vec<_> = callbacks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "UserService.users$.map#1",
+                "UserService.load$.subscribe#1",
+                "UserService.load$.subscribe#2",
+                "UserService.load$.subscribe#3",
+            ]
+        );
+        assert_eq!(callbacks[2].member_name, "load$");
+        assert_eq!(callbacks[2].callee, "subscribe");
+        assert_eq!((callbacks[2].span.line, callbacks[2].span.column), (7, 13));
+        assert_eq!(callbacks[3].span.line, 8);
+
+        let at = |line, column| {
+            parser
+                .find_anonymous_callback_at(content, line, column)
+                .unwrap()
+                .map(|callback| callback.name)
+        };
+        assert_eq!(at(5, 50).as_deref(), Some("UserService.load$.subscribe#1"));
+        assert_eq!(at(14, 25).as_deref(), Some("Other.run.setTimeout#1"));
+        assert_eq!(at(5, 9), None);
+    }
+
+    #[test]
+    fn test_find_ignored_methods() {
+        let parser = TypeScriptParser::new();
+        let content = r#"export class Board {
+    // @xray:ignore
+    render() {}
+
+    /**
+     * Called on every animation frame
+     * @xray:ignore polled every frame
+     */
+    @HostListener('window:scroll')
+    onScroll = () => {};
+
+    // @xray:ignored is not the marker
+    save() {}
+
+    /* @xray:ignore */ tick(): void;
+    tick(step?: number) {}
+}
+
+class Other {
+    // @xray:ignore
+    run() {}
+}
+"#;
+
+        let ignored = parser.find_ignored_methods(content, Some("Board")).unwrap();
+        let names: Vec<_> = ignored.iter().map(|m| m.method_name.as_str()).collect();
+        assert_eq!(names, vec!["render", "onScroll", "tick"]);
+        assert_eq!(ignored[0].line, 3);
+        assert_eq!(ignored[0].reason, None);
+        assert_eq!(ignored[1].reason.as_deref(), Some("polled every frame"));
+
+        let all = parser.find_ignored_methods(content, None).unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[3].class_name, "Other");
+    }
+
+    #[test]
+    fn test_find_component_imports() {
+        let parser = TypeScriptParser::new();
+        let content = r#"import { CommonModule } from '@angular/common';
+import { MatButtonModule as Buttons } from '@angular/material/button';
+import * as Shared from './shared';
+import { SHARED_IMPORTS } from './imports';
+
+@Component({
+    selector: 'app-user',
+    standalone: true,
+    imports: [
+        CommonModule,
+        Buttons,
+        Shared.CardModule,
+        ...SHARED_IMPORTS,
+        forwardRef(() => UserAvatar),
+    ],
+})
+export class UserComponent {}
+
+@Component({ selector: 'app-list', imports: SHARED_IMPORTS })
+export class ListComponent {}
+
+@Component({ selector: 'app-empty' })
+export class EmptyComponent {}
+"#;
+
+        let imports = parser
+            .find_component_imports(content, "UserComponent")
+            .unwrap()
+            .unwrap();
+        let symbols: Vec<_> = imports
+            .iter()
+            .map(|import| {
+                (
+                    import.symbol.as_str(),
+                    import.source.as_deref(),
+                    import.spread,
+                )
+            })
+            .collect();
+        assert_eq!(
+            symbols,
+            vec![
+                ("CommonModule", Some("@angular/common"), false),
+                ("Buttons", Some("@angular/material/button"), false),
+                ("Shared.CardModule", Some("./shared"), false),
+                ("SHARED_IMPORTS", Some("./imports"), true),
+                ("UserAvatar", None, false),
+            ]
+        );
+        assert_eq!((imports[0].line, imports[0].column), (10, 9));
+        assert_eq!(imports[4].line, 14);
+
+        let list = parser
+            .find_component_imports(content, "ListComponent")
+            .unwrap()
+            .unwrap();
+        assert_eq!(list.len(), 1);
+        assert!(list[0].spread);
+        assert_eq!(
+            parser
+                .find_component_imports(content, "EmptyComponent")
+                .unwrap(),
+            Some(Vec::new())
+        );
+        assert_eq!(
+            parser.find_component_imports(content, "Missing").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_pipe_chains() {
+        let parser = TypeScriptParser::new();
+        let content = r#"export class SearchComponent {
+    ngOnInit() {
+        this.results$ = this.query.valueChanges.pipe(
+            debounceTime(300),
+            distinctUntilChanged(),
+            switchMap(query => this.api.search(query).pipe(catchError(() => of([])))),
+            untilDestroyed,
+        );
+        this.user$?.pipe(take(1)).subscribe();
+    }
+}
+"#;
+
+        let chains = parser
+            .find_pipe_chains(content, "SearchComponent", "ngOnInit")
+            .unwrap()
+            .unwrap();
+        let summary: Vec<_> = chains
+            .iter()
+            .map(|chain| {
+                let operators: Vec<_> = chain
+                    .operators
+                    .iter()
+                    .map(|op| (op.name.as_str(), op.arity))
+                    .collect();
+                (chain.source.as_str(), chain.line, operators)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "this.query.valueChanges",
+                    3,
+                    vec![
+                        ("debounceTime", 1),
+                        ("distinctUntilChanged", 0),
+                        ("switchMap", 1),
+                        ("untilDestroyed", 0),
+                    ]
+                ),
+                ("this.api.search()", 6, vec![("catchError", 1)]),
+                ("this.user$", 9, vec![("take", 1)]),
+            ]
+        );
+        assert_eq!(chains[0].operators[2].line, 6);
+        assert_eq!(chains[0].column, 25);
+        assert!(parser
+            .find_pipe_chains(content, "SearchComponent", "missing")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_find_change_detection() {
+        let parser = TypeScriptParser::new();
+        let content = r#"
+@Component({ selector: 'a-list', changeDetection: ChangeDetectionStrategy.OnPush })
+export class ListComponent {}
+
+@Component({ selector: 'a-item' })
+export class ItemComponent {}
+
+@Injectable()
+export class ItemService {}
+"#;
+
+        let module = parser.parse_module(content).unwrap();
+        let components: Vec<_> = parser
+            .find_change_detection(&module)
+            .into_iter()
+            .map(|c| (c.class_name, c.strategy, c.span.line))
+            .collect();
+        assert_eq!(
+            components,
+            vec![
+                ("ListComponent".to_string(), Some("OnPush".to_string()), 3),
+                ("ItemComponent".to_string(), None, 6),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_state_mutations() {
+        let parser = TypeScriptParser::new();
+        let content = r#"class ListComponent {
+    ngOnInit() {
+        const items = [];
+        items.push(1);
+        this.count++;
+        this.user!.name = 'x';
+        this.service.load().subscribe(items => this.items.push(...items));
+        this.form.patchValue({});
+    }
+}
+"#;
+
+        let module = parser.parse_module(content).unwrap();
+        let mutations: Vec<_> = parser
+            .find_state_mutations(&module, "ListComponent", "ngOnInit")
+            .unwrap()
+            .into_iter()
+            .map(|m| (m.member, m.line))
+            .collect();
+        assert_eq!(
+            mutations,
+            vec![
+                ("count".to_string(), 5),
+                ("user".to_string(), 6),
+                ("items".to_string(), 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_unmanaged_subscriptions() {
+        let parser = TypeScriptParser::new();
+        let content = r#"export class UserComponent {
+    private subscription = new Subscription();
+
+    ngOnInit() {
+        this.route.params.subscribe(params => this.load(params));
+        this.store.select(selectUser).pipe(map(user => user.name)).subscribe();
+        this.sub = this.user$.subscribe();
+        this.subscription.add(this.events$.subscribe());
+        this.ticks$.pipe(takeUntil(this.destroy$)).subscribe();
+        this.resize$.pipe(takeUntilDestroyed(this.destroyRef), debounceTime(10)).subscribe();
+    }
+
+    clicks = this.clicks$.subscribe();
+}
+"#;
+
+        let leaks: Vec<_> = parser
+            .find_unmanaged_subscriptions(content)
+            .unwrap()
+            .into_iter()
+            .map(|leak| (leak.member_name, leak.source, leak.line, leak.column))
+            .collect();
+        assert_eq!(
+            leaks,
+            vec![
+                (
+                    "ngOnInit".to_string(),
+                    "this.route.params".to_string(),
+                    5,
+                    9
+                ),
+                (
+                    "ngOnInit".to_string(),
+                    "this.store.select().pipe()".to_string(),
+                    6,
+                    9
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_identifier_at() {
+        let parser = TypeScriptParser::new();
+        let content = r#"export class UserService {
+    private cache = new Map();
+
+    load$() {
+        return this.http.get(url).subscribe(user => this.render(user));
+    }
+}
+"#;
+        let at = |line, column| {
+            parser
+                .find_identifier_at(content, line, column)
+                .unwrap()
+                .unwrap()
+        };
+
+        let context = at(5, 59);
+        assert_eq!(context.class_name, "UserService");
+        assert_eq!(context.member_name.as_deref(), Some("load$"));
+        assert_eq!(context.member_kind, Some(MemberKind::Method));
+        assert_eq!(
+            context.callback.as_deref(),
+            Some("UserService.load$.subscribe#1")
+        );
+        assert_eq!(context.identifier.as_deref(), Some("render"));
+
+        let context = at(4, 5);
+        assert_eq!(context.member_name.as_deref(), Some("load$"));
+        assert_eq!(context.callback, None);
+        assert_eq!(context.identifier.as_deref(), Some("load$"));
+
+        let context = at(2, 15);
+        assert_eq!(context.member_name.as_deref(), Some("cache"));
+        assert_eq!(context.member_kind, None);
+        assert_eq!(context.identifier.as_deref(), Some("cache"));
+
+        assert_eq!(at(3, 1).member_name, None);
+        assert!(parser.find_identifier_at(content, 9, 1).unwrap().is_none());
     }
 }
@@ -0,0 +1,208 @@
+use crate::tsconfig::strip_json_comments;
+use ignore::WalkBuilder;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How a resolved project limits the search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProjectScope {
+    /// Only search files inside the project
+    #[default]
+    Restrict,
+    /// Search everywhere, but rank files inside the project first
+    Prioritize,
+}
+
+impl ProjectScope {
+    /// Parse the scope name used across the FFI boundary
+    pub fn parse(scope: &str) -> Option<Self> {
+        match scope {
+            "restrict" => Some(Self::Restrict),
+            "prioritize" => Some(Self::Prioritize),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawProject {
+    name: Option<String>,
+    #[serde(default)]
+    root: String,
+    source_root: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawWorkspace {
+    #[serde(default)]
+    projects: BTreeMap<String, serde_json::Value>,
+}
+
+/// A project declared by `angular.json`, `workspace.json` or an Nx `project.json`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectInfo {
+    pub name: String,
+    /// Absolute project root
+    pub root: PathBuf,
+    /// Absolute source root, if declared
+    pub source_root: Option<PathBuf>,
+}
+
+impl ProjectInfo {
+    /// Directory that holds the project's sources
+    pub fn search_root(&self) -> &Path {
+        self.source_root.as_deref().unwrap_or(&self.root)
+    }
+}
+
+/// All projects of a (possibly multi-app) Angular / Nx workspace
+#[derive(Debug, Default)]
+pub struct WorkspaceProjects {
+    projects: Vec<ProjectInfo>,
+}
+
+impl WorkspaceProjects {
+    /// Discover projects from the workspace configuration files
+    ///
+    /// `angular.json` and `workspace.json` are read directly; when `nx.json`
+    /// is present, `project.json` files are discovered across the tree.
+    pub fn load<P: AsRef<Path>>(workspace_path: P) -> Self {
+        let workspace_path = workspace_path.as_ref();
+        let mut projects = Vec::new();
+
+        for config_name in ["angular.json", "workspace.json"] {
+            projects.extend(Self::read_workspace_file(
+                workspace_path,
+                &workspace_path.join(config_name),
+            ));
+        }
+
+        if workspace_path.join("nx.json").is_file() {
+            let project_files = WalkBuilder::new(workspace_path)
+                .hidden(true)
+                .require_git(false)
+                .filter_entry(|e| e.file_name() != "node_modules")
+                .build()
+                .filter_map(Result::ok)
+                .filter(|e| e.file_name() == "project.json");
+
+            for entry in project_files {
+                if let Some(project) = Self::read_project_file(workspace_path, entry.path()) {
+                    if !projects
+                        .iter()
+                        .any(|p: &ProjectInfo| p.root == project.root)
+                    {
+                        projects.push(project);
+                    }
+                }
+            }
+        }
+
+        Self { projects }
+    }
+
+    fn read_workspace_file(workspace_path: &Path, config_path: &Path) -> Vec<ProjectInfo> {
+        let raw = fs::read_to_string(config_path).ok().and_then(|content| {
+            serde_json::from_str::<RawWorkspace>(&strip_json_comments(&content)).ok()
+        });
+
+        let Some(raw) = raw else {
+            return Vec::new();
+        };
+
+        raw.projects
+            .into_iter()
+            .filter_map(|(name, value)| {
+                // Older Nx workspaces map names to a project.json directory
+                let project = match value {
+                    serde_json::Value::String(root) => RawProject {
+                        name: None,
+                        root,
+                        source_root: None,
+                    },
+                    value => serde_json::from_value::<RawProject>(value).ok()?,
+                };
+
+                Some(ProjectInfo {
+                    name,
+                    root: workspace_path.join(&project.root),
+                    source_root: project.source_root.map(|s| workspace_path.join(s)),
+                })
+            })
+            .collect()
+    }
+
+    fn read_project_file(workspace_path: &Path, project_path: &Path) -> Option<ProjectInfo> {
+        let content = fs::read_to_string(project_path).ok()?;
+        let project: RawProject = serde_json::from_str(&strip_json_comments(&content)).ok()?;
+        let root = project_path.parent()?.to_path_buf();
+
+        let name = project.name.unwrap_or_else(|| {
+            root.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+
+        Some(ProjectInfo {
+            name,
+            source_root: project.source_root.map(|s| workspace_path.join(s)),
+            root,
+        })
+    }
+
+    /// Find a project by name, or the project owning a path (deepest root wins)
+    pub fn resolve(&self, name_or_path: &str) -> Option<&ProjectInfo> {
+        if let Some(project) = self.projects.iter().find(|p| p.name == name_or_path) {
+            return Some(project);
+        }
+
+        let path = Path::new(name_or_path);
+        self.projects
+            .iter()
+            .filter(|p| path.starts_with(&p.root))
+            .max_by_key(|p| p.root.components().count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_workspace;
+
+    #[test]
+    fn test_angular_and_nx_projects() {
+        let workspace = temp_workspace("projects");
+        fs::create_dir_all(workspace.join("libs/shared/ui")).unwrap();
+        fs::write(
+            workspace.join("angular.json"),
+            r#"{ "projects": { "admin": { "root": "apps/admin", "sourceRoot": "apps/admin/src" } } }"#,
+        )
+        .unwrap();
+        fs::write(workspace.join("nx.json"), "{}").unwrap();
+        fs::write(
+            workspace.join("libs/shared/ui/project.json"),
+            r#"{ "name": "shared-ui", "sourceRoot": "libs/shared/ui/src" }"#,
+        )
+        .unwrap();
+
+        let projects = WorkspaceProjects::load(&workspace);
+
+        let admin = projects.resolve("admin").unwrap();
+        assert_eq!(admin.search_root(), workspace.join("apps/admin/src"));
+
+        let owner = projects
+            .resolve(
+                workspace
+                    .join("libs/shared/ui/src/button.ts")
+                    .to_str()
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(owner.name, "shared-ui");
+
+        assert!(projects.resolve("unknown").is_none());
+    }
+}
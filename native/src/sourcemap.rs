@@ -0,0 +1,147 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Original TypeScript position of a generated JavaScript position
+#[derive(Debug, Clone, PartialEq)]
+pub struct OriginalPosition {
+    /// Source file resolved against the map location or the workspace
+    pub file_path: PathBuf,
+    /// Source as listed in the map, e.g. `webpack:///./src/app/app.component.ts`
+    pub source: String,
+    /// 1-based line in the source file
+    pub line: u32,
+    /// 1-based column in the source file
+    pub column: u32,
+    /// Original identifier at the position, when the map records one
+    pub name: Option<String>,
+}
+
+/// Map a position of a generated file back to its original source
+///
+/// # Arguments
+/// * `map_path` - The `.map` file of the generated JavaScript file
+/// * `line` - 1-based line in the generated file, as in stack frames
+/// * `column` - 1-based column in the generated file
+/// * `workspace_path` - The workspace root that bundler URLs
+///   (`webpack://app/./src/...`) are relative to
+///
+/// # Returns
+/// The original position, or `None` if the map has no mapping there
+pub fn resolve_position(
+    map_path: &Path,
+    line: u32,
+    column: u32,
+    workspace_path: &Path,
+) -> Result<Option<OriginalPosition>, String> {
+    let content = fs::read(map_path).map_err(|e| format!("Failed to read source map: {}", e))?;
+    let map =
+        swc_sourcemap::decode_slice(&content).map_err(|e| format!("Invalid source map: {}", e))?;
+
+    if line == 0 {
+        return Ok(None);
+    }
+    let Some(token) = map.lookup_token(line - 1, column.saturating_sub(1)) else {
+        return Ok(None);
+    };
+    let Some(source) = token.get_source() else {
+        return Ok(None);
+    };
+
+    let map_dir = map_path.parent().unwrap_or_else(|| Path::new(""));
+    Ok(Some(OriginalPosition {
+        file_path: resolve_source(source, map_dir, workspace_path),
+        source: source.to_string(),
+        line: token.get_src_line() + 1,
+        column: token.get_src_col() + 1,
+        name: token.get_name().map(|name| name.to_string()),
+    }))
+}
+
+/// Turn a map source into a file path
+///
+/// Bundler URLs (`webpack://<project>/./src/...`) are relative to the
+/// workspace, `file://` URLs are absolute and plain paths are relative to
+/// the directory of the map.
+fn resolve_source(source: &str, map_dir: &Path, workspace_path: &Path) -> PathBuf {
+    if let Some(path) = source.strip_prefix("file://") {
+        return normalize(Path::new(path));
+    }
+    if let Some((_, rest)) = source.split_once("://") {
+        // Drop the project namespace: `webpack://app/./src` and `webpack:///./src`
+        let path = rest.split_once('/').map_or(rest, |(_, path)| path);
+        return normalize(&workspace_path.join(path.trim_start_matches('/')));
+    }
+
+    normalize(&map_dir.join(source))
+}
+
+/// Resolve `.` and `..` components without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_workspace;
+
+    #[test]
+    fn test_resolve_source() {
+        let map_dir = Path::new("/ws/dist/app");
+        let workspace = Path::new("/ws");
+
+        assert_eq!(
+            resolve_source("../../src/app/app.component.ts", map_dir, workspace),
+            PathBuf::from("/ws/src/app/app.component.ts")
+        );
+        assert_eq!(
+            resolve_source("webpack:///./src/main.ts", map_dir, workspace),
+            PathBuf::from("/ws/src/main.ts")
+        );
+        assert_eq!(
+            resolve_source("webpack://my-app/./src/main.ts", map_dir, workspace),
+            PathBuf::from("/ws/src/main.ts")
+        );
+        assert_eq!(
+            resolve_source("file:///ws/src/main.ts", map_dir, workspace),
+            PathBuf::from("/ws/src/main.ts")
+        );
+    }
+
+    #[test]
+    fn test_resolve_position() {
+        let dir = temp_workspace("sourcemap");
+        let map_path = dir.join("main.js.map");
+        fs::write(
+            &map_path,
+            r#"{"version":3,"sources":["../src/app.component.ts"],"names":["render"],"mappings":"AAAA;AACA,IAAIA"}"#,
+        )
+        .unwrap();
+
+        let position = resolve_position(&map_path, 2, 5, Path::new("/ws"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            position.file_path,
+            normalize(&dir.join("../src/app.component.ts"))
+        );
+        assert_eq!(position.source, "../src/app.component.ts");
+        assert_eq!((position.line, position.column), (2, 5));
+        assert_eq!(position.name.as_deref(), Some("render"));
+
+        assert!(resolve_position(&map_path, 0, 1, Path::new("/ws"))
+            .unwrap()
+            .is_none());
+        assert!(resolve_position(&dir.join("missing.map"), 1, 1, Path::new("/ws")).is_err());
+    }
+}
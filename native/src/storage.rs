@@ -2,7 +2,6 @@ use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use napi::bindgen_prelude::*;
-use napi_derive::napi;
 use std::io::{Read, Write};
 
 /// Compress snapshot data using gzip
@@ -0,0 +1,666 @@
+/// How a template expression is bound
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingKind {
+    /// `(click)="save()"` or `on-click="save()"`
+    Event,
+    /// `[value]="total()"` or `bind-value="total()"`
+    Property,
+    /// `[(ngModel)]="name"`
+    TwoWay,
+    /// `*ngFor="let item of items"`
+    Structural,
+    /// `{{ compute() }}`, in text or attribute values
+    Interpolation,
+}
+
+impl BindingKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BindingKind::Event => "event",
+            BindingKind::Property => "property",
+            BindingKind::TwoWay => "twoWay",
+            BindingKind::Structural => "structural",
+            BindingKind::Interpolation => "interpolation",
+        }
+    }
+}
+
+/// An expression bound in an Angular template
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateBinding {
+    pub kind: BindingKind,
+    /// Bound event, property or directive, e.g. `click`; `None` for
+    /// interpolations
+    pub name: Option<String>,
+    /// The bound expression, trimmed
+    pub expression: String,
+    /// 1-based line of the attribute or `{{`
+    pub line: u32,
+    /// 1-based column of the attribute or `{{`
+    pub column: u32,
+}
+
+/// List the bindings and interpolations of a template, in source order
+///
+/// This is a tolerant scanner rather than a full HTML parser: comments are
+/// skipped, tags are split into attributes and text is searched for
+/// `{{ }}`. Unterminated constructs end the scan.
+pub fn parse_bindings(content: &str) -> Vec<TemplateBinding> {
+    let mut scanner = Scanner::new(content);
+    scanner.scan();
+    scanner.bindings
+}
+
+/// Find the bindings and interpolations whose expression references a
+/// component method or property, e.g. `save` in `(click)="save($event)"`
+pub fn find_method_bindings(content: &str, method_name: &str) -> Vec<TemplateBinding> {
+    parse_bindings(content)
+        .into_iter()
+        .filter(|binding| references(&binding.expression, method_name))
+        .collect()
+}
+
+/// Change-detection performance problems found in a template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    /// A function or getter is called from a binding that Angular
+    /// re-evaluates on every change detection pass
+    FunctionCall,
+    /// An `*ngFor` without `trackBy`, so every new array re-creates the
+    /// DOM of all its items
+    MissingTrackBy,
+    /// An `*ngFor` over a long array literal hard-coded in the template
+    LargeStaticList,
+}
+
+impl WarningKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WarningKind::FunctionCall => "functionCall",
+            WarningKind::MissingTrackBy => "missingTrackBy",
+            WarningKind::LargeStaticList => "largeStaticList",
+        }
+    }
+}
+
+/// A performance anti-pattern in a template binding
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateWarning {
+    pub kind: WarningKind,
+    /// The offending identifier, e.g. `getTotal` in `{{ getTotal() }}`, or
+    /// the iterated collection of an `*ngFor`
+    pub identifier: String,
+    /// The bound expression
+    pub expression: String,
+    /// 1-based line of the binding
+    pub line: u32,
+    /// 1-based column of the binding
+    pub column: u32,
+}
+
+/// Template helpers that are cheap to call
+const BUILTIN_FUNCTIONS: &[&str] = &["$any"];
+
+/// Items from which an array literal iterated by `*ngFor` is reported
+pub const LARGE_STATIC_LIST_ITEMS: usize = 20;
+
+/// Report change-detection anti-patterns in a template
+///
+/// Calls in interpolations, property bindings and structural directives
+/// run on every change detection pass; event bindings only run when the
+/// event fires and are not reported. Signal reads (`count()`) are calls
+/// too; they are cheap, but cannot be told apart from the template alone.
+///
+/// `*ngFor` loops are also checked for a `trackBy` function and for long
+/// hard-coded lists. `<ng-template ngFor [ngForOf]>` is not checked.
+pub fn analyze_bindings(content: &str) -> Vec<TemplateWarning> {
+    let mut warnings = Vec::new();
+
+    for binding in parse_bindings(content) {
+        if binding.kind == BindingKind::Event {
+            continue;
+        }
+        let warning = |kind, identifier: &str| TemplateWarning {
+            kind,
+            identifier: identifier.to_string(),
+            expression: binding.expression.clone(),
+            line: binding.line,
+            column: binding.column,
+        };
+
+        for identifier in called_functions(&binding.expression) {
+            warnings.push(warning(WarningKind::FunctionCall, identifier));
+        }
+
+        if binding.kind == BindingKind::Structural && binding.name.as_deref() == Some("ngFor") {
+            let collection = ng_for_collection(&binding.expression);
+            if !references(&binding.expression, "trackBy") {
+                warnings.push(warning(WarningKind::MissingTrackBy, collection));
+            }
+            if array_literal_len(collection).is_some_and(|len| len >= LARGE_STATIC_LIST_ITEMS) {
+                warnings.push(warning(WarningKind::LargeStaticList, collection));
+            }
+        }
+    }
+
+    warnings
+}
+
+/// The iterated collection of an `*ngFor` expression, e.g. `items` in
+/// `let item of items; trackBy: byId`
+fn ng_for_collection(expression: &str) -> &str {
+    let Some(of) = identifiers(expression)
+        .into_iter()
+        .find(|identifier| identifier.name == "of")
+    else {
+        return "";
+    };
+
+    let rest = &expression[of.offset + 2..];
+    let mut depth = 0i32;
+    let end = rest
+        .char_indices()
+        .find(|&(_, c)| {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {}
+            }
+            depth == 0 && c == ';'
+        })
+        .map_or(rest.len(), |(offset, _)| offset);
+
+    // The microsyntax also allows whitespace before the next key
+    let collection = rest[..end].trim();
+    collection
+        .split_once(" trackBy")
+        .or_else(|| collection.split_once(" let "))
+        .map_or(collection, |(collection, _)| collection.trim())
+}
+
+/// Number of items of an array literal, or `None` for other expressions
+fn array_literal_len(expression: &str) -> Option<usize> {
+    let inner = expression.strip_prefix('[')?.strip_suffix(']')?;
+    if inner.trim().is_empty() {
+        return Some(0);
+    }
+
+    let bytes = inner.as_bytes();
+    let mut items = 1;
+    let mut depth = 0;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\'' | b'"' | b'`' => {
+                pos = skip_quoted(bytes, pos);
+                continue;
+            }
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b',' if depth == 0 => items += 1,
+            _ => {}
+        }
+        pos += 1;
+    }
+    // A trailing comma does not start another item
+    if inner.trim_end().ends_with(',') {
+        items -= 1;
+    }
+
+    Some(items)
+}
+
+/// Names of the functions called in an expression, e.g. `format` in
+/// `format(user.born)`
+fn called_functions(expression: &str) -> Vec<&str> {
+    identifiers(expression)
+        .into_iter()
+        .filter(|identifier| {
+            let after = &expression[identifier.offset + identifier.name.len()..];
+            after.trim_start().starts_with('(') && !BUILTIN_FUNCTIONS.contains(&identifier.name)
+        })
+        .map(|identifier| identifier.name)
+        .collect()
+}
+
+/// Check whether an expression references a component member by name
+///
+/// Members of other objects (`user.save()`) and names inside string
+/// literals do not count; `this.save()` does.
+pub fn references(expression: &str, name: &str) -> bool {
+    identifiers(expression)
+        .iter()
+        .any(|identifier| identifier.name == name && identifier.is_component_member)
+}
+
+/// An identifier of a template expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Identifier<'a> {
+    pub name: &'a str,
+    /// Byte offset of the identifier in the expression
+    pub offset: usize,
+    /// Not accessed on another object (`user.name`), except `this`
+    pub is_component_member: bool,
+}
+
+/// Split an expression into identifiers, skipping string literals
+pub fn identifiers(expression: &str) -> Vec<Identifier<'_>> {
+    let bytes = expression.as_bytes();
+    let mut identifiers = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let byte = bytes[pos];
+        if byte == b'\'' || byte == b'"' || byte == b'`' {
+            pos = skip_quoted(bytes, pos);
+            continue;
+        }
+        if !is_identifier_start(byte) || (pos > 0 && is_identifier_part(bytes[pos - 1])) {
+            pos += 1;
+            continue;
+        }
+
+        let start = pos;
+        while pos < bytes.len() && is_identifier_part(bytes[pos]) {
+            pos += 1;
+        }
+        let before = expression[..start].trim_end();
+        let is_component_member = match before.strip_suffix('.') {
+            Some(object) => {
+                let object = object.strip_suffix('?').unwrap_or(object).trim_end();
+                object.ends_with("this")
+                    && !object[..object.len() - 4]
+                        .trim_end()
+                        .ends_with(|c: char| c.is_ascii_alphanumeric() || "_$.".contains(c))
+            }
+            None => true,
+        };
+        identifiers.push(Identifier {
+            name: &expression[start..pos],
+            offset: start,
+            is_component_member,
+        });
+    }
+
+    identifiers
+}
+
+fn is_identifier_start(byte: u8) -> bool {
+    byte.is_ascii_alphabetic() || byte == b'_' || byte == b'$'
+}
+
+fn is_identifier_part(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$'
+}
+
+/// Offset just past the string literal starting at `pos`
+fn skip_quoted(bytes: &[u8], pos: usize) -> usize {
+    let quote = bytes[pos];
+    let mut end = pos + 1;
+    while end < bytes.len() && bytes[end] != quote {
+        end += if bytes[end] == b'\\' { 2 } else { 1 };
+    }
+    (end + 1).min(bytes.len())
+}
+
+struct Scanner<'a> {
+    content: &'a str,
+    bytes: &'a [u8],
+    /// Byte offsets of the start of every line
+    line_starts: Vec<usize>,
+    bindings: Vec<TemplateBinding>,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(content: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+
+        Self {
+            content,
+            bytes: content.as_bytes(),
+            line_starts,
+            bindings: Vec::new(),
+        }
+    }
+
+    fn scan(&mut self) {
+        let mut pos = 0;
+        while pos < self.bytes.len() {
+            let rest = &self.bytes[pos..];
+            pos = if rest.starts_with(b"<!--") {
+                match self.find(pos + 4, "-->") {
+                    Some(end) => end + 3,
+                    None => return,
+                }
+            } else if rest.starts_with(b"{{") {
+                match self.find(pos + 2, "}}") {
+                    Some(end) => {
+                        self.push_interpolation(pos, end);
+                        end + 2
+                    }
+                    None => return,
+                }
+            } else if rest[0] == b'<' && rest.get(1).is_some_and(u8::is_ascii_alphabetic) {
+                self.scan_tag(pos + 1)
+            } else {
+                pos + 1
+            };
+        }
+    }
+
+    /// Scan the attributes of the tag whose name starts at `pos`
+    ///
+    /// # Returns
+    /// The offset just past the tag
+    fn scan_tag(&mut self, mut pos: usize) -> usize {
+        let len = self.bytes.len();
+        while pos < len
+            && !self.bytes[pos].is_ascii_whitespace()
+            && !b">/".contains(&self.bytes[pos])
+        {
+            pos += 1;
+        }
+
+        loop {
+            while pos < len && (self.bytes[pos].is_ascii_whitespace() || self.bytes[pos] == b'/') {
+                pos += 1;
+            }
+            if pos >= len {
+                return len;
+            }
+            if self.bytes[pos] == b'>' {
+                return pos + 1;
+            }
+
+            let name_start = pos;
+            while pos < len
+                && !self.bytes[pos].is_ascii_whitespace()
+                && !b"=>".contains(&self.bytes[pos])
+            {
+                pos += 1;
+            }
+            let name = &self.content[name_start..pos];
+
+            let mut value_pos = pos;
+            while value_pos < len && self.bytes[value_pos].is_ascii_whitespace() {
+                value_pos += 1;
+            }
+            if value_pos >= len || self.bytes[value_pos] != b'=' {
+                self.push_attribute(name, name_start, None);
+                continue;
+            }
+
+            value_pos += 1;
+            while value_pos < len && self.bytes[value_pos].is_ascii_whitespace() {
+                value_pos += 1;
+            }
+            let (value_start, value_end, next) = match self.bytes.get(value_pos) {
+                Some(&quote @ (b'"' | b'\'')) => {
+                    let end = self.bytes[value_pos + 1..]
+                        .iter()
+                        .position(|&byte| byte == quote)
+                        .map_or(len, |offset| value_pos + 1 + offset);
+                    (value_pos + 1, end, (end + 1).min(len))
+                }
+                _ => {
+                    let end = self.bytes[value_pos..]
+                        .iter()
+                        .position(|byte| byte.is_ascii_whitespace() || *byte == b'>')
+                        .map_or(len, |offset| value_pos + offset);
+                    (value_pos, end, end)
+                }
+            };
+            self.push_attribute(name, name_start, Some((value_start, value_end)));
+            pos = next;
+        }
+    }
+
+    fn push_attribute(&mut self, name: &str, offset: usize, value: Option<(usize, usize)>) {
+        let (kind, bound_name) = if let Some(inner) = strip_delimiters(name, "[(", ")]") {
+            (BindingKind::TwoWay, inner)
+        } else if let Some(inner) = name.strip_prefix("bindon-") {
+            (BindingKind::TwoWay, inner)
+        } else if let Some(inner) = strip_delimiters(name, "(", ")") {
+            (BindingKind::Event, inner)
+        } else if let Some(inner) = name.strip_prefix("on-") {
+            (BindingKind::Event, inner)
+        } else if let Some(inner) = strip_delimiters(name, "[", "]") {
+            (BindingKind::Property, inner)
+        } else if let Some(inner) = name.strip_prefix("bind-") {
+            (BindingKind::Property, inner)
+        } else if let Some(inner) = name.strip_prefix('*') {
+            (BindingKind::Structural, inner)
+        } else {
+            // Plain attribute: only interpolations in its value are bound
+            if let Some((start, end)) = value {
+                self.scan_interpolations(start, end);
+            }
+            return;
+        };
+
+        let expression = value.map_or("", |(start, end)| &self.content[start..end]);
+        let (line, column) = self.position(offset);
+        self.bindings.push(TemplateBinding {
+            kind,
+            name: Some(bound_name.to_string()),
+            expression: expression.trim().to_string(),
+            line,
+            column,
+        });
+    }
+
+    fn scan_interpolations(&mut self, mut pos: usize, end: usize) {
+        while let Some(start) = self.find(pos, "{{").filter(|&start| start < end) {
+            let Some(close) = self.find(start + 2, "}}").filter(|&close| close < end) else {
+                return;
+            };
+            self.push_interpolation(start, close);
+            pos = close + 2;
+        }
+    }
+
+    /// Record the interpolation opened at `start` and closed at `end`
+    fn push_interpolation(&mut self, start: usize, end: usize) {
+        let (line, column) = self.position(start);
+        self.bindings.push(TemplateBinding {
+            kind: BindingKind::Interpolation,
+            name: None,
+            expression: self.content[start + 2..end].trim().to_string(),
+            line,
+            column,
+        });
+    }
+
+    fn find(&self, from: usize, needle: &str) -> Option<usize> {
+        self.content
+            .get(from..)?
+            .find(needle)
+            .map(|offset| from + offset)
+    }
+
+    /// 1-based line and column of a byte offset
+    fn position(&self, offset: usize) -> (u32, u32) {
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self.content[line_start..offset].chars().count() + 1;
+        (line as u32, column as u32)
+    }
+}
+
+/// Strip an opening and a closing delimiter, e.g. `(click)` into `click`
+fn strip_delimiters<'n>(name: &'n str, open: &str, close: &str) -> Option<&'n str> {
+    name.strip_prefix(open)?.strip_suffix(close)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = r#"<!-- {{ ignored() }} -->
+<button (click)="save($event)" [disabled]="!canSave()">Save</button>
+<input [(ngModel)]="name" title="Hi {{ user.name }}">
+<li *ngFor="let item of items">{{ format(item) }}</li>
+<span on-mouseenter='highlight()'>{{ total }}</span>
+"#;
+
+    #[test]
+    fn test_parse_bindings() {
+        let bindings: Vec<_> = parse_bindings(TEMPLATE)
+            .into_iter()
+            .map(|b| (b.kind, b.name, b.expression, b.line, b.column))
+            .collect();
+        let name = |name: &str| Some(name.to_string());
+
+        assert_eq!(
+            bindings,
+            vec![
+                (
+                    BindingKind::Event,
+                    name("click"),
+                    "save($event)".to_string(),
+                    2,
+                    9
+                ),
+                (
+                    BindingKind::Property,
+                    name("disabled"),
+                    "!canSave()".to_string(),
+                    2,
+                    32
+                ),
+                (
+                    BindingKind::TwoWay,
+                    name("ngModel"),
+                    "name".to_string(),
+                    3,
+                    8
+                ),
+                (
+                    BindingKind::Interpolation,
+                    None,
+                    "user.name".to_string(),
+                    3,
+                    37
+                ),
+                (
+                    BindingKind::Structural,
+                    name("ngFor"),
+                    "let item of items".to_string(),
+                    4,
+                    5
+                ),
+                (
+                    BindingKind::Interpolation,
+                    None,
+                    "format(item)".to_string(),
+                    4,
+                    32
+                ),
+                (
+                    BindingKind::Event,
+                    name("mouseenter"),
+                    "highlight()".to_string(),
+                    5,
+                    7
+                ),
+                (BindingKind::Interpolation, None, "total".to_string(), 5, 35),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_method_bindings() {
+        let lines = |method| -> Vec<u32> {
+            find_method_bindings(TEMPLATE, method)
+                .iter()
+                .map(|binding| binding.line)
+                .collect()
+        };
+
+        assert_eq!(lines("save"), vec![2]);
+        assert_eq!(lines("canSave"), vec![2]);
+        assert_eq!(lines("format"), vec![4]);
+        assert_eq!(lines("ignored"), Vec::<u32>::new());
+        // `user.name` is not the component's `name`
+        assert_eq!(lines("name"), vec![3]);
+    }
+
+    #[test]
+    fn test_references() {
+        assert!(references("this.save()", "save"));
+        assert!(references("a && save(b)", "save"));
+        assert!(!references("user.save()", "save"));
+        assert!(!references("'save()'", "save"));
+        assert!(!references("saveAll()", "save"));
+        assert!(!references("other.this.save()", "save"));
+    }
+
+    #[test]
+    fn test_analyze_bindings() {
+        let template = r#"<h1 [title]="getTitle()">{{ user.fullName() }} {{ name }}</h1>
+<button (click)="save()" [class.active]="$any(tab).active">{{ 'load()' }}</button>
+<li *ngIf="isVisible (item)">{{ items | slice:0:5 }}</li>
+"#;
+
+        let warnings: Vec<_> = analyze_bindings(template)
+            .into_iter()
+            .map(|w| (w.kind, w.identifier, w.line))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                (WarningKind::FunctionCall, "getTitle".to_string(), 1),
+                (WarningKind::FunctionCall, "fullName".to_string(), 1),
+                (WarningKind::FunctionCall, "isVisible".to_string(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ng_for_warnings() {
+        let numbers: Vec<String> = (1..=LARGE_STATIC_LIST_ITEMS)
+            .map(|n| n.to_string())
+            .collect();
+        let template = format!(
+            r#"<li *ngFor="let user of users">{{{{ user.name }}}}</li>
+<li *ngFor="let user of users; trackBy: byId">{{{{ user.name }}}}</li>
+<li *ngFor="let n of [{}]; let i = index">{{{{ n }}}}</li>
+"#,
+            numbers.join(", ")
+        );
+
+        let warnings: Vec<_> = analyze_bindings(&template)
+            .into_iter()
+            .map(|w| (w.kind, w.line))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                (WarningKind::MissingTrackBy, 1),
+                (WarningKind::MissingTrackBy, 3),
+                (WarningKind::LargeStaticList, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ng_for_collection() {
+        assert_eq!(ng_for_collection("let item of items"), "items");
+        assert_eq!(
+            ng_for_collection("let item of items | async; trackBy: byId"),
+            "items | async"
+        );
+        assert_eq!(
+            ng_for_collection("let item of items trackBy: byId"),
+            "items"
+        );
+        assert_eq!(
+            ng_for_collection("let x of [1, 2]; let i = index"),
+            "[1, 2]"
+        );
+        assert_eq!(array_literal_len("[1, [2, 3], 'a,b',]"), Some(3));
+        assert_eq!(array_literal_len("items"), None);
+    }
+}
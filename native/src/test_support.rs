@@ -0,0 +1,35 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Scratch workspace removed when dropped, even by a failing test
+pub struct TempWorkspace(PathBuf);
+
+impl std::ops::Deref for TempWorkspace {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempWorkspace {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempWorkspace {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Create an empty scratch workspace, replacing any left by an earlier run
+///
+/// `name` must be unique across the crate's tests, which run in parallel.
+pub fn temp_workspace(name: &str) -> TempWorkspace {
+    let dir = std::env::temp_dir().join(format!("xray-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    TempWorkspace(dir)
+}
@@ -0,0 +1,258 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of `extends` hops followed when loading a tsconfig
+const MAX_EXTENDS_DEPTH: usize = 8;
+
+/// Root configs tried in order; Nx workspaces only have the base one
+const CONFIG_FILES: &[&str] = &["tsconfig.json", "tsconfig.base.json"];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTsConfig {
+    extends: Option<String>,
+    #[serde(default)]
+    compiler_options: RawCompilerOptions,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCompilerOptions {
+    base_url: Option<String>,
+    paths: Option<BTreeMap<String, Vec<String>>>,
+}
+
+/// Path mapping configuration read from `tsconfig.json`
+#[derive(Debug, Default)]
+pub struct TsConfig {
+    /// `baseUrl`, resolved against the config declaring it
+    base_url: Option<PathBuf>,
+    /// Directory of the config declaring `paths`, which targets are
+    /// resolved against when there is no `baseUrl`
+    paths_dir: PathBuf,
+    /// Alias patterns (e.g. `@shared/*`) and their target patterns
+    paths: Vec<(String, Vec<String>)>,
+}
+
+impl TsConfig {
+    /// Load `tsconfig.json` (or `tsconfig.base.json`) from the workspace
+    /// root, following `extends`
+    pub fn load<P: AsRef<Path>>(workspace_path: P) -> Result<Self, String> {
+        let workspace_path = workspace_path.as_ref();
+        let config_path = CONFIG_FILES
+            .iter()
+            .map(|name| workspace_path.join(name))
+            .find(|path| path.is_file())
+            .ok_or_else(|| format!("No tsconfig in {}", workspace_path.display()))?;
+        let mut config = TsConfig::default();
+        Self::load_into(&config_path, &mut config, 0)?;
+        Ok(config)
+    }
+
+    fn load_into(config_path: &Path, config: &mut TsConfig, depth: usize) -> Result<(), String> {
+        let content = fs::read_to_string(config_path)
+            .map_err(|e| format!("Cannot read {}: {}", config_path.display(), e))?;
+        let raw: RawTsConfig = serde_json::from_str(&strip_json_comments(&content))
+            .map_err(|e| format!("Invalid {}: {}", config_path.display(), e))?;
+
+        let config_dir = config_path.parent().unwrap_or(Path::new(".")).to_path_buf();
+
+        // Parent configs are applied first so the child's settings win
+        if let Some(extends) = &raw.extends {
+            if depth < MAX_EXTENDS_DEPTH && extends.starts_with('.') {
+                let mut parent = config_dir.join(extends);
+                if !extends.ends_with(".json") {
+                    parent = PathBuf::from(format!("{}.json", parent.display()));
+                }
+                Self::load_into(&parent, config, depth + 1)?;
+            }
+        }
+
+        if let Some(base_url) = &raw.compiler_options.base_url {
+            config.base_url = Some(config_dir.join(base_url));
+        }
+
+        if let Some(paths) = raw.compiler_options.paths {
+            config.paths_dir = config_dir;
+            config.paths = paths.into_iter().collect();
+            // Longest prefix wins, like the TypeScript compiler
+            config
+                .paths
+                .sort_by_key(|(pattern, _)| std::cmp::Reverse(pattern.len()));
+        }
+
+        Ok(())
+    }
+
+    /// Resolve an aliased import path to candidate filesystem paths
+    ///
+    /// Each candidate is the substituted target without an extension; it may
+    /// name a file (`foo.ts`), a directory, or a barrel (`foo/index.ts`).
+    pub fn resolve(&self, import_path: &str) -> Vec<PathBuf> {
+        for (pattern, targets) in &self.paths {
+            let captured = match pattern.split_once('*') {
+                Some((prefix, suffix)) => import_path
+                    .strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_suffix(suffix)),
+                None if pattern == import_path => Some(""),
+                None => None,
+            };
+
+            if let Some(captured) = captured {
+                let base_dir = self.base_url.as_deref().unwrap_or(&self.paths_dir);
+                return targets
+                    .iter()
+                    .map(|target| base_dir.join(target.replacen('*', captured, 1)))
+                    .collect();
+            }
+        }
+
+        Vec::new()
+    }
+}
+
+/// Strip `//` and `/* */` comments and trailing commas from JSONC content
+pub fn strip_json_comments(content: &str) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    output.push(escaped);
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                output.push(c);
+            }
+            ('/', Some('/')) => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        output.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = '\0';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            (',', _) => {
+                // Drop the comma if the next significant character closes a scope
+                let next_significant = chars.clone().find(|next| !next.is_whitespace());
+                if !matches!(next_significant, Some('}') | Some(']')) {
+                    output.push(c);
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_workspace;
+
+    #[test]
+    fn test_strip_json_comments() {
+        let content = r#"{
+  // line comment
+  "a": "http://example.com", /* block */
+  "b": [1, 2,],
+}"#;
+        let parsed: serde_json::Value =
+            serde_json::from_str(&strip_json_comments(content)).unwrap();
+        assert_eq!(parsed["a"], "http://example.com");
+        assert_eq!(parsed["b"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_resolve_wildcard_alias() {
+        let config = TsConfig {
+            base_url: Some(PathBuf::from("/ws")),
+            paths_dir: PathBuf::from("/ws/config"),
+            paths: vec![
+                (
+                    "@shared/*".to_string(),
+                    vec!["src/app/shared/*".to_string()],
+                ),
+                ("@env".to_string(), vec!["src/environments/env".to_string()]),
+            ],
+        };
+
+        assert_eq!(
+            config.resolve("@shared/services/foo.service"),
+            vec![PathBuf::from("/ws/src/app/shared/services/foo.service")]
+        );
+        assert_eq!(
+            config.resolve("@env"),
+            vec![PathBuf::from("/ws/src/environments/env")]
+        );
+        assert!(config.resolve("@other/thing").is_empty());
+    }
+
+    #[test]
+    fn test_load() {
+        let workspace = temp_workspace("tsconfig");
+        assert!(TsConfig::load(&workspace).is_err());
+
+        // Nx workspaces only have tsconfig.base.json
+        fs::write(
+            workspace.join("tsconfig.base.json"),
+            r#"{ "compilerOptions": { "paths": { "@shared/*": ["libs/shared/*"] } } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            TsConfig::load(&workspace).unwrap().resolve("@shared/ui"),
+            vec![workspace.join("libs/shared/ui")]
+        );
+
+        // Without baseUrl, targets are relative to the config declaring paths
+        fs::create_dir_all(workspace.join("configs")).unwrap();
+        fs::write(workspace.join("configs/base.json"), "{}").unwrap();
+        fs::write(
+            workspace.join("tsconfig.json"),
+            r#"{
+  "extends": "./configs/base.json",
+  "compilerOptions": { "paths": { "@app/*": ["src/app/*"] } }
+}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            TsConfig::load(&workspace).unwrap().resolve("@app/core"),
+            vec![workspace.join("src/app/core")]
+        );
+
+        // An inherited baseUrl still applies
+        fs::write(
+            workspace.join("configs/base.json"),
+            r#"{ "compilerOptions": { "baseUrl": ".." } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            TsConfig::load(&workspace).unwrap().resolve("@app/core"),
+            vec![workspace.join("configs/../src/app/core")]
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use swc_ecma_ast::*;
+
+/// The body of a function-like class member
+#[derive(Debug, Clone, Copy)]
+pub enum FunctionBody<'a> {
+    Block(&'a BlockStmt),
+    /// Concise arrow body, e.g. `onSave = () => this.save()`
+    Expr(&'a Expr),
+}
+
+impl<'a> FunctionBody<'a> {
+    /// Body of an arrow or function expression, looking through parentheses
+    pub fn of_value(value: &'a Expr) -> Option<Self> {
+        match value {
+            Expr::Arrow(arrow) => Some(Self::of_arrow(&arrow.body)),
+            Expr::Fn(fn_expr) => fn_expr.function.body.as_ref().map(Self::Block),
+            Expr::Paren(paren) => Self::of_value(&paren.expr),
+            _ => None,
+        }
+    }
+
+    fn of_arrow(body: &'a BlockStmtOrExpr) -> Self {
+        match body {
+            BlockStmtOrExpr::BlockStmt(block) => Self::Block(block),
+            BlockStmtOrExpr::Expr(expr) => Self::Expr(expr),
+        }
+    }
+}
+
+/// Callbacks of [`walk_body`], called before the children are walked
+///
+/// `depth` counts the control-flow statements (`if`, loops, `switch`,
+/// `try`) enclosing the node; an `else if` does not nest deeper than its
+/// `if`. Nested functions and callbacks are walked as part of the body,
+/// nested classes are not.
+pub trait Visitor<'a> {
+    fn visit_stmt(&mut self, _stmt: &'a Stmt, _depth: usize) {}
+    fn visit_expr(&mut self, _expr: &'a Expr, _depth: usize) {}
+}
+
+/// Walk every statement and expression of a function body
+pub fn walk_body<'a>(body: FunctionBody<'a>, visitor: &mut impl Visitor<'a>) {
+    match body {
+        FunctionBody::Block(block) => walk_stmts(&block.stmts, visitor, 0),
+        FunctionBody::Expr(expr) => walk_expr(expr, visitor, 0),
+    }
+}
+
+fn walk_stmts<'a>(stmts: &'a [Stmt], visitor: &mut impl Visitor<'a>, depth: usize) {
+    for stmt in stmts {
+        walk_stmt(stmt, visitor, depth);
+    }
+}
+
+fn walk_stmt<'a>(stmt: &'a Stmt, visitor: &mut impl Visitor<'a>, depth: usize) {
+    visitor.visit_stmt(stmt, depth);
+
+    match stmt {
+        Stmt::Block(block) => walk_stmts(&block.stmts, visitor, depth),
+        Stmt::With(with) => {
+            walk_expr(&with.obj, visitor, depth);
+            walk_stmt(&with.body, visitor, depth + 1);
+        }
+        Stmt::Return(ret) => walk_opt_expr(ret.arg.as_deref(), visitor, depth),
+        Stmt::Labeled(labeled) => walk_stmt(&labeled.body, visitor, depth),
+        Stmt::If(if_stmt) => {
+            walk_expr(&if_stmt.test, visitor, depth);
+            walk_stmt(&if_stmt.cons, visitor, depth + 1);
+            match if_stmt.alt.as_deref() {
+                Some(alt @ Stmt::If(_)) => walk_stmt(alt, visitor, depth),
+                Some(alt) => walk_stmt(alt, visitor, depth + 1),
+                None => {}
+            }
+        }
+        Stmt::Switch(switch) => {
+            walk_expr(&switch.discriminant, visitor, depth);
+            for case in &switch.cases {
+                walk_opt_expr(case.test.as_deref(), visitor, depth);
+                walk_stmts(&case.cons, visitor, depth + 1);
+            }
+        }
+        Stmt::Throw(throw) => walk_expr(&throw.arg, visitor, depth),
+        Stmt::Try(try_stmt) => {
+            walk_stmts(&try_stmt.block.stmts, visitor, depth + 1);
+            if let Some(handler) = &try_stmt.handler {
+                walk_stmts(&handler.body.stmts, visitor, depth + 1);
+            }
+            if let Some(finalizer) = &try_stmt.finalizer {
+                walk_stmts(&finalizer.stmts, visitor, depth + 1);
+            }
+        }
+        Stmt::While(while_stmt) => {
+            walk_expr(&while_stmt.test, visitor, depth);
+            walk_stmt(&while_stmt.body, visitor, depth + 1);
+        }
+        Stmt::DoWhile(do_while) => {
+            walk_stmt(&do_while.body, visitor, depth + 1);
+            walk_expr(&do_while.test, visitor, depth);
+        }
+        Stmt::For(for_stmt) => {
+            match &for_stmt.init {
+                Some(VarDeclOrExpr::VarDecl(var)) => walk_var_decl(var, visitor, depth),
+                Some(VarDeclOrExpr::Expr(expr)) => walk_expr(expr, visitor, depth),
+                None => {}
+            }
+            walk_opt_expr(for_stmt.test.as_deref(), visitor, depth);
+            walk_opt_expr(for_stmt.update.as_deref(), visitor, depth);
+            walk_stmt(&for_stmt.body, visitor, depth + 1);
+        }
+        Stmt::ForIn(for_in) => {
+            walk_expr(&for_in.right, visitor, depth);
+            walk_stmt(&for_in.body, visitor, depth + 1);
+        }
+        Stmt::ForOf(for_of) => {
+            walk_expr(&for_of.right, visitor, depth);
+            walk_stmt(&for_of.body, visitor, depth + 1);
+        }
+        Stmt::Decl(Decl::Var(var)) => walk_var_decl(var, visitor, depth),
+        Stmt::Decl(Decl::Using(using)) => {
+            for decl in &using.decls {
+                walk_opt_expr(decl.init.as_deref(), visitor, depth);
+            }
+        }
+        Stmt::Decl(Decl::Fn(fn_decl)) => walk_function(&fn_decl.function, visitor, depth),
+        Stmt::Expr(expr) => walk_expr(&expr.expr, visitor, depth),
+        _ => {}
+    }
+}
+
+fn walk_var_decl<'a>(var: &'a VarDecl, visitor: &mut impl Visitor<'a>, depth: usize) {
+    for decl in &var.decls {
+        walk_opt_expr(decl.init.as_deref(), visitor, depth);
+    }
+}
+
+fn walk_function<'a>(function: &'a Function, visitor: &mut impl Visitor<'a>, depth: usize) {
+    if let Some(body) = &function.body {
+        walk_stmts(&body.stmts, visitor, depth);
+    }
+}
+
+fn walk_opt_expr<'a>(expr: Option<&'a Expr>, visitor: &mut impl Visitor<'a>, depth: usize) {
+    if let Some(expr) = expr {
+        walk_expr(expr, visitor, depth);
+    }
+}
+
+fn walk_args<'a>(args: &'a [ExprOrSpread], visitor: &mut impl Visitor<'a>, depth: usize) {
+    for arg in args {
+        walk_expr(&arg.expr, visitor, depth);
+    }
+}
+
+fn walk_expr<'a>(expr: &'a Expr, visitor: &mut impl Visitor<'a>, depth: usize) {
+    visitor.visit_expr(expr, depth);
+
+    match expr {
+        Expr::Array(array) => {
+            for elem in array.elems.iter().flatten() {
+                walk_expr(&elem.expr, visitor, depth);
+            }
+        }
+        Expr::Object(object) => {
+            for prop in &object.props {
+                match prop {
+                    PropOrSpread::Spread(spread) => walk_expr(&spread.expr, visitor, depth),
+                    PropOrSpread::Prop(prop) => walk_prop(prop, visitor, depth),
+                }
+            }
+        }
+        Expr::Fn(fn_expr) => walk_function(&fn_expr.function, visitor, depth),
+        Expr::Arrow(arrow) => match &*arrow.body {
+            BlockStmtOrExpr::BlockStmt(block) => walk_stmts(&block.stmts, visitor, depth),
+            BlockStmtOrExpr::Expr(body) => walk_expr(body, visitor, depth),
+        },
+        Expr::Unary(unary) => walk_expr(&unary.arg, visitor, depth),
+        Expr::Update(update) => walk_expr(&update.arg, visitor, depth),
+        Expr::Bin(bin) => {
+            walk_expr(&bin.left, visitor, depth);
+            walk_expr(&bin.right, visitor, depth);
+        }
+        Expr::Assign(assign) => {
+            if let AssignTarget::Simple(SimpleAssignTarget::Member(member)) = &assign.left {
+                walk_member(member, visitor, depth);
+            }
+            walk_expr(&assign.right, visitor, depth);
+        }
+        Expr::Member(member) => walk_member(member, visitor, depth),
+        Expr::SuperProp(super_prop) => {
+            if let SuperProp::Computed(computed) = &super_prop.prop {
+                walk_expr(&computed.expr, visitor, depth);
+            }
+        }
+        Expr::Cond(cond) => {
+            walk_expr(&cond.test, visitor, depth);
+            walk_expr(&cond.cons, visitor, depth);
+            walk_expr(&cond.alt, visitor, depth);
+        }
+        Expr::Call(call) => {
+            if let Callee::Expr(callee) = &call.callee {
+                walk_expr(callee, visitor, depth);
+            }
+            walk_args(&call.args, visitor, depth);
+        }
+        Expr::New(new) => {
+            walk_expr(&new.callee, visitor, depth);
+            if let Some(args) = &new.args {
+                walk_args(args, visitor, depth);
+            }
+        }
+        Expr::Seq(seq) => {
+            for expr in &seq.exprs {
+                walk_expr(expr, visitor, depth);
+            }
+        }
+        Expr::Tpl(tpl) => {
+            for expr in &tpl.exprs {
+                walk_expr(expr, visitor, depth);
+            }
+        }
+        Expr::TaggedTpl(tagged) => {
+            walk_expr(&tagged.tag, visitor, depth);
+            for expr in &tagged.tpl.exprs {
+                walk_expr(expr, visitor, depth);
+            }
+        }
+        Expr::Yield(yield_expr) => walk_opt_expr(yield_expr.arg.as_deref(), visitor, depth),
+        Expr::Await(await_expr) => walk_expr(&await_expr.arg, visitor, depth),
+        Expr::Paren(paren) => walk_expr(&paren.expr, visitor, depth),
+        Expr::TsTypeAssertion(assertion) => walk_expr(&assertion.expr, visitor, depth),
+        Expr::TsConstAssertion(assertion) => walk_expr(&assertion.expr, visitor, depth),
+        Expr::TsNonNull(non_null) => walk_expr(&non_null.expr, visitor, depth),
+        Expr::TsAs(as_expr) => walk_expr(&as_expr.expr, visitor, depth),
+        Expr::TsInstantiation(instantiation) => walk_expr(&instantiation.expr, visitor, depth),
+        Expr::TsSatisfies(satisfies) => walk_expr(&satisfies.expr, visitor, depth),
+        Expr::OptChain(opt_chain) => match &*opt_chain.base {
+            OptChainBase::Member(member) => walk_member(member, visitor, depth),
+            OptChainBase::Call(call) => {
+                walk_expr(&call.callee, visitor, depth);
+                walk_args(&call.args, visitor, depth);
+            }
+        },
+        _ => {}
+    }
+}
+
+fn walk_member<'a>(member: &'a MemberExpr, visitor: &mut impl Visitor<'a>, depth: usize) {
+    walk_expr(&member.obj, visitor, depth);
+    if let MemberProp::Computed(computed) = &member.prop {
+        walk_expr(&computed.expr, visitor, depth);
+    }
+}
+
+fn walk_prop<'a>(prop: &'a Prop, visitor: &mut impl Visitor<'a>, depth: usize) {
+    match prop {
+        Prop::KeyValue(key_value) => walk_expr(&key_value.value, visitor, depth),
+        Prop::Assign(assign) => walk_expr(&assign.value, visitor, depth),
+        Prop::Getter(getter) => {
+            if let Some(body) = &getter.body {
+                walk_stmts(&body.stmts, visitor, depth);
+            }
+        }
+        Prop::Setter(setter) => {
+            if let Some(body) = &setter.body {
+                walk_stmts(&body.stmts, visitor, depth);
+            }
+        }
+        Prop::Method(method) => walk_function(&method.function, visitor, depth),
+        Prop::Shorthand(_) => {}
+    }
+}
@@ -0,0 +1,125 @@
+use crate::file_locator::{FileLocator, LocatorConfig};
+use std::collections::VecDeque;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Number of resolved classes remembered by default
+pub const DEFAULT_RECENT_CAPACITY: usize = 64;
+
+/// Recently resolved classes and their files, most recently used first
+#[derive(Debug)]
+pub struct RecentResults {
+    capacity: usize,
+    entries: VecDeque<(String, PathBuf)>,
+}
+
+impl RecentResults {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Remember a resolved class, evicting the least recently used one
+    pub fn record(&mut self, class_name: &str, path: PathBuf) {
+        self.entries.retain(|(name, _)| name != class_name);
+        self.entries.push_front((class_name.to_string(), path));
+        self.entries.truncate(self.capacity);
+    }
+
+    /// Directories of previous hits, the searched class's own first, then
+    /// most recently used first, without duplicates
+    pub fn directories(&self, class_name: &str) -> Vec<PathBuf> {
+        let own = self.entries.iter().filter(|(name, _)| name == class_name);
+        let others = self.entries.iter().filter(|(name, _)| name != class_name);
+
+        let mut directories: Vec<PathBuf> = Vec::new();
+        for (_, path) in own.chain(others) {
+            if let Some(directory) = path.parent() {
+                if !directories.iter().any(|d| d == directory) {
+                    directories.push(directory.to_path_buf());
+                }
+            }
+        }
+        directories
+    }
+}
+
+/// Persistent locator that searches near previous hits before walking
+///
+/// Classes of one feature tend to live side by side, so the directories of
+/// recent results and their siblings are tried first; a miss there falls
+/// back to the indexed full search.
+pub struct WarmLocator {
+    workspace_path: PathBuf,
+    config: LocatorConfig,
+    recent: RecentResults,
+}
+
+impl WarmLocator {
+    pub fn new<P: AsRef<Path>>(workspace_path: P, config: LocatorConfig, capacity: usize) -> Self {
+        Self {
+            workspace_path: workspace_path.as_ref().to_path_buf(),
+            config,
+            recent: RecentResults::new(capacity),
+        }
+    }
+
+    /// Locate a class, returning the locator that ran the search so callers
+    /// can read its statistics
+    pub fn lookup(&mut self, class_name: &str) -> io::Result<(Option<String>, FileLocator)> {
+        let locator = FileLocator::new(&self.workspace_path).with_config(self.config.clone());
+
+        let directories = self.recent.directories(class_name);
+        let mut path = locator.find_class_near(class_name, &directories)?;
+        if path.is_none() {
+            path = locator.find_class(class_name)?;
+        }
+
+        if let Some(path) = &path {
+            self.recent.record(class_name, PathBuf::from(path));
+        }
+
+        Ok((path, locator))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_workspace;
+    use std::fs;
+
+    #[test]
+    fn test_recent_results() {
+        let mut recent = RecentResults::new(2);
+        recent.record("A", PathBuf::from("/ws/a/a.ts"));
+        recent.record("B", PathBuf::from("/ws/b/b.ts"));
+        recent.record("C", PathBuf::from("/ws/a/c.ts"));
+
+        // A was evicted; C's directory comes first as the most recent
+        assert_eq!(
+            recent.directories("X"),
+            vec![PathBuf::from("/ws/a"), PathBuf::from("/ws/b")]
+        );
+        assert_eq!(recent.directories("B")[0], PathBuf::from("/ws/b"));
+    }
+
+    #[test]
+    fn test_near_hits_match_exact_names() {
+        let workspace = temp_workspace("warm");
+        fs::create_dir_all(workspace.join("src/users")).unwrap();
+        fs::create_dir_all(workspace.join("src/models")).unwrap();
+        let service = workspace.join("src/users/user.service.ts");
+        let model = workspace.join("src/models/user.ts");
+        fs::write(&service, "export class UserService {}").unwrap();
+        fs::write(&model, "export class User {}").unwrap();
+
+        let mut locator = WarmLocator::new(&workspace, LocatorConfig::default(), 4);
+        let (path, _) = locator.lookup("UserService").unwrap();
+        assert_eq!(path.as_deref(), service.to_str());
+        let (path, _) = locator.lookup("User").unwrap();
+        assert_eq!(path.as_deref(), model.to_str());
+    }
+}
@@ -0,0 +1,392 @@
+use crate::class_index::extract_class_names;
+use crate::file_locator::FileLocator;
+use ignore::gitignore::Gitignore;
+use ignore::Match;
+use notify::event::ModifyKind;
+use notify::{recommended_watcher, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+/// Ignore files honored in every directory, later ones taking precedence
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore"];
+
+/// In-memory class index kept in sync with filesystem events
+#[derive(Debug, Default)]
+struct WatchState {
+    /// File path → classes declared in it
+    files: BTreeMap<String, Vec<String>>,
+    /// Class name → files declaring it, in path order
+    classes: HashMap<String, BTreeSet<String>>,
+}
+
+impl WatchState {
+    fn insert(&mut self, path: String, classes: Vec<String>) {
+        self.remove(&path);
+        for class_name in &classes {
+            self.classes
+                .entry(class_name.clone())
+                .or_default()
+                .insert(path.clone());
+        }
+        self.files.insert(path, classes);
+    }
+
+    fn remove(&mut self, path: &str) {
+        if let Some(classes) = self.files.remove(path) {
+            for class_name in classes {
+                if let Some(paths) = self.classes.get_mut(&class_name) {
+                    paths.remove(path);
+                    if paths.is_empty() {
+                        self.classes.remove(&class_name);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drop every file at or below a removed path (files or whole directories)
+    fn remove_tree(&mut self, root: &Path) {
+        let removed: Vec<String> = self
+            .files
+            .keys()
+            .filter(|path| Path::new(path.as_str()).starts_with(root))
+            .cloned()
+            .collect();
+        for path in removed {
+            self.remove(&path);
+        }
+    }
+
+    fn lookup(&self, class_name: &str) -> Option<&str> {
+        self.classes
+            .get(class_name)
+            .and_then(|paths| paths.iter().next())
+            .map(String::as_str)
+    }
+}
+
+/// Ignore files of the watched directories
+///
+/// Each file applies to its directory and below, the nearest one deciding,
+/// as in the walks of searches.
+#[derive(Default)]
+struct IgnoreFiles {
+    /// Directory → matchers of its ignore files
+    matchers: HashMap<PathBuf, Vec<Gitignore>>,
+}
+
+impl IgnoreFiles {
+    /// Load, or reload after a change, the ignore files of a directory
+    fn load(&mut self, dir: &Path) {
+        let matchers: Vec<Gitignore> = IGNORE_FILES
+            .iter()
+            .map(|name| dir.join(name))
+            .filter(|path| path.is_file())
+            .map(|path| Gitignore::new(path).0)
+            .collect();
+        if matchers.is_empty() {
+            self.matchers.remove(dir);
+        } else {
+            self.matchers.insert(dir.to_path_buf(), matchers);
+        }
+    }
+
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        for dir in path.ancestors().skip(1) {
+            let Some(matchers) = self.matchers.get(dir) else {
+                continue;
+            };
+            for matcher in matchers.iter().rev() {
+                match matcher.matched_path_or_any_parents(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+        }
+        false
+    }
+}
+
+/// Long-lived class locator that watches the workspace for changes
+///
+/// The class index is built once (reusing the persisted index) and then
+/// updated from create/modify/rename/delete events, so lookups never touch
+/// the filesystem.
+///
+/// Only the directories a search would enter are watched, each on its own,
+/// so skipped trees such as `node_modules` cost no watches; directories
+/// that appear later are watched as their events arrive.
+pub struct ClassWatcher {
+    state: Arc<Mutex<WatchState>>,
+    _watcher: Arc<Mutex<RecommendedWatcher>>,
+}
+
+impl ClassWatcher {
+    pub fn new<P: AsRef<Path>>(workspace_path: P) -> Result<Self, String> {
+        let workspace_path = workspace_path.as_ref().to_path_buf();
+        let locator = FileLocator::new(&workspace_path);
+
+        let index = locator
+            .load_index()
+            .map_err(|e| format!("Failed to index workspace: {}", e))?;
+
+        let mut state = WatchState::default();
+        for (path, classes) in index.entries() {
+            state.insert(path.to_string(), classes.to_vec());
+        }
+        let state = Arc::new(Mutex::new(state));
+
+        let directories: Vec<PathBuf> = locator.directories(&workspace_path).collect();
+        let mut ignores = IgnoreFiles::default();
+        for dir in &directories {
+            ignores.load(dir);
+        }
+
+        // Events are applied on a thread of their own, since watches cannot
+        // be added from within the event handler
+        let (sender, events) = mpsc::channel();
+        let mut watcher = recommended_watcher(move |event: notify::Result<Event>| {
+            let _ = sender.send(event);
+        })
+        .map_err(|e| format!("Failed to create watcher: {}", e))?;
+        for dir in &directories {
+            watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(|e| format!("Failed to watch workspace: {}", e))?;
+        }
+        let watcher = Arc::new(Mutex::new(watcher));
+
+        let handler_state = Arc::clone(&state);
+        // Weak, so dropping the watcher closes the channel and ends the thread
+        let handler_watcher = Arc::downgrade(&watcher);
+        thread::spawn(move || {
+            for event in events {
+                let Ok(event) = event else {
+                    continue;
+                };
+                if matches!(
+                    event.kind,
+                    EventKind::Access(_) | EventKind::Modify(ModifyKind::Metadata(_))
+                ) {
+                    continue;
+                }
+                let (Some(watcher), Ok(mut state)) =
+                    (handler_watcher.upgrade(), handler_state.lock())
+                else {
+                    break;
+                };
+                for path in &event.paths {
+                    let added = apply_change(&mut state, &locator, &mut ignores, path);
+                    if let Ok(mut watcher) = watcher.lock() {
+                        for dir in added {
+                            let _ = watcher.watch(&dir, RecursiveMode::NonRecursive);
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            state,
+            _watcher: watcher,
+        })
+    }
+
+    /// Look up the first file (in path order) declaring the class
+    pub fn lookup(&self, class_name: &str) -> Option<String> {
+        let state = self.state.lock().ok()?;
+        state.lookup(class_name).map(str::to_string)
+    }
+}
+
+/// Re-index a single changed path
+///
+/// Existing searchable files are re-read, and changed ignore files are
+/// reloaded. Existing directories, such as the new side of a rename or a
+/// tree written by a checkout, have all their files indexed. Anything else that no longer exists is treated as a deletion
+/// (which also covers the old side of a rename and removed directories).
+///
+/// # Returns
+/// The directories that appeared and need watching
+fn apply_change(
+    state: &mut WatchState,
+    locator: &FileLocator,
+    ignores: &mut IgnoreFiles,
+    path: &Path,
+) -> Vec<PathBuf> {
+    if path.is_file() {
+        let file_name = path.file_name().and_then(|name| name.to_str());
+        if let (Some(name), Some(dir)) = (file_name, path.parent()) {
+            if IGNORE_FILES.contains(&name) {
+                ignores.load(dir);
+                return Vec::new();
+            }
+        }
+        if ignores.is_ignored(path, false) || !locator.is_searchable(path) {
+            return Vec::new();
+        }
+
+        let key = path.to_string_lossy().to_string();
+        match locator.read_source(path) {
+            Some(content) => state.insert(key, extract_class_names(&content)),
+            None => state.remove(&key),
+        }
+    } else if path.is_dir() {
+        if ignores.is_ignored(path, true) || !locator.is_searchable_dir(path) {
+            return Vec::new();
+        }
+
+        let directories: Vec<PathBuf> = locator.directories(path).collect();
+        for dir in &directories {
+            ignores.load(dir);
+        }
+        for file in locator.typescript_files_in(path).filter_map(Result::ok) {
+            if let Some(content) = locator.read_source(&file) {
+                state.insert(
+                    file.to_string_lossy().to_string(),
+                    extract_class_names(&content),
+                );
+            }
+        }
+        return directories;
+    } else if !path.exists() {
+        state.remove_tree(path);
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_workspace;
+    use std::fs;
+
+    #[test]
+    fn test_apply_change() {
+        let workspace = temp_workspace("watch");
+        fs::create_dir_all(workspace.join("src")).unwrap();
+
+        let locator = FileLocator::new(&workspace);
+        let mut ignores = IgnoreFiles::default();
+        let mut state = WatchState::default();
+
+        let file = workspace.join("src/user.service.ts");
+        fs::write(&file, "export class UserService {}").unwrap();
+        apply_change(&mut state, &locator, &mut ignores, &file);
+        assert_eq!(state.lookup("UserService"), file.to_str());
+
+        // Non-TypeScript files are ignored
+        let readme = workspace.join("README.md");
+        fs::write(&readme, "class NotCode").unwrap();
+        apply_change(&mut state, &locator, &mut ignores, &readme);
+        assert_eq!(state.lookup("NotCode"), None);
+
+        // Removing the directory drops its classes
+        fs::remove_dir_all(workspace.join("src")).unwrap();
+        apply_change(&mut state, &locator, &mut ignores, &workspace.join("src"));
+        assert_eq!(state.lookup("UserService"), None);
+    }
+
+    #[test]
+    fn test_watched_directories() {
+        let workspace = temp_workspace("watch-dirs");
+        for dir in [
+            "src/app",
+            "src/generated",
+            "node_modules/lib",
+            "dist",
+            ".git",
+        ] {
+            fs::create_dir_all(workspace.join(dir)).unwrap();
+        }
+        fs::write(workspace.join("src/.gitignore"), "generated/\n").unwrap();
+
+        let locator = FileLocator::new(&workspace);
+        let directories: BTreeSet<PathBuf> = locator.directories(&workspace).collect();
+        assert_eq!(
+            directories,
+            BTreeSet::from([
+                workspace.to_path_buf(),
+                workspace.join("src"),
+                workspace.join("src/app")
+            ])
+        );
+
+        // The nested ignore file keeps new files in its directory out
+        let mut ignores = IgnoreFiles::default();
+        for dir in &directories {
+            ignores.load(dir);
+        }
+        let mut state = WatchState::default();
+        let file = workspace.join("src/generated/api.ts");
+        fs::write(&file, "export class ApiClient {}").unwrap();
+        apply_change(&mut state, &locator, &mut ignores, &file);
+        assert_eq!(state.lookup("ApiClient"), None);
+    }
+
+    #[test]
+    fn test_renamed_directory() {
+        let workspace = temp_workspace("watch-rename");
+        fs::create_dir_all(workspace.join("src/a/nested")).unwrap();
+        fs::write(workspace.join("src/a/user.ts"), "export class User {}").unwrap();
+        fs::write(
+            workspace.join("src/a/nested/role.ts"),
+            "export class Role {}",
+        )
+        .unwrap();
+
+        let locator = FileLocator::new(&workspace);
+        let mut ignores = IgnoreFiles::default();
+        let mut state = WatchState::default();
+        apply_change(&mut state, &locator, &mut ignores, &workspace.join("src/a"));
+        assert_eq!(
+            state.lookup("User"),
+            workspace.join("src/a/user.ts").to_str()
+        );
+
+        // Both sides of the rename are reported
+        fs::rename(workspace.join("src/a"), workspace.join("src/b")).unwrap();
+        apply_change(&mut state, &locator, &mut ignores, &workspace.join("src/a"));
+        let added = apply_change(&mut state, &locator, &mut ignores, &workspace.join("src/b"));
+        assert_eq!(
+            state.lookup("User"),
+            workspace.join("src/b/user.ts").to_str()
+        );
+        assert_eq!(
+            state.lookup("Role"),
+            workspace.join("src/b/nested/role.ts").to_str()
+        );
+        assert_eq!(
+            added,
+            [workspace.join("src/b"), workspace.join("src/b/nested")]
+        );
+    }
+
+    #[test]
+    fn test_watches_new_directories() {
+        let workspace = temp_workspace("watch-live");
+
+        let watcher = ClassWatcher::new(&workspace).unwrap();
+        let staging = temp_workspace("watch-staging");
+        fs::create_dir_all(staging.join("nested")).unwrap();
+        fs::write(staging.join("nested/user.ts"), "export class User {}").unwrap();
+        fs::rename(&staging, workspace.join("src")).unwrap();
+
+        let found = (0..100).any(|_| {
+            thread::sleep(std::time::Duration::from_millis(20));
+            watcher.lookup("User").is_some()
+        });
+        assert!(found);
+
+        // The directory that arrived is watched too
+        fs::write(workspace.join("src/nested/role.ts"), "export class Role {}").unwrap();
+        let found = (0..100).any(|_| {
+            thread::sleep(std::time::Duration::from_millis(20));
+            watcher.lookup("Role").is_some()
+        });
+        assert!(found);
+    }
+}
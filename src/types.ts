@@ -16,6 +16,356 @@ export interface PerformanceMessage {
 export interface FileLocation {
   filePath: string;
   found: boolean;
+  line?: number;
+  column?: number;
+  spanStart?: number;
+  spanEnd?: number;
+  truncated: boolean;
+  stats?: SearchStats;
+  fuzzyCandidates?: FuzzyMatch[];
+  superClass?: string;
+  interfaces?: string[];
+}
+
+/**
+ * A class whose name resembles the one searched natively
+ */
+export interface FuzzyMatch {
+  filePath: string;
+  className: string;
+  score: number;
+}
+
+/**
+ * Statistics of a finished native lookup
+ */
+export interface SearchStats {
+  filesVisited: number;
+  filesRead: number;
+  bytesRead: number;
+  filesReindexed: number;
+  cacheHits: number;
+  cacheMisses: number;
+  cacheHitRate: number;
+  elapsedMs: number;
+}
+
+/**
+ * Progress of a running native workspace scan
+ */
+export interface SearchProgress {
+  filesScanned: number;
+  currentDirectory: string;
+  matches: number;
+  skippedLarge: number;
+  skippedBinary: number;
+}
+
+/**
+ * Optional settings for native file location
+ */
+export interface LocateOptions {
+  verifyAst?: boolean;
+  globs?: string[];
+  includeDeclaration?: boolean;
+  fuzzy?: boolean;
+  fuzzyThreshold?: number;
+  project?: string;
+  projectScope?: "restrict" | "prioritize";
+  maxFileSize?: number;
+  scanDeclarationFiles?: boolean;
+  persistMatchCache?: boolean;
+  maxResults?: number;
+  timeoutMs?: number;
+  searchNodeModules?: boolean;
+  followSymlinks?: boolean;
+  maxDepth?: number;
+  skipDirs?: string[];
+  extraSkipDirs?: string[];
+  /** Only accepted by asynchronous searches; synchronous calls reject it */
+  onProgress?: (progress: SearchProgress) => void;
+}
+
+/**
+ * Candidate file for a class, ranked by the Rust native module
+ */
+export interface FileCandidate {
+  filePath: string;
+  score: number;
+  reasons: string[];
+}
+
+/**
+ * Component location result from Rust native module
+ */
+export interface ComponentLocation {
+  filePath: string;
+  className: string;
+  found: boolean;
+}
+
+/**
+ * Class found by a workspace-wide native search (by decorator, base class, ...)
+ */
+export interface ClassLocation {
+  filePath: string;
+  className: string;
+}
+
+/**
+ * Exported class listed by a native workspace snapshot
+ */
+export interface ClassSummary {
+  filePath: string;
+  className: string;
+  decorators: string[];
+  methodCount: number;
+}
+
+/**
+ * Class method location result from Rust native module
+ */
+export interface ClassMethodLocation {
+  filePath: string;
+  line: number;
+  column: number;
+  found: boolean;
+}
+
+/**
+ * Class method listed by the Rust native module
+ */
+export interface ClassMethodInfo {
+  name: string;
+  kind: "method" | "getter" | "setter" | "constructor";
+  line: number;
+  endLine: number;
+  isStatic: boolean;
+  visibility: "public" | "protected" | "private";
+  isAsync: boolean;
+}
+
+/**
+ * Component input or output listed by the Rust native module
+ */
+export interface ComponentBindingInfo {
+  name: string;
+  alias?: string;
+  direction: "input" | "output" | "model";
+  line: number;
+  typeText?: string;
+  required: boolean;
+}
+
+/**
+ * Angular lifecycle hook detected by the Rust native module
+ */
+export interface LifecycleHookInfo {
+  name: string;
+  line: number;
+  endLine: number;
+  declaresInterface: boolean;
+}
+
+/**
+ * Call made from a method body, extracted by the Rust native module
+ */
+export interface MethodCallInfo {
+  callee: string;
+  name: string;
+  line: number;
+  column: number;
+}
+
+/**
+ * Size and complexity of a method, measured by the Rust native module
+ */
+export interface MethodMetrics {
+  cyclomaticComplexity: number;
+  statementCount: number;
+  maxNestingDepth: number;
+  lineCount: number;
+}
+
+/**
+ * Original TypeScript position of a bundled position, mapped natively
+ */
+export interface SourcePosition {
+  filePath: string;
+  source: string;
+  line: number;
+  column: number;
+  name?: string;
+}
+
+/**
+ * Template binding found by the Rust native module
+ */
+export interface TemplateBindingInfo {
+  filePath: string;
+  kind: "event" | "property" | "twoWay" | "structural" | "interpolation";
+  name?: string;
+  expression: string;
+  line: number;
+  column: number;
+}
+
+/**
+ * Change-detection anti-pattern found in a template by the Rust native module
+ */
+export interface TemplateWarningInfo {
+  kind: "functionCall" | "missingTrackBy" | "largeStaticList";
+  identifier: string;
+  expression: string;
+  line: number;
+  column: number;
+}
+
+/**
+ * Class property location result from Rust native module
+ */
+export interface PropertyLocation {
+  line: number;
+  column: number;
+  endLine: number;
+  found: boolean;
+}
+
+/**
+ * Subscription that is never released, found by the Rust native module
+ */
+export interface UnmanagedSubscriptionInfo {
+  filePath: string;
+  className: string;
+  memberName: string;
+  source: string;
+  line: number;
+  column: number;
+}
+
+/**
+ * Runtime change detection cost of a component, recorded by the probe
+ */
+export interface ComponentRuntimeCost {
+  className: string;
+  changeDetectionCount: number;
+  durationMs: number;
+}
+
+/**
+ * Reason a component cannot switch to OnPush as is
+ */
+export interface OnPushBlockerInfo {
+  reason: string;
+  line: number;
+}
+
+/**
+ * Default change detection component assessed for OnPush
+ */
+export interface OnPushCandidateInfo {
+  filePath: string;
+  className: string;
+  line: number;
+  eligible: boolean;
+  blockers: OnPushBlockerInfo[];
+  changeDetectionCount?: number;
+  durationMs?: number;
+}
+
+/**
+ * RxJS operator chain of a method, found by the Rust native module
+ */
+export interface PipeChainInfo {
+  source: string;
+  operators: PipeOperatorInfo[];
+  line: number;
+  column: number;
+}
+
+/**
+ * Operator of an RxJS `pipe(...)` call
+ */
+export interface PipeOperatorInfo {
+  name: string;
+  arity: number;
+  line: number;
+}
+
+/**
+ * Symbol imported by a standalone component, found by the Rust native module
+ */
+export interface ComponentImportInfo {
+  symbol: string;
+  source?: string;
+  spread: boolean;
+  line: number;
+  column: number;
+}
+
+/**
+ * Class and member containing a source position
+ */
+export interface PositionContextInfo {
+  className: string;
+  memberName?: string;
+  memberKind?: "method" | "getter" | "setter" | "constructor";
+  methodKey?: string;
+  callback?: string;
+  identifier?: string;
+}
+
+/**
+ * Method opted out of profiling with an `@xray:ignore` comment
+ */
+export interface IgnoredMethodInfo {
+  className: string;
+  methodName: string;
+  line: number;
+  reason?: string;
+}
+
+/**
+ * Anonymous callback of a class member, named by the Rust native module
+ */
+export interface AnonymousCallbackInfo {
+  name: string;
+  className: string;
+  memberName: string;
+  callee: string;
+  index: number;
+  line: number;
+  column: number;
+  endLine: number;
+}
+
+/**
+ * Options of the native method instrumentation
+ */
+export interface InstrumentOptions {
+  decorator?: string;
+  decoratorImport?: string;
+}
+
+/**
+ * Text replacement between two 1-based positions
+ */
+export interface SourceEdit {
+  startLine: number;
+  startColumn: number;
+  endLine: number;
+  endColumn: number;
+  text: string;
+}
+
+/**
+ * Source rewritten by the native method instrumentation
+ */
+export interface InstrumentResult {
+  source: string;
+  reversePatch: SourceEdit[];
+  instrumented: string[];
+  skipped: string[];
 }
 
 /**
@@ -23,7 +373,33 @@ export interface FileLocation {
  */
 export interface MethodLocation {
   line: number;
+  column: number;
+  endLine: number;
+  endColumn: number;
   found: boolean;
+  kind?: "method" | "getter" | "setter" | "constructor";
+  overloadLines: number[];
+  decorators: MethodDecoratorInfo[];
+  diagnostics: ParseDiagnosticInfo[];
+  docComment?: string;
+}
+
+/**
+ * Syntax error reported by tolerant native parsing
+ */
+export interface ParseDiagnosticInfo {
+  message: string;
+  line: number;
+  column: number;
+}
+
+/**
+ * Decorator applied to a method, reported by the Rust native module
+ */
+export interface MethodDecoratorInfo {
+  name: string;
+  text: string;
+  line: number;
 }
 
 /**
@@ -65,6 +441,14 @@ export interface CallStackNode {
   selfTime: number;
   filePath?: string;
   line?: number;
+  /** Custom dimensions such as route, zone, componentId or httpUrl */
+  tags?: Record<string, unknown>;
+  /** Bytes allocated during the call, children included */
+  allocatedBytes?: number;
+  /** Asynchronous context the call runs in */
+  asyncId?: number;
+  /** Context that scheduled `asyncId`; links root calls to their logical parent */
+  triggerAsyncId?: number;
 }
 
 /**
@@ -74,6 +458,37 @@ export interface FlameGraphData {
   nodes: FlameGraphNode[];
   totalDuration: number;
   rootCallId?: string;
+  /** Calls whose parent is missing, shown under an `(orphaned)` root */
+  orphanedCalls: number;
+  /** Problems found in the call data, such as broken parent cycles */
+  warnings: string[];
+  /** Longest idle times between consecutive children, longest first */
+  largestGaps: FlameGraphGap[];
+  summary: FlameGraphSummary;
+  matchedTime?: number;
+}
+
+/**
+ * Shape of a flame graph, to pick the initial zoom and spot deep recursion
+ */
+export interface FlameGraphSummary {
+  maxDepth: number;
+  nodeCount: number;
+  /** Self value of the nodes at each depth, roots first */
+  selfTimeByDepth: number[];
+  /** Call node with the largest value, the deepest one on ties */
+  widestFrame?: { id: string; name: string; value: number; depth: number };
+}
+
+/**
+ * Idle time between two consecutive children of a call
+ */
+export interface FlameGraphGap {
+  callId: string;
+  name: string;
+  start: number;
+  end: number;
+  duration: number;
 }
 
 /**
@@ -81,6 +496,8 @@ export interface FlameGraphData {
  */
 export interface FlameGraphNode {
   id: string;
+  /** First call of the node, with the `stableIds` option */
+  callId?: string;
   name: string;
   value: number;
   selfValue: number;
@@ -89,6 +506,118 @@ export interface FlameGraphNode {
   filePath?: string;
   line?: number;
   percentage: number;
+  count: number;
+  matched?: boolean;
+  truncatedChildren?: number;
+  truncatedTime?: number;
+  /** Tags of the call, or those shared by every merged call */
+  tags?: Record<string, unknown>;
+  /**
+   * Coloring hint: 0 app, 1 Angular, 2 RxJS, 3 zone.js, 4 other packages,
+   * 5 synthetic frames
+   */
+  category: number;
+  /** Milliseconds between consecutive children not spent in any child */
+  gapTime: number;
+  /** Durations of the merged calls, in aggregated mode */
+  stats?: { min: number; max: number; avg: number; p95: number };
+  /** Extent for icicle and sunburst charts, with the `layout` option */
+  layout?: { x0: number; x1: number; startAngle: number; endAngle: number };
+  /** Continues asynchronous work of its parent rather than being called by it */
+  asyncContinuation?: boolean;
+  /** Recordings the node occurs in, for merged recordings */
+  runCount?: number;
+}
+
+/**
+ * Options of the native flame graph construction
+ */
+export interface FlameGraphOptions {
+  childOrder?: "chronological" | "left-heavy";
+  aggregate?: boolean;
+  startTime?: number;
+  endTime?: number;
+  minPercentage?: number;
+  minDurationMs?: number;
+  search?: string;
+  searchRegex?: boolean;
+  maxDepth?: number;
+  virtualRoot?: boolean;
+  weight?: "duration" | "count" | "allocation";
+  layout?: boolean;
+  percentOfParent?: boolean;
+  stableIds?: boolean;
+  maxChildren?: number;
+  includePatterns?: string[];
+  excludePatterns?: string[];
+  dropElidedTime?: boolean;
+}
+
+/**
+ * Calls on a timeline, from the native flame chart construction
+ */
+export interface FlameChartData {
+  /** Sorted by lane, then depth-first */
+  events: FlameChartEvent[];
+  /** Rows needed by each lane */
+  laneDepths: number[];
+  startTime: number;
+  endTime: number;
+  orphanedCalls: number;
+  warnings: string[];
+}
+
+/**
+ * One call at its recorded time
+ */
+export interface FlameChartEvent {
+  id: string;
+  name: string;
+  start: number;
+  end: number;
+  duration: number;
+  /** Depth below the root of the lane */
+  depth: number;
+  /** Lane of the root call, overlapping roots getting separate lanes */
+  lane: number;
+  parentId?: string;
+  category: number;
+  filePath?: string;
+  line?: number;
+  tags?: Record<string, unknown>;
+}
+
+/**
+ * Options of the native flame chart construction
+ */
+export interface FlameChartOptions {
+  startTime?: number;
+  endTime?: number;
+}
+
+/**
+ * Options of the native SVG flame graph rendering
+ */
+export interface SvgOptions {
+  /** Image width in pixels, 1200 by default */
+  width?: number;
+  /** Frame height in pixels, 16 by default */
+  frameHeight?: number;
+  title?: string;
+}
+
+/**
+ * Method ranked by the time spent in its own code
+ */
+export interface Hotspot {
+  name: string;
+  className: string;
+  methodName: string;
+  selfTime: number;
+  callCount: number;
+  averageSelfTime: number;
+  filePath?: string;
+  line?: number;
 }
 
 /**
@@ -150,13 +679,228 @@ export interface MethodPerformanceDiff {
   diffType: "improved" | "regressed" | "new" | "removed" | "unchanged";
 }
 
+/**
+ * Watcher-backed class locator from Rust native module
+ */
+export interface WorkspaceWatcher {
+  locateFileCached(className: string): FileLocation;
+  close(): void;
+}
+
+/**
+ * Native locator that searches near recent results first
+ */
+export interface WorkspaceLocator {
+  locateFile(className: string): FileLocation;
+}
+
+/**
+ * Native flame graph input streamed in chunks of binary call records
+ */
+export interface FlameGraphBuilder {
+  addChunk(records: Buffer): number;
+  readonly callCount: number;
+  finish(options?: FlameGraphOptions): string;
+}
+
+/**
+ * Cancellation handle for asynchronous native searches
+ */
+export interface SearchCancellation {
+  cancel(): void;
+  readonly isCancelled: boolean;
+}
+
 /**
  * Native module interface (Rust bindings)
  */
 export interface NativeModule {
-  locateFile(className: string, workspacePath: string): FileLocation;
-  parseMethod(fileContent: string, methodName: string): MethodLocation;
-  buildFlameGraphData(callStackJson: string): string;
+  locateFile(
+    className: string,
+    workspacePath: string,
+    options?: LocateOptions,
+  ): FileLocation;
+  locateFileAsync(
+    className: string,
+    workspacePath: string,
+    options?: LocateOptions,
+    cancellation?: SearchCancellation,
+    signal?: AbortSignal,
+  ): Promise<FileLocation>;
+  SearchCancellation: new () => SearchCancellation;
+  locateFiles(
+    classNames: string[],
+    workspacePath: string,
+    options?: LocateOptions,
+  ): FileLocation[];
+  locateFileAll(
+    className: string,
+    workspacePath: string,
+    options?: LocateOptions,
+  ): FileCandidate[];
+  locateSymbol(
+    symbolName: string,
+    kind: "class" | "interface" | "enum" | "type" | "function" | "const",
+    workspacePath: string,
+  ): FileLocation;
+  locateComponentBySelector(
+    selector: string,
+    workspacePath: string,
+  ): ComponentLocation;
+  locateComponentForTemplate(
+    templatePath: string,
+    workspacePath: string,
+  ): ComponentLocation;
+  locateByDecorator(
+    decoratorName: string,
+    workspacePath: string,
+  ): ClassLocation[];
+  findSubclasses(baseClassName: string, workspacePath: string): ClassLocation[];
+  enumerateClasses(workspacePath: string): ClassSummary[];
+  findOnPushCandidates(
+    workspacePath: string,
+    runtimeCosts?: ComponentRuntimeCost[],
+  ): OnPushCandidateInfo[];
+  findUnmanagedSubscriptions(workspacePath: string): UnmanagedSubscriptionInfo[];
+  locateFileWithTsconfig(
+    className: string,
+    importPath: string,
+    workspacePath: string,
+  ): FileLocation;
+  locateSpecForClass(className: string, workspacePath: string): FileLocation;
+  locateFileFromBarrel(
+    className: string,
+    barrelPath: string,
+    workspacePath: string,
+  ): FileLocation;
+  WorkspaceWatcher: new (workspacePath: string) => WorkspaceWatcher;
+  WorkspaceLocator: new (
+    workspacePath: string,
+    options?: LocateOptions,
+    capacity?: number,
+  ) => WorkspaceLocator;
+  locateMethod(
+    className: string,
+    methodName: string,
+    workspacePath: string,
+  ): ClassMethodLocation;
+  listClassMethods(fileContent: string, className: string): ClassMethodInfo[];
+  listComponentBindings(
+    fileContent: string,
+    className: string,
+  ): ComponentBindingInfo[];
+  detectLifecycleHooks(
+    fileContent: string,
+    className: string,
+  ): LifecycleHookInfo[];
+  parseMethod(
+    fileContent: string,
+    methodName: string,
+    kind?: "method" | "getter" | "setter" | "constructor",
+    className?: string,
+  ): MethodLocation;
+  parseMethodTolerant(
+    fileContent: string,
+    methodName: string,
+    kind?: "method" | "getter" | "setter" | "constructor",
+    className?: string,
+  ): MethodLocation;
+  parseMethods(fileContent: string, methodNames: string[]): MethodLocation[];
+  findPropertyLine(
+    fileContent: string,
+    className: string,
+    propertyName: string,
+  ): PropertyLocation;
+  extractMethodCalls(
+    fileContent: string,
+    className: string,
+    methodName: string,
+  ): MethodCallInfo[];
+  extractPipeChains(
+    fileContent: string,
+    className: string,
+    methodName: string,
+  ): PipeChainInfo[];
+  methodStructuralHash(
+    fileContent: string,
+    className: string,
+    methodName: string,
+  ): string | null;
+  findComponentImports(
+    fileContent: string,
+    className: string,
+  ): ComponentImportInfo[];
+  extractDecoratorMetadata(
+    fileContent: string,
+    className: string,
+    decoratorName: string,
+  ): string | null;
+  findIgnoredMethods(
+    fileContent: string,
+    className?: string,
+  ): IgnoredMethodInfo[];
+  findIdentifierAt(
+    fileContent: string,
+    line: number,
+    column: number,
+  ): PositionContextInfo | null;
+  listAnonymousCallbacks(
+    fileContent: string,
+    className: string,
+  ): AnonymousCallbackInfo[];
+  nameAnonymousCallback(
+    fileContent: string,
+    line: number,
+    column: number,
+  ): AnonymousCallbackInfo | null;
+  measureMethod(
+    fileContent: string,
+    className: string,
+    methodName: string,
+  ): MethodMetrics | null;
+  findTemplateBindings(
+    templatePath: string,
+    methodName: string,
+    workspacePath: string,
+  ): TemplateBindingInfo[];
+  analyzeTemplateBindings(templateContent: string): TemplateWarningInfo[];
+  mapToSource(
+    mapPath: string,
+    line: number,
+    column: number,
+    workspacePath: string,
+  ): SourcePosition | null;
+  instrumentClassMethods(
+    fileContent: string,
+    className: string,
+    methodNames: string[],
+    options?: InstrumentOptions,
+  ): InstrumentResult;
+  stripInstrumentation(fileContent: string): string;
+  /**
+   * Accepts the calls as a JSON array or as binary call records (see
+   * `decode_call_records` in native/src/flame_graph.rs)
+   */
+  buildFlameGraphData(
+    callStack: string | Buffer,
+    options?: FlameGraphOptions,
+  ): string;
+  /** Aggregated flame graph of several recordings, one call array each */
+  mergeCallStacks(recordingsJson: string, options?: FlameGraphOptions): string;
+  FlameGraphBuilder: new () => FlameGraphBuilder;
+  exportFoldedStacks(callStackJson: string): string;
+  exportSpeedscope(callStackJson: string): string;
+  exportTraceEvents(callStackJson: string): string;
+  topSelfTime(callStackJson: string, limit: number): string;
+  buildFlameChartData(
+    callStackJson: string,
+    options?: FlameChartOptions,
+  ): string;
+  renderFlameGraphSvg(
+    callStackJson: string,
+    options?: FlameGraphOptions,
+    svgOptions?: SvgOptions,
+  ): string;
   comparePerformanceSnapshots(
     baselineJson: string,
     currentJson: string,